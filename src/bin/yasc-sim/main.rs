@@ -0,0 +1,277 @@
+//! Headless simulator that plays many games between bots and reports their results. It is used
+//! to balance units: every game is generated from its own seed, so any game can be replayed.
+//!
+//! Usage: `yasc-sim [--games N] [--seed N] [--players N] [--radius N] [--bots NAME,...]
+//! [--max-turns N] [--jobs N] [--format csv|json]`
+extern crate num_cpus;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate yasc;
+
+use std::env;
+use std::io::{self, Write};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+use yasc::bots::{self, Bot, GameSummary};
+use yasc::init::generator::{generate, MapGenerationError};
+
+const DEFAULT_GAMES_NUM: u64 = 100;
+const DEFAULT_PLAYERS_NUM: u8 = 2;
+const DEFAULT_RADIUS: u32 = 6;
+const DEFAULT_BOTS: &str = "greedy,random";
+const DEFAULT_MAX_TURNS: u32 = 200;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Options {
+    games_num: u64,
+    first_seed: u64,
+    players_num: u8,
+    radius: u32,
+    /// Names of bots for each seat. A single bot takes all seats.
+    bots: Vec<String>,
+    max_turns: u32,
+    jobs: usize,
+    format: Format,
+}
+
+/// Result of one simulated game
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+struct GameRecord {
+    seed: u64,
+    #[serde(flatten)]
+    summary: GameSummary,
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    value
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| format!("{} expects a number", name))
+}
+
+fn parse_options<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        games_num: DEFAULT_GAMES_NUM,
+        first_seed: 0,
+        players_num: DEFAULT_PLAYERS_NUM,
+        radius: DEFAULT_RADIUS,
+        bots: DEFAULT_BOTS.split(',').map(String::from).collect(),
+        max_turns: DEFAULT_MAX_TURNS,
+        jobs: num_cpus::get(),
+        format: Format::Csv,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => options.games_num = parse_number(&arg, args.next())?,
+            "--seed" => options.first_seed = parse_number(&arg, args.next())?,
+            "--players" => options.players_num = parse_number(&arg, args.next())?,
+            "--radius" => options.radius = parse_number(&arg, args.next())?,
+            "--max-turns" => options.max_turns = parse_number(&arg, args.next())?,
+            "--jobs" => options.jobs = parse_number(&arg, args.next())?,
+            "--bots" => {
+                options.bots = args
+                    .next()
+                    .ok_or_else(|| String::from("--bots expects a list of bot names"))?
+                    .split(',')
+                    .map(String::from)
+                    .collect();
+            }
+            "--format" => {
+                options.format = match args.next().as_deref() {
+                    Some("csv") => Format::Csv,
+                    Some("json") => Format::Json,
+                    _ => return Err(String::from("--format expects csv or json")),
+                }
+            }
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    if options.players_num < 2 {
+        return Err(String::from("--players expects at least 2 players"));
+    }
+    if options.jobs == 0 {
+        return Err(String::from("--jobs expects at least 1 job"));
+    }
+    if options.bots.len() != 1 && options.bots.len() != options.players_num as usize {
+        return Err(String::from(
+            "--bots expects either one bot or a bot for each player",
+        ));
+    }
+    if let Some(name) = options.bots.iter().find(|n| bots::create(n, 0).is_none()) {
+        return Err(format!("Unknown bot {}", name));
+    }
+    Ok(options)
+}
+
+/// Play one game on a map generated from the seed
+fn run_game(options: &Options, seed: u64) -> Result<GameRecord, MapGenerationError> {
+    let mut engine = generate(seed, options.radius, options.players_num)?;
+    let mut seats: Vec<Box<dyn Bot>> = (0..options.players_num as usize)
+        .map(|seat| {
+            let name = &options.bots[seat % options.bots.len()];
+            let bot_seed = seed
+                .wrapping_mul(u64::from(options.players_num))
+                .wrapping_add(seat as u64);
+            bots::create(name, bot_seed).unwrap()
+        }).collect();
+
+    let summary = bots::play(&mut engine, &mut seats, options.max_turns);
+    Ok(GameRecord { seed, summary })
+}
+
+/// Play all games using `options.jobs` threads. Records are returned sorted by seed, so the
+/// result does not depend on the number of threads.
+fn simulate(options: &Options) -> Result<Vec<GameRecord>, (u64, MapGenerationError)> {
+    let options = Arc::new(options.clone());
+    let next_game = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel();
+
+    let workers: Vec<_> = (0..options.jobs)
+        .map(|_| {
+            let options = Arc::clone(&options);
+            let next_game = Arc::clone(&next_game);
+            let sender = sender.clone();
+            thread::spawn(move || loop {
+                let game = next_game.fetch_add(1, Ordering::SeqCst) as u64;
+                if game >= options.games_num {
+                    break;
+                }
+                let seed = options.first_seed + game;
+                let result = run_game(&options, seed).map_err(|e| (seed, e));
+                if sender.send(result).is_err() {
+                    break;
+                }
+            })
+        }).collect();
+    drop(sender);
+
+    let mut records = receiver.iter().collect::<Result<Vec<_>, _>>()?;
+    for worker in workers {
+        worker.join().expect("Simulation thread should not panic");
+    }
+    records.sort_by_key(|r| r.seed);
+    Ok(records)
+}
+
+fn write_csv<W: Write>(out: &mut W, records: &[GameRecord]) -> io::Result<()> {
+    writeln!(
+        out,
+        "seed,turns,seat,player_id,bot,won,units_bought,starvation_deaths"
+    )?;
+    for record in records {
+        for (seat, player) in record.summary.players.iter().enumerate() {
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{},{}",
+                record.seed,
+                record.summary.turns,
+                seat,
+                player.player_id,
+                player.bot,
+                record.summary.winner == Some(player.player_id),
+                player.units_bought,
+                player.starvation_deaths,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_json<W: Write>(out: &mut W, records: &[GameRecord]) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, records)?;
+    writeln!(out)
+}
+
+fn main() {
+    let options = parse_options(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!(
+            "Usage: yasc-sim [--games N] [--seed N] [--players N] [--radius N] \
+             [--bots NAME,...] [--max-turns N] [--jobs N] [--format csv|json]"
+        );
+        process::exit(2);
+    });
+
+    let records = simulate(&options).unwrap_or_else(|(seed, e)| {
+        eprintln!("Cannot generate a map for seed {}: {:?}", seed, e);
+        process::exit(1);
+    });
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let res = match options.format {
+        Format::Csv => write_csv(&mut out, &records),
+        Format::Json => write_json(&mut out, &records),
+    };
+    if let Err(e) = res {
+        eprintln!("Cannot write results: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_options, simulate, write_csv, Format, Options};
+
+    fn options(args: &[&str]) -> Result<Options, String> {
+        parse_options(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn options_are_parsed() {
+        let options = options(&[
+            "--games", "3", "--players", "3", "--bots", "greedy,random,greedy", "--format",
+            "json",
+        ]).unwrap();
+
+        assert_eq!(options.games_num, 3);
+        assert_eq!(options.players_num, 3);
+        assert_eq!(options.bots, vec!["greedy", "random", "greedy"]);
+        assert_eq!(options.format, Format::Json);
+    }
+
+    #[test]
+    fn options_with_wrong_bots_are_rejected() {
+        assert!(options(&["--bots", "nobody"]).is_err());
+        assert!(options(&["--players", "3", "--bots", "greedy,random"]).is_err());
+    }
+
+    #[test]
+    fn results_do_not_depend_on_jobs_num() {
+        let one = options(&["--games", "4", "--radius", "4", "--max-turns", "10", "--jobs", "1"]);
+        let four = options(&["--games", "4", "--radius", "4", "--max-turns", "10", "--jobs", "4"]);
+
+        let one = simulate(&one.unwrap()).unwrap();
+        let four = simulate(&four.unwrap()).unwrap();
+
+        assert_eq!(one, four);
+        assert_eq!(one.iter().map(|r| r.seed).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn csv_has_a_row_for_each_player() {
+        let options = options(&["--games", "2", "--radius", "4", "--max-turns", "5"]).unwrap();
+        let records = simulate(&options).unwrap();
+
+        let mut out = Vec::new();
+        write_csv(&mut out, &records).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 1 + 2 * 2);
+        assert!(lines[0].starts_with("seed,turns,"));
+        assert!(lines[1].starts_with("0,"));
+    }
+}
@@ -0,0 +1,427 @@
+//! State of the hot-seat game client and handling of the keyboard input
+use termion::event::Key;
+
+use yasc::game::{Coord, GameEngine, PlayerAction, UnitType};
+use yasc::hints;
+use yasc::saves::SavedGamesCatalog;
+
+use crate::render::column;
+
+/// Units that can be bought with number keys, in order of keys
+const PURCHASABLE: [UnitType; 9] = [
+    UnitType::Militia,
+    UnitType::Soldier,
+    UnitType::Knight,
+    UnitType::GreatKnight,
+    UnitType::Tower,
+    UnitType::Bridge,
+    UnitType::Boat,
+    UnitType::Archer,
+    UnitType::Engineer,
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Nothing is selected
+    Idle,
+    /// A tile of active player is selected, the next action will use it as a source
+    Selected(Coord),
+    /// Player has chosen a unit to buy for the region of selected tile
+    Buying(Coord, UnitType),
+    /// Player is typing a name for saved game
+    Saving(String),
+}
+
+pub struct App {
+    engine: GameEngine,
+    catalog: SavedGamesCatalog,
+    cursor: Coord,
+    /// Column cursor tries to stay in when moving between rows
+    column: i32,
+    mode: Mode,
+    message: Option<String>,
+}
+
+impl App {
+    pub fn new(engine: GameEngine, catalog: SavedGamesCatalog) -> Self {
+        let mut app = Self {
+            engine,
+            catalog,
+            cursor: Coord::new(0, 0),
+            column: 0,
+            mode: Mode::Idle,
+            message: None,
+        };
+        app.focus_active_player();
+        app
+    }
+
+    pub fn engine(&self) -> &GameEngine {
+        &self.engine
+    }
+
+    pub fn cursor(&self) -> Coord {
+        self.cursor
+    }
+
+    pub fn mode(&self) -> &Mode {
+        &self.mode
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Return a coordinate of selected tile, if any
+    pub fn selected(&self) -> Option<Coord> {
+        match self.mode {
+            Mode::Selected(c) | Mode::Buying(c, _) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Process a key pressed by user. Return false if application should be closed
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        if let Mode::Saving(ref mut name) = self.mode {
+            match key {
+                Key::Char('\n') => {
+                    let name = name.clone();
+                    self.save(&name);
+                }
+                Key::Char(c) if c.is_ascii_alphanumeric() => name.push(c),
+                Key::Backspace => {
+                    name.pop();
+                }
+                Key::Esc => self.mode = Mode::Idle,
+                _ => (),
+            }
+            return true;
+        }
+
+        match key {
+            Key::Char('q') | Key::Ctrl('c') => return false,
+            Key::Left => self.move_in_row(-1),
+            Key::Right => self.move_in_row(1),
+            Key::Up => self.move_to_row(-1),
+            Key::Down => self.move_to_row(1),
+            Key::Esc => self.mode = Mode::Idle,
+            Key::Char('\n') | Key::Char(' ') => self.confirm(),
+            Key::Char('e') => self.act(PlayerAction::EndTurn),
+            Key::Char('R') => self.act(PlayerAction::Resign),
+            Key::Char('s') => self.mode = Mode::Saving(String::new()),
+            Key::Char('l') => self.load_last(),
+            Key::Char('h') => self.hint(),
+            Key::Char('u') => {
+                if let Some(src) = self.selected() {
+                    self.act(PlayerAction::UpgradeUnit(src));
+                }
+            }
+            Key::Char('f') => {
+                if let Mode::Selected(src) = self.mode {
+                    let dst = self.cursor;
+                    self.act(PlayerAction::Shoot { src, dst });
+                }
+            }
+            Key::Char(c @ '1'..='9') => {
+                if let Some(src) = self.selected() {
+                    let unit_type = PURCHASABLE[c as usize - '1' as usize];
+                    self.mode = Mode::Buying(src, unit_type);
+                }
+            }
+            _ => (),
+        }
+        true
+    }
+
+    /// Handle a confirmation of the current selection on the tile under cursor
+    fn confirm(&mut self) {
+        let dst = self.cursor;
+        match self.mode.clone() {
+            Mode::Idle => {
+                let player_id = self.engine.active_player().id();
+                match self.engine.location().region_at(dst) {
+                    Some(region) if region.owner().id() == player_id => {
+                        self.mode = Mode::Selected(dst);
+                        self.message = None;
+                    }
+                    _ => self.message = Some(String::from("This tile is not yours")),
+                }
+            }
+            Mode::Selected(src) if src == dst => self.mode = Mode::Idle,
+            Mode::Selected(src) => self.act(PlayerAction::MoveUnit { src, dst }),
+            Mode::Buying(src, unit_type) => {
+                let region_id = self.engine.location().region_at(src).unwrap().id();
+                self.act(PlayerAction::PlaceNewUnit(region_id, unit_type, dst));
+            }
+            Mode::Saving(_) => (),
+        }
+    }
+
+    /// Perform an action on behalf of active player
+    fn act(&mut self, action: PlayerAction) {
+        let player_id = self.engine.active_player().id();
+        let turn_ends = action == PlayerAction::EndTurn || action == PlayerAction::Resign;
+        match self.engine.act(player_id, action) {
+            Ok(()) => {
+                self.mode = Mode::Idle;
+                self.message = None;
+                if turn_ends {
+                    self.focus_active_player();
+                }
+            }
+            Err(e) => {
+                let explanation = hints::explain(&self.engine, player_id, &action, &e);
+                self.message = Some(format!("Cannot do that: {}", explanation));
+            }
+        }
+    }
+
+    /// Show what is worth doing for the active player and put cursor on the tile of the action
+    fn hint(&mut self) {
+        let player_id = self.engine.active_player().id();
+        self.message = match hints::suggest(&self.engine, player_id) {
+            Some(suggestion) => {
+                match suggestion.action {
+                    PlayerAction::MoveUnit { dst, .. } | PlayerAction::PlaceNewUnit(_, _, dst) => {
+                        self.cursor = dst
+                    }
+                    _ => (),
+                }
+                Some(format!("Hint: {}", suggestion.reason))
+            }
+            None => None,
+        };
+    }
+
+    fn save(&mut self, name: &str) {
+        self.mode = Mode::Idle;
+        if name.is_empty() {
+            self.message = Some(String::from("Name of saved game cannot be empty"));
+            return;
+        }
+        self.message = Some(match self.catalog.save(name, &self.engine) {
+            Ok(info) => format!("Saved game as '{}'", info.name),
+            Err(e) => format!("Failed to save game: {}", e),
+        });
+    }
+
+    fn load_last(&mut self) {
+        let last = self
+            .catalog
+            .list_saved_games()
+            .iter()
+            .max_by_key(|g| g.timestamp)
+            .cloned();
+        if last.is_none() {
+            self.message = Some(String::from("There are no saved games"));
+            return;
+        }
+        let info = last.unwrap();
+        match self.catalog.load(&info) {
+            Ok(engine) => {
+                self.engine = engine;
+                self.mode = Mode::Idle;
+                self.message = Some(format!("Loaded game '{}'", info.name));
+                self.focus_active_player();
+            }
+            Err(e) => self.message = Some(format!("Failed to load game: {}", e)),
+        }
+    }
+
+    /// Move cursor to the capital of the largest region of active player
+    fn focus_active_player(&mut self) {
+        let player_id = self.engine.active_player().id();
+        let location = self.engine.location();
+        let focus = location
+            .regions()
+            .values()
+            .filter(|r| r.owner().id() == player_id)
+            .max_by_key(|r| (r.coordinates().len(), r.id()))
+            .and_then(|r| {
+                r.coordinates()
+                    .iter()
+                    .find(|&&c| {
+                        location.tile_at(c).unwrap().unit().map(|u| u.unit_type())
+                            == Some(UnitType::Village)
+                    })
+                    .or_else(|| r.coordinates().iter().min())
+                    .cloned()
+            })
+            .or_else(|| location.map().keys().min().cloned());
+        if let Some(c) = focus {
+            self.cursor = c;
+            self.column = column(c);
+        }
+    }
+
+    /// Move cursor to the next existing tile in the same row
+    fn move_in_row(&mut self, direction: i32) {
+        let current = column(self.cursor);
+        let next = self
+            .engine
+            .location()
+            .map()
+            .keys()
+            .filter(|c| c.y == self.cursor.y)
+            .filter(|&&c| (column(c) - current) * direction > 0)
+            .min_by_key(|&&c| (column(c) - current).abs())
+            .cloned();
+        if let Some(c) = next {
+            self.cursor = c;
+            self.column = column(c);
+        }
+    }
+
+    /// Move cursor to the tile of the next row that is the closest to remembered column
+    fn move_to_row(&mut self, direction: i32) {
+        let row = self.cursor.y + direction;
+        let target = self.column;
+        let next = self
+            .engine
+            .location()
+            .map()
+            .keys()
+            .filter(|c| c.y == row)
+            .min_by_key(|&&c| ((column(c) - target).abs(), column(c)))
+            .cloned();
+        if let Some(c) = next {
+            self.cursor = c;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use termion::event::Key;
+
+    use yasc::game::test_util::create_valid_engine;
+    use yasc::game::{Coord, UnitType};
+    use yasc::saves::SavedGamesCatalog;
+
+    use super::{App, Mode};
+
+    fn create_app() -> (tempfile::TempDir, App) {
+        let dir = tempfile::tempdir().unwrap();
+        let catalog = SavedGamesCatalog::new(dir.path().to_str().unwrap(), "test").unwrap();
+        let (_, _, engine) = create_valid_engine();
+        (dir, App::new(engine, catalog))
+    }
+
+    #[test]
+    fn cursor_starts_at_capital_of_active_player() {
+        let (_dir, app) = create_app();
+        assert_eq!(app.cursor(), Coord::new(1, -1));
+    }
+
+    #[test]
+    fn cursor_moves_between_rows_and_keeps_column() {
+        let (_dir, mut app) = create_app();
+        app.handle_key(Key::Down);
+        assert_eq!(app.cursor(), Coord::new(0, 0));
+        app.handle_key(Key::Down);
+        assert_eq!(app.cursor(), Coord::new(0, 1));
+        app.handle_key(Key::Up);
+        app.handle_key(Key::Up);
+        assert_eq!(app.cursor(), Coord::new(1, -1));
+    }
+
+    #[test]
+    fn cursor_does_not_leave_the_map() {
+        let (_dir, mut app) = create_app();
+        app.handle_key(Key::Up);
+        assert_eq!(app.cursor(), Coord::new(1, -1));
+        app.handle_key(Key::Right);
+        app.handle_key(Key::Right);
+        app.handle_key(Key::Right);
+        assert_eq!(app.cursor(), Coord::new(3, -1));
+    }
+
+    #[test]
+    fn unit_is_moved_with_selection() {
+        let (_dir, mut app) = create_app();
+        app.handle_key(Key::Down);
+        app.handle_key(Key::Right);
+        assert_eq!(app.cursor(), Coord::new(1, 0));
+        app.handle_key(Key::Char('\n'));
+        assert_eq!(*app.mode(), Mode::Selected(Coord::new(1, 0)));
+        app.handle_key(Key::Down);
+        assert_eq!(app.cursor(), Coord::new(0, 1));
+        app.handle_key(Key::Char('\n'));
+
+        assert_eq!(*app.mode(), Mode::Idle);
+        let unit = app.engine().location().tile_at(Coord::new(0, 1)).unwrap().unit();
+        assert_eq!(unit.map(|u| u.unit_type()), Some(UnitType::Soldier));
+    }
+
+    #[test]
+    fn unit_is_bought_for_selected_region() {
+        let (_dir, mut app) = create_app();
+        app.handle_key(Key::Char('\n'));
+        app.handle_key(Key::Char('1'));
+        assert_eq!(
+            *app.mode(),
+            Mode::Buying(Coord::new(1, -1), UnitType::Militia)
+        );
+        app.handle_key(Key::Right);
+        app.handle_key(Key::Char('\n'));
+
+        assert_eq!(app.message(), None);
+        let unit = app.engine().location().tile_at(Coord::new(2, -1)).unwrap().unit();
+        assert_eq!(unit.map(|u| u.unit_type()), Some(UnitType::Militia));
+    }
+
+    #[test]
+    fn hint_is_shown_for_active_player() {
+        let (_dir, mut app) = create_app();
+        app.handle_key(Key::Char('h'));
+
+        assert!(app.message().unwrap().starts_with("Hint: "));
+    }
+
+    #[test]
+    fn resigned_player_leaves_the_game() {
+        let (_dir, mut app) = create_app();
+        let first = app.engine().active_player().id();
+        app.handle_key(Key::Char('R'));
+
+        assert_ne!(app.engine().active_player().id(), first);
+        assert!(!app.engine().is_player_active(first));
+    }
+
+    #[test]
+    fn selection_of_other_players_tile_is_refused() {
+        let (_dir, mut app) = create_app();
+        app.handle_key(Key::Down);
+        app.handle_key(Key::Down);
+        app.handle_key(Key::Char('\n'));
+
+        assert_eq!(*app.mode(), Mode::Idle);
+        assert!(app.message().is_some());
+    }
+
+    #[test]
+    fn end_turn_passes_control_to_next_player() {
+        let (_dir, mut app) = create_app();
+        let first = app.engine().active_player().id();
+        app.handle_key(Key::Char('e'));
+
+        assert_ne!(app.engine().active_player().id(), first);
+        assert_eq!(app.cursor(), Coord::new(2, 0));
+    }
+
+    #[test]
+    fn game_is_saved_and_loaded() {
+        let (_dir, mut app) = create_app();
+        let saved = app.engine().clone();
+        app.handle_key(Key::Char('s'));
+        for c in "first".chars() {
+            app.handle_key(Key::Char(c));
+        }
+        app.handle_key(Key::Char('\n'));
+        app.handle_key(Key::Char('e'));
+        assert_ne!(*app.engine(), saved);
+
+        app.handle_key(Key::Char('l'));
+        assert_eq!(*app.engine(), saved);
+    }
+}
@@ -0,0 +1,143 @@
+//! Terminal client for a hot-seat game: all players share the same keyboard and make their turns
+//! one after another.
+//!
+//! Usage: `yasc-tui [--players N] [--saves DIR]`
+extern crate termion;
+extern crate yasc;
+
+mod app;
+mod render;
+
+use std::env;
+use std::f64::consts::PI;
+use std::io::{self, Write};
+use std::process;
+
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::AlternateScreen;
+use termion::{clear, cursor};
+
+use yasc::game::{Coord, GameEngine, TileSurface};
+use yasc::init::builder::GameEngineBuilder;
+use yasc::saves::SavedGamesCatalog;
+
+use crate::app::App;
+
+const MAP_RADIUS: u32 = 5;
+const DEFAULT_PLAYERS_NUM: u8 = 2;
+const DEFAULT_SAVES_DIR: &str = "saves";
+const SAVES_PREFIX: &str = "yasc";
+
+struct Options {
+    players_num: u8,
+    saves_dir: String,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        players_num: DEFAULT_PLAYERS_NUM,
+        saves_dir: String::from(DEFAULT_SAVES_DIR),
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--players" => {
+                options.players_num = args
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|n| (2..=6).contains(n))
+                    .ok_or_else(|| String::from("--players expects a number from 2 to 6"))?;
+            }
+            "--saves" => {
+                options.saves_dir = args
+                    .next()
+                    .ok_or_else(|| String::from("--saves expects a directory"))?;
+            }
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+/// Create a round island split between players into equal sectors
+fn new_game(players_num: u8) -> GameEngine {
+    let mut builder = GameEngineBuilder::circle(MAP_RADIUS, players_num).unwrap();
+    let player_ids: Vec<_> = builder.players().iter().map(|p| p.id()).collect();
+
+    let mut land = Vec::new();
+    Coord::new(0, 0).for_each_in_range(MAP_RADIUS as i32 - 1, |c| land.push(c));
+    for c in land {
+        builder.set_surface(c, TileSurface::Land).unwrap();
+        if c == Coord::new(0, 0) {
+            continue;
+        }
+        let x = 3f64.sqrt() * (f64::from(c.x) + f64::from(c.y) / 2.0);
+        let y = 1.5 * f64::from(c.y);
+        let sector = ((y.atan2(x) + PI) / (2.0 * PI) * f64::from(players_num)) as usize;
+        let owner = player_ids[sector % player_ids.len()];
+        builder.set_owner(c, owner).unwrap();
+    }
+    // The center is left to the first player so the whole land is covered with regions
+    builder.set_owner(Coord::new(0, 0), player_ids[0]).unwrap();
+
+    builder.build().unwrap()
+}
+
+fn draw<W: Write>(out: &mut W, app: &App) -> io::Result<()> {
+    write!(
+        out,
+        "{}{}{}{}",
+        clear::All,
+        cursor::Goto(1, 1),
+        render::render_map(app),
+        render::render_status(app)
+    )?;
+    out.flush()
+}
+
+fn run(options: Options) -> io::Result<()> {
+    let catalog = SavedGamesCatalog::new(&options.saves_dir, SAVES_PREFIX)?;
+    let mut app = App::new(new_game(options.players_num), catalog);
+
+    let stdin = io::stdin();
+    let mut screen = AlternateScreen::from(io::stdout().into_raw_mode()?);
+    write!(screen, "{}", cursor::Hide)?;
+    draw(&mut screen, &app)?;
+
+    for key in stdin.keys() {
+        if !app.handle_key(key?) {
+            break;
+        }
+        draw(&mut screen, &app)?;
+    }
+
+    write!(screen, "{}", cursor::Show)?;
+    screen.flush()
+}
+
+fn main() {
+    let options = parse_options().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!("Usage: yasc-tui [--players N] [--saves DIR]");
+        process::exit(2);
+    });
+    if let Err(e) = run(options) {
+        eprintln!("Terminal error: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::new_game;
+
+    #[test]
+    fn new_game_is_created_for_any_supported_players_num() {
+        for players_num in 2..=6 {
+            let engine = new_game(players_num);
+            assert_eq!(engine.players().len(), players_num as usize);
+            assert_eq!(engine.validate(), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Drawing of the game state into a terminal
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use termion::color;
+
+use yasc::game::{Coord, GameEngine, Location, MoveKind, ReachableTile, ID};
+use yasc::init::ascii::{surface_char, unit_char};
+
+use crate::app::{App, Mode};
+
+/// Width of one hex cell in characters. Neighbouring cells in one row are separated by one space
+/// and every next row is shifted by a half of the cell.
+const CELL_WIDTH: i32 = 4;
+
+const PLAYER_COLORS: [(u8, u8, u8); 6] = [
+    (4, 1, 1),
+    (1, 3, 1),
+    (4, 4, 1),
+    (3, 1, 4),
+    (1, 3, 4),
+    (4, 2, 0),
+];
+
+const WATER_COLOR: (u8, u8, u8) = (0, 1, 3);
+
+/// Return a column of a hex with provided coordinate in "half-cells".
+///
+/// Rows of the map are formed by coordinates with the same `y`, and each next row is shifted by
+/// a half of the cell to the left, so two hexes of neighbouring rows never share a column.
+pub fn column(coordinate: Coord) -> i32 {
+    2 * coordinate.x + coordinate.y
+}
+
+/// Return a color that is used to draw regions of a player with provided index
+fn player_color(index: usize) -> color::AnsiValue {
+    let (r, g, b) = PLAYER_COLORS[index % PLAYER_COLORS.len()];
+    color::AnsiValue::rgb(r, g, b)
+}
+
+/// Return indices of players in the order they make their turns, keyed by their ids
+pub fn player_indices(engine: &GameEngine) -> HashMap<ID, usize> {
+    engine
+        .players()
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.id(), i))
+        .collect()
+}
+
+/// Return bounds of the map as `(min_row, max_row, min_column)`
+fn bounds(location: &Location) -> (i32, i32, i32) {
+    let min_row = location.map().keys().map(|c| c.y).min().unwrap_or(0);
+    let max_row = location.map().keys().map(|c| c.y).max().unwrap_or(0);
+    let min_column = location.map().keys().map(|&c| column(c)).min().unwrap_or(0);
+    (min_row, max_row, min_column)
+}
+
+/// Render the map with all regions and units. Every line of the result ends with `\r\n` because
+/// terminal is expected to be in raw mode.
+pub fn render_map(app: &App) -> String {
+    let engine = app.engine();
+    let location = engine.location();
+    let indices = player_indices(engine);
+    let (min_row, max_row, min_column) = bounds(location);
+    let reachable = match app.mode() {
+        Mode::Selected(src) => engine.reachable_tiles(*src),
+        _ => HashMap::new(),
+    };
+
+    let mut rows: HashMap<i32, Vec<Coord>> = HashMap::new();
+    for &c in location.map().keys() {
+        rows.entry(c.y).or_default().push(c);
+    }
+
+    let mut out = String::new();
+    for row in min_row..=max_row {
+        let mut coordinates = rows.remove(&row).unwrap_or_default();
+        coordinates.sort_by_key(|&c| column(c));
+
+        let mut position = 0;
+        for c in coordinates {
+            let start = (column(c) - min_column) * CELL_WIDTH / 2;
+            while position < start {
+                out.push(' ');
+                position += 1;
+            }
+            out.push_str(&render_cell(app, &indices, &reachable, c));
+            position += CELL_WIDTH - 1;
+        }
+        write!(out, "{}{}\r\n", color::Bg(color::Reset), color::Fg(color::Reset)).unwrap();
+    }
+    out
+}
+
+fn render_cell(
+    app: &App,
+    indices: &HashMap<ID, usize>,
+    reachable: &HashMap<Coord, ReachableTile>,
+    coordinate: Coord,
+) -> String {
+    let location = app.engine().location();
+    let tile = location.tile_at(coordinate).unwrap();
+
+    let background = if tile.surface().is_water() {
+        let (r, g, b) = WATER_COLOR;
+        color::AnsiValue::rgb(r, g, b)
+    } else {
+        location
+            .region_at(coordinate)
+            .and_then(|r| indices.get(&r.owner().id()))
+            .map_or(color::AnsiValue::grayscale(8), |&i| player_color(i))
+    };
+    let glyph = tile.unit().map_or_else(
+        || {
+            if tile.surface().is_water() {
+                '~'
+            } else {
+                surface_char(*tile.surface()).unwrap_or(' ')
+            }
+        },
+        |u| unit_char(u.unit_type()),
+    );
+    let (left, right) = if coordinate == app.cursor() {
+        ('[', ']')
+    } else if Some(coordinate) == app.selected() {
+        ('<', '>')
+    } else {
+        match reachable.get(&coordinate).map(|t| t.kind) {
+            Some(MoveKind::Attack) => ('!', '!'),
+            Some(_) => ('(', ')'),
+            None => (' ', ' '),
+        }
+    };
+
+    format!(
+        "{}{}{}{}{}{}",
+        color::Bg(background),
+        color::Fg(color::White),
+        left,
+        glyph,
+        right,
+        color::Bg(color::Reset),
+    )
+}
+
+/// Render information about the game, the tile under cursor and a list of available keys
+pub fn render_status(app: &App) -> String {
+    let engine = app.engine();
+    let indices = player_indices(engine);
+    let mut out = String::new();
+
+    if let Some(winner) = engine.winner() {
+        write!(out, "Player {} won the game!\r\n", indices[&winner] + 1).unwrap();
+    } else {
+        write!(
+            out,
+            "Turn {}, player {}{}{} is moving\r\n",
+            engine.current_turn(),
+            color::Fg(player_color(engine.active_player_num())),
+            engine.active_player_num() + 1,
+            color::Fg(color::Reset),
+        )
+        .unwrap();
+    }
+
+    let cursor = app.cursor();
+    write!(out, "Cursor at ({}, {})", cursor.x, cursor.y).unwrap();
+    if let Some(region) = engine.location().region_at(cursor) {
+        let owner = match indices.get(&region.owner().id()) {
+            Some(i) => format!("player {}", i + 1),
+            None => String::from("nobody"),
+        };
+        write!(
+            out,
+            ": region {} of {}, money {}",
+            region.id(),
+            owner,
+            engine.region_money(region.id()).unwrap_or(0),
+        )
+        .unwrap();
+    }
+    out.push_str("\r\n");
+
+    let active_id = engine.active_player().id();
+    let mut regions: Vec<_> = engine
+        .location()
+        .regions()
+        .values()
+        .filter(|r| r.owner().id() == active_id)
+        .map(|r| (r.id(), r.coordinates().len()))
+        .collect();
+    regions.sort();
+    out.push_str("Your regions:");
+    for (id, size) in regions {
+        write!(
+            out,
+            " #{} ({} tiles, {} money)",
+            id,
+            size,
+            engine.region_money(id).unwrap_or(0)
+        )
+        .unwrap();
+    }
+    out.push_str("\r\n\r\n");
+
+    match app.mode() {
+        Mode::Idle => out.push_str("Select a tile of your region with Enter"),
+        Mode::Selected(_) => out.push_str(
+            "Move cursor and press Enter to move the unit to (x) or attack !x!, 1-9 to buy a unit, \
+             u to upgrade, f to shoot at cursor",
+        ),
+        Mode::Buying(_, unit_type) => write!(
+            out,
+            "Move cursor and press Enter to place {:?}",
+            unit_type
+        )
+        .unwrap(),
+        Mode::Saving(name) => write!(out, "Save as: {}_", name).unwrap(),
+    }
+    out.push_str("\r\n");
+    if let Some(message) = app.message() {
+        out.push_str(message);
+    }
+    out.push_str("\r\n\r\n");
+    out.push_str(
+        "arrows: move cursor  enter: select  \
+         1-9: buy militia/soldier/knight/great knight/tower/bridge/boat/archer/engineer\r\n\
+         u: upgrade  f: shoot  h: hint  e: end turn  R: resign  s: save  l: load last save  \
+         esc: cancel  q: quit\r\n",
+    );
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::column;
+    use yasc::game::Coord;
+
+    #[test]
+    fn neighbours_in_row_are_two_columns_apart() {
+        assert_eq!(column(Coord::new(1, 0)) - column(Coord::new(0, 0)), 2);
+    }
+
+    #[test]
+    fn neighbours_in_adjacent_rows_are_one_column_apart() {
+        let c = Coord::new(0, 0);
+        for n in c.neighbors().iter().filter(|n| n.y != c.y) {
+            assert_eq!((column(*n) - column(c)).abs(), 1);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+
+use super::{border, can_sustain, controlled_regions, movable_units, Bot};
+use crate::game::{description, Coord, PlayerAction, PlayerView, UnitType};
+
+/// Units greedy bot buys to attack, from the cheapest to the most expensive one
+const ATTACKERS: [UnitType; 3] = [UnitType::Militia, UnitType::Soldier, UnitType::Knight];
+
+/// Bot that tries to capture as much land as possible during each turn: first it attacks with
+/// units it already has, then buys new units right on the border while it can afford to pay for
+/// them, and finally clears its land from trees and graves.
+pub struct GreedyBot {
+    turn: u32,
+    tried: HashSet<PlayerAction>,
+}
+
+impl GreedyBot {
+    pub fn new() -> Self {
+        Self {
+            turn: 0,
+            tried: HashSet::new(),
+        }
+    }
+
+    /// Return tiles of enemies on the border of region of provided coordinate, the most valuable
+    /// first
+    fn targets(view: &PlayerView, coordinate: Coord) -> Vec<Coord> {
+        let region = view.location().region_at(coordinate).unwrap();
+        let player_id = view.player_id();
+        let mut targets: Vec<(u8, Coord)> = border(view, region)
+            .into_iter()
+            .filter(|&c| {
+                view.location()
+                    .region_at(c)
+                    .map(|r| {
+                        view.are_allies(player_id, r.owner().id())
+                            || view.have_truce(player_id, r.owner().id())
+                    }) != Some(true)
+            })
+            .map(|c| (Self::value(view, c), c))
+            .collect();
+        targets.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        targets.into_iter().map(|(_, c)| c).collect()
+    }
+
+    fn value(view: &PlayerView, coordinate: Coord) -> u8 {
+        match view
+            .location()
+            .tile_at(coordinate)
+            .and_then(|t| t.unit())
+            .map(|u| u.unit_type())
+        {
+            Some(UnitType::Village) => 3,
+            Some(u) if !description(u).is_unownable => 2,
+            Some(_) => 0,
+            None => 1,
+        }
+    }
+
+    /// Return all actions bot would like to do, the most desired first
+    pub(crate) fn candidates(view: &PlayerView) -> Vec<PlayerAction> {
+        let mut candidates = Vec::new();
+
+        let mut units = movable_units(view);
+        units.sort_by_key(|(c, u)| (-(description(u.unit_type()).attack as i32), *c));
+        for &(src, _) in units.iter() {
+            for dst in Self::targets(view, src) {
+                candidates.push(PlayerAction::MoveUnit { src, dst });
+            }
+        }
+
+        for region in controlled_regions(view) {
+            // Targets depend only on the region, so any of its tiles will do
+            let origin = *region.coordinates().iter().min().unwrap();
+            for &unit_type in ATTACKERS.iter() {
+                if !can_sustain(view, region.id(), unit_type) {
+                    continue;
+                }
+                for dst in Self::targets(view, origin) {
+                    candidates.push(PlayerAction::PlaceNewUnit(region.id(), unit_type, dst));
+                }
+            }
+        }
+
+        for &(src, _) in units.iter() {
+            let region = view.location().region_at(src).unwrap();
+            let mut overgrown: Vec<Coord> = region
+                .coordinates()
+                .iter()
+                .filter(|&&c| {
+                    view
+                        .location()
+                        .tile_at(c)
+                        .unwrap()
+                        .unit()
+                        .map(|u| description(u.unit_type()).is_unownable)
+                        == Some(true)
+                }).cloned()
+                .collect();
+            overgrown.sort();
+            for dst in overgrown {
+                candidates.push(PlayerAction::MoveUnit { src, dst });
+            }
+        }
+
+        candidates
+    }
+}
+
+impl Default for GreedyBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bot for GreedyBot {
+    fn name(&self) -> &str {
+        "greedy"
+    }
+
+    fn next_action(&mut self, view: &PlayerView) -> PlayerAction {
+        if view.current_turn() != self.turn {
+            self.turn = view.current_turn();
+            self.tried.clear();
+        }
+
+        let tried = &mut self.tried;
+        Self::candidates(view)
+            .into_iter()
+            .find(|a| tried.insert(*a))
+            .unwrap_or(PlayerAction::EndTurn)
+    }
+}
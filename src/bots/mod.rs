@@ -0,0 +1,265 @@
+//! Computer controlled players and a runner that plays whole games with them
+use std::collections::HashMap;
+
+use crate::game::{
+    description, has_controlled_size, Coord, GameEngine, GameEvent, PlayerAction, PlayerView,
+    Region, Unit, UnitType, ID,
+};
+
+mod greedy;
+mod random;
+
+pub use self::greedy::GreedyBot;
+pub use self::random::RandomBot;
+
+/// After this number of actions during one turn the turn is ended for the player
+pub const MAX_ACTIONS_PER_TURN: u32 = 200;
+
+/// Units that can be bought by players, from the cheapest to the most expensive one
+pub const PURCHASABLE_UNITS: [UnitType; 5] = [
+    UnitType::Militia,
+    UnitType::Tower,
+    UnitType::Soldier,
+    UnitType::Knight,
+    UnitType::GreatKnight,
+];
+
+/// A computer controlled player
+pub trait Bot {
+    /// Name of the bot that is used in reports
+    fn name(&self) -> &str;
+
+    /// Choose the next action for the player who sees the game through provided view. Bot can
+    /// return an action that is not valid, it will be asked for another one in that case. When
+    /// bot has nothing left to do it should return `PlayerAction::EndTurn`.
+    fn next_action(&mut self, view: &PlayerView) -> PlayerAction;
+}
+
+/// Create a bot by its name. Return `None` if there is no bot with such name.
+pub fn create(name: &str, seed: u64) -> Option<Box<dyn Bot>> {
+    match name {
+        "random" => Some(Box::new(RandomBot::new(seed))),
+        "greedy" => Some(Box::new(GreedyBot::new())),
+        _ => None,
+    }
+}
+
+/// Statistics of one player collected while playing a game
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerSummary {
+    pub player_id: ID,
+    pub bot: String,
+    pub units_bought: u32,
+    pub starvation_deaths: u32,
+    pub rejected_actions: u32,
+}
+
+/// Result of a game played by bots
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub winner: Option<ID>,
+    /// Number of completed turns
+    pub turns: u32,
+    pub players: Vec<PlayerSummary>,
+}
+
+/// Play a game until somebody wins or `max_turns` turns are completed. Bots are given to players
+/// in the same order players make their turns.
+///
+/// # Panics
+///
+/// This function will panic if number of bots is different from number of players
+pub fn play(engine: &mut GameEngine, bots: &mut [Box<dyn Bot>], max_turns: u32) -> GameSummary {
+    assert_eq!(engine.players().len(), bots.len(), "Each player needs a bot");
+
+    let mut summaries: Vec<PlayerSummary> = engine
+        .players()
+        .iter()
+        .zip(bots.iter())
+        .map(|(p, b)| PlayerSummary {
+            player_id: p.id(),
+            bot: b.name().to_owned(),
+            units_bought: 0,
+            starvation_deaths: 0,
+            rejected_actions: 0,
+        }).collect();
+    let player_nums: HashMap<ID, usize> = engine
+        .players()
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.id(), i))
+        .collect();
+
+    let mut actions_made = 0;
+    while engine.winner().is_none() && engine.current_turn() <= max_turns {
+        let num = engine.active_player_num();
+        let player_id = engine.active_player().id();
+        let action = if actions_made >= MAX_ACTIONS_PER_TURN {
+            PlayerAction::EndTurn
+        } else {
+            bots[num].next_action(&engine.view_for(player_id))
+        };
+
+        match engine.act(player_id, action) {
+            Ok(()) => {
+                if let PlayerAction::PlaceNewUnit(..) = action {
+                    summaries[num].units_bought += 1;
+                }
+            }
+            Err(_) => summaries[num].rejected_actions += 1,
+        }
+        actions_made = if action == PlayerAction::EndTurn {
+            0
+        } else {
+            actions_made + 1
+        };
+
+        for event in engine.take_events() {
+            if let GameEvent::UnitStarved { player_id, .. } = event {
+                summaries[player_nums[&player_id]].starvation_deaths += 1;
+            }
+        }
+    }
+
+    GameSummary {
+        winner: engine.winner(),
+        turns: engine.current_turn() - 1,
+        players: summaries,
+    }
+}
+
+/// Return regions of the player that are big enough to buy units, sorted by id
+fn controlled_regions(view: &PlayerView) -> Vec<&Region> {
+    let mut regions: Vec<&Region> = view
+        .location()
+        .regions()
+        .values()
+        .filter(|r| r.owner().id() == view.player_id())
+        .filter(|r| has_controlled_size(view.location(), r))
+        .collect();
+    regions.sort_by_key(|r| r.id());
+    regions
+}
+
+/// Return units of the player that still can move during this turn, sorted by coordinate. Units
+/// aboard boats are returned with the coordinate of the boat, as they move from it.
+fn movable_units(view: &PlayerView) -> Vec<(Coord, Unit)> {
+    let mut units: Vec<(Coord, Unit)> = view
+        .location()
+        .regions()
+        .values()
+        .filter(|r| r.owner().id() == view.player_id())
+        .flat_map(|r| r.coordinates().iter())
+        .flat_map(|&c| {
+            let unit = view.location().tile_at(c).unwrap().unit();
+            unit.into_iter().chain(view.cargo_at(c)).map(move |&u| (c, u))
+        }).filter(|(_, u)| view.unit_moves_left(u.id()).unwrap_or(0) > 0)
+        .collect();
+    units.sort();
+    units
+}
+
+/// Return tiles that do not belong to the region but are adjacent to it and can be stepped on,
+/// sorted by coordinate
+fn border(view: &PlayerView, region: &Region) -> Vec<Coord> {
+    let mut border: Vec<Coord> = region
+        .coordinates()
+        .iter()
+        .flat_map(|c| c.neighbors().to_vec())
+        .filter(|c| !region.coordinates().contains(c))
+        .filter(|&c| view.location().tile_at(c).map(|t| t.surface().is_land()) == Some(true))
+        .collect();
+    border.sort();
+    border.dedup();
+    border
+}
+
+/// Return true if region can buy a unit and still pay for all its units at the end of turn
+fn can_sustain(view: &PlayerView, region_id: ID, unit_type: UnitType) -> bool {
+    let d = description(unit_type);
+    let money = view.region_money(region_id).unwrap_or(0);
+    let profit = view.region_profit(region_id).unwrap_or(0);
+    money >= d.purchase_cost && money - d.purchase_cost + profit - d.turn_cost >= 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::{create, movable_units, play, Bot};
+    use crate::game::{Coord, PlayerAction, PlayerView, RulesVariant, UnitType};
+    use crate::init::ascii::parse;
+    use crate::init::generator::generate;
+
+    struct PassiveBot;
+
+    impl Bot for PassiveBot {
+        fn name(&self) -> &str {
+            "passive"
+        }
+
+        fn next_action(&mut self, _view: &PlayerView) -> PlayerAction {
+            PlayerAction::EndTurn
+        }
+    }
+
+    #[test]
+    fn check_game_stops_after_max_turns() {
+        let mut engine = generate(3, 5, 2).unwrap();
+        let mut bots: Vec<Box<dyn Bot>> = vec![Box::new(PassiveBot), Box::new(PassiveBot)];
+
+        let summary = play(&mut engine, &mut bots, 5);
+
+        assert_eq!(summary.turns, 5);
+        assert_eq!(summary.winner, None);
+        assert_eq!(summary.players.len(), 2);
+        assert_eq!(summary.players[0].bot, "passive");
+        assert_eq!(summary.players[0].units_bought, 0);
+        assert_eq!(summary.players[0].rejected_actions, 0);
+    }
+
+    #[test]
+    fn check_bots_play_valid_games() {
+        for seed in 0..3 {
+            let mut engine = generate(seed, 5, 3).unwrap();
+            let mut bots: Vec<Box<dyn Bot>> = ["greedy", "random", "greedy"]
+                .iter()
+                .enumerate()
+                .map(|(i, name)| create(name, seed + i as u64).unwrap())
+                .collect();
+
+            let summary = play(&mut engine, &mut bots, 30);
+
+            assert_eq!(engine.validate(), Ok(()));
+            assert!(summary.turns <= 30);
+            assert!(summary.players.iter().any(|p| p.units_bought > 0));
+        }
+    }
+
+    #[test]
+    fn check_units_aboard_boats_can_be_moved() {
+        let text = "players 2\norigin 0 0\n1V  1S  1O  2.  2V\n  1.  1.  ~~  2.  2.\n";
+        let mut builder = parse(text).unwrap();
+        builder.set_rules_variant(RulesVariant::Archipelago);
+        let mut engine = builder.build().unwrap();
+        let pl: Vec<_> = engine.players().iter().map(|p| p.id()).collect();
+        let (shore, sea) = (Coord::new(1, 0), Coord::new(2, 0));
+        engine
+            .act(pl[0], PlayerAction::MoveUnit { src: shore, dst: sea })
+            .unwrap();
+        engine.act(pl[0], PlayerAction::EndTurn).unwrap();
+        engine.act(pl[1], PlayerAction::EndTurn).unwrap();
+
+        let units: Vec<UnitType> = movable_units(&engine.view_for(pl[0]))
+            .into_iter()
+            .filter(|&(c, _)| c == sea)
+            .map(|(_, u)| u.unit_type())
+            .collect();
+        assert_eq!(units.len(), 2);
+        assert!(units.contains(&UnitType::Boat));
+        assert!(units.contains(&UnitType::Soldier));
+    }
+
+    #[test]
+    fn check_unknown_bot_is_not_created() {
+        assert!(create("nobody", 1).is_none());
+    }
+}
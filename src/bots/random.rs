@@ -0,0 +1,80 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+use super::{border, controlled_regions, movable_units, Bot, PURCHASABLE_UNITS};
+use crate::game::{description, PlayerAction, PlayerView};
+
+/// Number of actions bot tries during one turn before ending it
+const ACTIONS_PER_TURN: u32 = 20;
+
+/// Bot that makes random moves and buys random units while it has money. It is useful as a
+/// baseline for other bots.
+pub struct RandomBot {
+    rng: XorShiftRng,
+    turn: u32,
+    actions_made: u32,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: XorShiftRng::seed_from_u64(seed),
+            turn: 0,
+            actions_made: 0,
+        }
+    }
+
+    fn random_move(&mut self, view: &PlayerView) -> Option<PlayerAction> {
+        let (src, unit) = *movable_units(view).choose(&mut self.rng)?;
+        let moves = view.unit_moves_left(unit.id()).unwrap_or(0) as i32;
+        let mut destinations = Vec::new();
+        src.for_each_in_range(moves, |c| {
+            if c != src && view.location().tile_at(c).is_some() {
+                destinations.push(c);
+            }
+        });
+        let dst = *destinations.choose(&mut self.rng)?;
+        Some(PlayerAction::MoveUnit { src, dst })
+    }
+
+    fn random_purchase(&mut self, view: &PlayerView) -> Option<PlayerAction> {
+        let region = *controlled_regions(view).choose(&mut self.rng)?;
+        let money = view.region_money(region.id()).unwrap_or(0);
+        let affordable: Vec<_> = PURCHASABLE_UNITS
+            .iter()
+            .filter(|&&u| description(u).purchase_cost <= money)
+            .collect();
+        let unit_type = **affordable.choose(&mut self.rng)?;
+
+        let mut destinations: Vec<_> = region.coordinates().iter().cloned().collect();
+        destinations.sort();
+        destinations.extend(border(view, region));
+        let dst = *destinations.choose(&mut self.rng)?;
+        Some(PlayerAction::PlaceNewUnit(region.id(), unit_type, dst))
+    }
+}
+
+impl Bot for RandomBot {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn next_action(&mut self, view: &PlayerView) -> PlayerAction {
+        if view.current_turn() != self.turn {
+            self.turn = view.current_turn();
+            self.actions_made = 0;
+        }
+        self.actions_made += 1;
+        if self.actions_made > ACTIONS_PER_TURN {
+            return PlayerAction::EndTurn;
+        }
+
+        let action = if self.rng.gen() {
+            self.random_move(view).or_else(|| self.random_purchase(view))
+        } else {
+            self.random_purchase(view).or_else(|| self.random_move(view))
+        };
+        action.unwrap_or(PlayerAction::EndTurn)
+    }
+}
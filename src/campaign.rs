@@ -0,0 +1,384 @@
+//! Campaigns are ordered sets of scenarios, where winning one scenario unlocks the next ones and
+//! brings a bonus that is carried over to them.
+//!
+//! Campaigns are stored in YAML files like scenarios, and progress of a player is stored with
+//! `SavedGamesCatalog::save_campaign`.
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use serde_yaml;
+
+use crate::scenario::{Scenario, ScenarioStatus};
+
+/// One scenario of a campaign
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct CampaignStage {
+    pub name: String,
+    pub scenario: Scenario,
+    /// Names of stages that have to be won before this one is unlocked
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Money that is given to the player in all next stages when this one is won
+    #[serde(default)]
+    pub bonus_money: i32,
+}
+
+/// Results of played stages of a campaign
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct CampaignProgress {
+    /// Name of the campaign the progress belongs to
+    #[serde(default)]
+    campaign: String,
+    /// Last results of played stages by their names
+    results: BTreeMap<String, ScenarioStatus>,
+    /// Sum of bonuses of won stages
+    bonus_money: i32,
+}
+
+impl CampaignProgress {
+    fn new(campaign: &str) -> Self {
+        CampaignProgress {
+            campaign: campaign.to_owned(),
+            ..CampaignProgress::default()
+        }
+    }
+
+    /// Return the name of the campaign the progress belongs to
+    pub fn campaign(&self) -> &str {
+        &self.campaign
+    }
+
+    pub fn result(&self, stage: &str) -> Option<ScenarioStatus> {
+        self.results.get(stage).cloned()
+    }
+
+    pub fn is_won(&self, stage: &str) -> bool {
+        self.result(stage) == Some(ScenarioStatus::Succeeded)
+    }
+
+    pub fn bonus_money(&self) -> i32 {
+        self.bonus_money
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum CampaignError {
+    UnknownStage(String),
+    DuplicateStage(String),
+    /// Stage requires a stage that does not go before it
+    UnknownRequirement(String),
+    StageIsLocked(String),
+    StageIsNotFinished(String),
+    /// Progress belongs to the campaign with provided name
+    OtherCampaign(String),
+}
+
+impl fmt::Display for CampaignError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CampaignError::UnknownStage(name) => write!(f, "There is no stage {:?}", name),
+            CampaignError::DuplicateStage(name) => {
+                write!(f, "There are several stages named {:?}", name)
+            }
+            CampaignError::UnknownRequirement(name) => write!(
+                f,
+                "Stage {:?} requires a stage that does not go before it",
+                name
+            ),
+            CampaignError::StageIsLocked(name) => write!(f, "Stage {:?} is locked", name),
+            CampaignError::StageIsNotFinished(name) => {
+                write!(f, "Stage {:?} is not finished yet", name)
+            }
+            CampaignError::OtherCampaign(name) => {
+                write!(f, "Progress belongs to another campaign {:?}", name)
+            }
+        }
+    }
+}
+
+impl Error for CampaignError {}
+
+/// Ordered stages of a campaign with the progress of the player
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Campaign {
+    name: String,
+    stages: Vec<CampaignStage>,
+    #[serde(default)]
+    progress: CampaignProgress,
+}
+
+impl Campaign {
+    /// Create a campaign with stages in the order they are played. Stages can only require
+    /// stages that go before them, so the stages form an acyclic graph.
+    pub fn new(name: &str, stages: Vec<CampaignStage>) -> Result<Self, CampaignError> {
+        let campaign = Campaign {
+            name: name.to_owned(),
+            stages,
+            progress: CampaignProgress::new(name),
+        };
+        campaign.validate()?;
+        Ok(campaign)
+    }
+
+    /// Read a campaign from a YAML file
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let buffer = File::open(path)?;
+        let mut campaign: Campaign = serde_yaml::from_reader(buffer)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        for stage in campaign.stages.iter_mut() {
+            stage.scenario.repair();
+        }
+        // Progress stored in the file of the campaign belongs to it
+        campaign.progress.campaign = campaign.name.clone();
+        campaign
+            .validate()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(campaign)
+    }
+
+    /// Write the campaign to a YAML file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let buffer = File::create(path)?;
+        serde_yaml::to_writer(buffer, self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn validate(&self) -> Result<(), CampaignError> {
+        let mut previous = HashSet::new();
+        for stage in self.stages.iter() {
+            if stage.requires.iter().any(|r| !previous.contains(r)) {
+                return Err(CampaignError::UnknownRequirement(stage.name.clone()));
+            }
+            if !previous.insert(&stage.name) {
+                return Err(CampaignError::DuplicateStage(stage.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stages(&self) -> &Vec<CampaignStage> {
+        &self.stages
+    }
+
+    pub fn progress(&self) -> &CampaignProgress {
+        &self.progress
+    }
+
+    /// Continue the campaign from progress saved earlier. Progress of other campaigns is refused.
+    pub fn set_progress(&mut self, progress: CampaignProgress) -> Result<(), CampaignError> {
+        if progress.campaign != self.name {
+            return Err(CampaignError::OtherCampaign(progress.campaign));
+        }
+        self.progress = progress;
+        Ok(())
+    }
+
+    fn stage(&self, name: &str) -> Result<&CampaignStage, CampaignError> {
+        self.stages
+            .iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| CampaignError::UnknownStage(name.to_owned()))
+    }
+
+    /// Check that all stages required by the stage are won
+    pub fn is_unlocked(&self, name: &str) -> Result<bool, CampaignError> {
+        let stage = self.stage(name)?;
+        Ok(stage.requires.iter().all(|r| self.progress.is_won(r)))
+    }
+
+    /// Return names of stages that are unlocked and not won yet, in the order of the campaign
+    pub fn available_stages(&self) -> Vec<&str> {
+        self.stages
+            .iter()
+            .filter(|s| !self.progress.is_won(&s.name))
+            .filter(|s| s.requires.iter().all(|r| self.progress.is_won(r)))
+            .map(|s| s.name.as_str())
+            .collect()
+    }
+
+    /// Check that all stages of the campaign are won
+    pub fn is_completed(&self) -> bool {
+        self.stages.iter().all(|s| self.progress.is_won(&s.name))
+    }
+
+    /// Return a scenario of an unlocked stage with bonuses of won stages given to the player
+    pub fn start(&self, name: &str) -> Result<Scenario, CampaignError> {
+        if !self.is_unlocked(name)? {
+            return Err(CampaignError::StageIsLocked(name.to_owned()));
+        }
+        let mut scenario = self.stage(name)?.scenario.clone();
+        if self.progress.bonus_money != 0 {
+            scenario.grant_money(self.progress.bonus_money);
+        }
+        Ok(scenario)
+    }
+
+    /// Record the result of a finished scenario of an unlocked stage. The bonus of the stage is
+    /// given only when it is won for the first time.
+    pub fn finish(&mut self, name: &str, scenario: &Scenario) -> Result<(), CampaignError> {
+        if !self.is_unlocked(name)? {
+            return Err(CampaignError::StageIsLocked(name.to_owned()));
+        }
+        let bonus_money = self.stage(name)?.bonus_money;
+        let status = scenario.status();
+        if status == ScenarioStatus::InProgress {
+            return Err(CampaignError::StageIsNotFinished(name.to_owned()));
+        }
+        if status == ScenarioStatus::Succeeded && !self.progress.is_won(name) {
+            self.progress.bonus_money += bonus_money;
+        }
+        if !self.progress.is_won(name) {
+            self.progress.results.insert(name.to_owned(), status);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Campaign, CampaignError, CampaignStage};
+    use crate::game::{Coord, PlayerAction};
+    use crate::init::ascii::parse;
+    use crate::scenario::{Objective, Scenario, ScenarioStatus};
+
+    /// Scenario that is won by moving the soldier to the enemy capital
+    fn create_scenario() -> Scenario {
+        let text = "players 2\norigin 0 0\n1V  1S  1.  2.  2V  2.\n";
+        let engine = parse(text).unwrap().build().unwrap();
+        let player_id = engine.players()[0].id();
+        Scenario::new(
+            engine,
+            player_id,
+            vec![Objective::CaptureTile(Coord::new(3, 0))],
+        )
+    }
+
+    fn stage(name: &str, requires: &[&str]) -> CampaignStage {
+        CampaignStage {
+            name: name.to_owned(),
+            scenario: create_scenario(),
+            requires: requires.iter().map(|&r| r.to_owned()).collect(),
+            bonus_money: 5,
+        }
+    }
+
+    fn win(campaign: &mut Campaign, name: &str) {
+        let mut scenario = campaign.start(name).unwrap();
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(3, 0),
+        };
+        assert_eq!(scenario.act(action), Ok(ScenarioStatus::Succeeded));
+        campaign.finish(name, &scenario).unwrap();
+    }
+
+    #[test]
+    fn stages_can_only_require_previous_stages() {
+        assert_eq!(
+            Campaign::new("test", vec![stage("first", &["second"]), stage("second", &[])]),
+            Err(CampaignError::UnknownRequirement("first".to_owned()))
+        );
+        assert_eq!(
+            Campaign::new("test", vec![stage("first", &[]), stage("first", &[])]),
+            Err(CampaignError::DuplicateStage("first".to_owned()))
+        );
+    }
+
+    #[test]
+    fn won_stages_unlock_next_ones() {
+        let stages = vec![
+            stage("first", &[]),
+            stage("second", &["first"]),
+            stage("third", &["first", "second"]),
+        ];
+        let mut campaign = Campaign::new("test", stages).unwrap();
+        assert_eq!(campaign.available_stages(), vec!["first"]);
+        assert_eq!(
+            campaign.start("second"),
+            Err(CampaignError::StageIsLocked("second".to_owned()))
+        );
+
+        win(&mut campaign, "first");
+        assert_eq!(campaign.available_stages(), vec!["second"]);
+        win(&mut campaign, "second");
+        assert_eq!(campaign.available_stages(), vec!["third"]);
+        win(&mut campaign, "third");
+        assert!(campaign.is_completed());
+    }
+
+    #[test]
+    fn bonus_money_is_carried_over() {
+        let stages = vec![stage("first", &[]), stage("second", &["first"])];
+        let mut campaign = Campaign::new("test", stages).unwrap();
+
+        let scenario = campaign.start("first").unwrap();
+        let capital = Coord::new(0, 0);
+        let region_id = scenario.engine().location().region_at(capital).unwrap().id();
+        let money = scenario.engine().region_money(region_id).unwrap();
+
+        win(&mut campaign, "first");
+        win(&mut campaign, "first");
+        assert_eq!(campaign.progress().bonus_money(), 5);
+
+        let scenario = campaign.start("second").unwrap();
+        let region_id = scenario.engine().location().region_at(capital).unwrap().id();
+        assert_eq!(scenario.engine().region_money(region_id), Some(money + 5));
+    }
+
+    #[test]
+    fn progress_of_other_campaign_is_refused() {
+        let mut campaign = Campaign::new("test", vec![stage("first", &[])]).unwrap();
+        let mut other = Campaign::new("other", vec![stage("first", &[])]).unwrap();
+        win(&mut other, "first");
+
+        assert_eq!(
+            campaign.set_progress(other.progress().clone()),
+            Err(CampaignError::OtherCampaign("other".to_owned()))
+        );
+        assert!(!campaign.progress().is_won("first"));
+
+        let mut copy = campaign.clone();
+        win(&mut copy, "first");
+        assert_eq!(campaign.set_progress(copy.progress().clone()), Ok(()));
+        assert!(campaign.progress().is_won("first"));
+    }
+
+    #[test]
+    fn unfinished_stage_cannot_be_recorded() {
+        let mut campaign = Campaign::new("test", vec![stage("first", &[])]).unwrap();
+        let scenario = campaign.start("first").unwrap();
+        assert_eq!(
+            campaign.finish("first", &scenario),
+            Err(CampaignError::StageIsNotFinished("first".to_owned()))
+        );
+        assert_eq!(campaign.progress().result("first"), None);
+    }
+
+    #[test]
+    fn locked_stage_cannot_be_recorded() {
+        let stages = vec![stage("first", &[]), stage("second", &["first"])];
+        let mut campaign = Campaign::new("test", stages).unwrap();
+        let mut scenario = campaign.start("first").unwrap();
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(3, 0),
+        };
+        assert_eq!(scenario.act(action), Ok(ScenarioStatus::Succeeded));
+
+        assert_eq!(
+            campaign.finish("second", &scenario),
+            Err(CampaignError::StageIsLocked("second".to_owned()))
+        );
+        assert_eq!(campaign.progress().result("second"), None);
+        assert_eq!(campaign.progress().bonus_money(), 0);
+    }
+}
@@ -0,0 +1,175 @@
+//! Time controls that limit how long players can think over their turns.
+//!
+//! The engine does not read the time itself: the caller provides a `TimeSource` to
+//! `GameEngine::check_time` and `GameEngine::act_with_time`, so tests can use
+//! `ManualTimeSource` and stay deterministic.
+//! All times are in milliseconds.
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::ids::ID;
+
+/// Source of the current time in milliseconds since some fixed moment
+pub trait TimeSource {
+    fn now(&self) -> u64;
+}
+
+/// Time source that reads the system time
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct SystemTimeSource;
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> u64 {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        since_epoch.as_secs() * 1000 + u64::from(since_epoch.subsec_millis())
+    }
+}
+
+/// Time source that only changes when it is told to, for tests and replays. Clones share the
+/// time, so a clone can be handed to a server in another thread and advanced from the test.
+#[derive(Clone, Debug, Default)]
+pub struct ManualTimeSource {
+    now: Arc<AtomicU64>,
+}
+
+impl ManualTimeSource {
+    pub fn new(now: u64) -> Self {
+        ManualTimeSource {
+            now: Arc::new(AtomicU64::new(now)),
+        }
+    }
+
+    pub fn advance(&self, milliseconds: u64) {
+        self.now.fetch_add(milliseconds, Ordering::SeqCst);
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    fn now(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum TimeControl {
+    /// Each turn of a player lasts at most `limit`
+    PerTurn { limit: u64 },
+    /// Each player has `initial` time for the whole game, and `increment` is added to it after
+    /// each of their turns
+    ChessClock { initial: u64, increment: u64 },
+}
+
+/// Time used by players under some time control
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TurnClock {
+    control: TimeControl,
+    /// Time left to players with a chess clock, not counting the current turn
+    remaining: BTreeMap<ID, u64>,
+    /// Time spent on the current turn
+    elapsed: u64,
+    /// Time of the previous update. It is not saved, so the time between saving a game and
+    /// loading it is not counted.
+    #[serde(skip)]
+    last_update: Option<u64>,
+}
+
+impl TurnClock {
+    pub fn new(control: TimeControl, player_ids: &[ID]) -> Self {
+        let remaining = match control {
+            TimeControl::PerTurn { .. } => BTreeMap::new(),
+            TimeControl::ChessClock { initial, .. } => {
+                player_ids.iter().map(|&id| (id, initial)).collect()
+            }
+        };
+        TurnClock {
+            control,
+            remaining,
+            elapsed: 0,
+            last_update: None,
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// Return the time spent on the current turn
+    pub fn elapsed(&self) -> u64 {
+        self.elapsed
+    }
+
+    /// Return the time the player has for their next turn, not counting the current one
+    pub fn budget(&self, player_id: ID) -> u64 {
+        match self.control {
+            TimeControl::PerTurn { limit } => limit,
+            TimeControl::ChessClock { initial, .. } => {
+                self.remaining.get(&player_id).cloned().unwrap_or(initial)
+            }
+        }
+    }
+
+    /// Add the time passed since the previous update to the current turn
+    pub(crate) fn update(&mut self, now: u64) {
+        if let Some(last_update) = self.last_update {
+            self.elapsed += now.saturating_sub(last_update);
+        }
+        self.last_update = Some(now);
+    }
+
+    /// Check if the player has used all their time on the current turn
+    pub(crate) fn is_expired(&self, player_id: ID) -> bool {
+        self.elapsed >= self.budget(player_id)
+    }
+
+    /// Charge the player for the current turn and start the next one
+    pub(crate) fn finish_turn(&mut self, player_id: ID) {
+        if let TimeControl::ChessClock { increment, .. } = self.control {
+            let left = self.budget(player_id).saturating_sub(self.elapsed);
+            self.remaining.insert(player_id, left + increment);
+        }
+        self.elapsed = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TimeControl, TurnClock};
+
+    #[test]
+    fn chess_clock_keeps_unused_time() {
+        let control = TimeControl::ChessClock {
+            initial: 1000,
+            increment: 100,
+        };
+        let mut clock = TurnClock::new(control, &[1, 2]);
+        clock.update(5000);
+        clock.update(5400);
+        assert_eq!(clock.elapsed(), 400);
+        assert!(!clock.is_expired(1));
+        clock.finish_turn(1);
+        assert_eq!(clock.budget(1), 700);
+        assert_eq!(clock.budget(2), 1000);
+
+        clock.update(6500);
+        assert!(clock.is_expired(2));
+        clock.finish_turn(2);
+        assert_eq!(clock.budget(2), 100);
+    }
+
+    #[test]
+    fn per_turn_limit_starts_over() {
+        let mut clock = TurnClock::new(TimeControl::PerTurn { limit: 300 }, &[1, 2]);
+        clock.update(0);
+        clock.update(300);
+        assert!(clock.is_expired(1));
+        clock.finish_turn(1);
+        assert_eq!(clock.elapsed(), 0);
+        assert!(!clock.is_expired(2));
+        assert_eq!(clock.budget(1), 300);
+    }
+}
@@ -8,12 +8,29 @@ pub const EMPTY_TILE_DEFENCE: u8 = 0;
 
 pub const EMPTY_TILE_INCOME: i32 = 1;
 
+pub const MARSH_TILE_INCOME: i32 = 0;
+
+pub const MOUNTAIN_DEFENCE: u8 = 1;
+
+pub const MOUNTAIN_MOVE_COST: u32 = 2;
+
 pub const CONTROLLED_REGION_STARTING_MONEY: i32 = 10;
 
 pub const MIN_LOCATION_LAND_COVERAGE_PCT: u8 = 50;
 
 pub const STANDARD_MOVES_NUM: u32 = 4;
 
+pub const DEFAULT_SIGHT_RADIUS: u32 = 2;
+
+pub const VETERAN_KILLS: u32 = 3;
+
+pub const VETERAN_TURNS: u32 = 10;
+
+pub const VETERAN_DEFENCE_BONUS: u8 = 1;
+
+// Tiles never defend better than this, so a great knight can capture any of them
+pub const MAX_TILE_DEFENCE: u8 = GREAT_KNIGHT.attack - 1;
+
 pub const GRAVE: UnitDescription = UnitDescription {
     name: UnitType::Grave,
     is_unownable: true,
@@ -23,8 +40,10 @@ pub const GRAVE: UnitDescription = UnitDescription {
     max_moves: 0,
     defence: 0,
     attack: 0,
+    attack_range: 0,
     upgrade_levels: 0,
     upgrades_to: None,
+    clears_trees: false,
 };
 
 pub const PINE_TREE: UnitDescription = UnitDescription {
@@ -36,8 +55,10 @@ pub const PINE_TREE: UnitDescription = UnitDescription {
     max_moves: 0,
     defence: 0,
     attack: 0,
+    attack_range: 0,
     upgrade_levels: 0,
     upgrades_to: None,
+    clears_trees: false,
 };
 
 pub const PALM_TREE: UnitDescription = UnitDescription {
@@ -49,8 +70,10 @@ pub const PALM_TREE: UnitDescription = UnitDescription {
     max_moves: 0,
     defence: 0,
     attack: 0,
+    attack_range: 0,
     upgrade_levels: 0,
     upgrades_to: None,
+    clears_trees: false,
 };
 
 pub const VILLAGE: UnitDescription = UnitDescription {
@@ -62,8 +85,10 @@ pub const VILLAGE: UnitDescription = UnitDescription {
     max_moves: 0,
     defence: 1,
     attack: 0,
+    attack_range: 0,
     upgrade_levels: 0,
     upgrades_to: None,
+    clears_trees: false,
 };
 
 pub const TOWER: UnitDescription = UnitDescription {
@@ -75,8 +100,25 @@ pub const TOWER: UnitDescription = UnitDescription {
     max_moves: 0,
     defence: 2,
     attack: 0,
+    attack_range: 0,
+    upgrade_levels: 0,
+    upgrades_to: Some(&CASTLE),
+    clears_trees: false,
+};
+
+pub const CASTLE: UnitDescription = UnitDescription {
+    name: UnitType::Castle,
+    is_unownable: false,
+    is_purchasable: false,
+    purchase_cost: 35,
+    turn_cost: 0,
+    max_moves: 0,
+    defence: 3,
+    attack: 0,
+    attack_range: 0,
     upgrade_levels: 0,
     upgrades_to: None,
+    clears_trees: false,
 };
 
 pub const GREAT_KNIGHT: UnitDescription = UnitDescription {
@@ -88,8 +130,10 @@ pub const GREAT_KNIGHT: UnitDescription = UnitDescription {
     max_moves: STANDARD_MOVES_NUM,
     defence: 3,
     attack: 4,
+    attack_range: 0,
     upgrade_levels: 4,
     upgrades_to: None,
+    clears_trees: false,
 };
 
 pub const KNIGHT: UnitDescription = UnitDescription {
@@ -101,8 +145,10 @@ pub const KNIGHT: UnitDescription = UnitDescription {
     max_moves: STANDARD_MOVES_NUM,
     defence: 3,
     attack: 3,
+    attack_range: 0,
     upgrade_levels: 3,
     upgrades_to: Some(&GREAT_KNIGHT),
+    clears_trees: false,
 };
 
 pub const SOLDIER: UnitDescription = UnitDescription {
@@ -114,8 +160,10 @@ pub const SOLDIER: UnitDescription = UnitDescription {
     max_moves: STANDARD_MOVES_NUM,
     defence: 2,
     attack: 2,
+    attack_range: 0,
     upgrade_levels: 2,
     upgrades_to: Some(&KNIGHT),
+    clears_trees: false,
 };
 
 pub const MILITIA: UnitDescription = UnitDescription {
@@ -127,6 +175,68 @@ pub const MILITIA: UnitDescription = UnitDescription {
     max_moves: STANDARD_MOVES_NUM,
     defence: 1,
     attack: 1,
+    attack_range: 0,
     upgrade_levels: 1,
     upgrades_to: Some(&SOLDIER),
+    clears_trees: false,
+};
+
+pub const BRIDGE: UnitDescription = UnitDescription {
+    name: UnitType::Bridge,
+    is_unownable: false,
+    is_purchasable: true,
+    purchase_cost: 15,
+    turn_cost: 0,
+    max_moves: 0,
+    defence: 0,
+    attack: 0,
+    attack_range: 0,
+    upgrade_levels: 0,
+    upgrades_to: None,
+    clears_trees: false,
+};
+
+pub const BOAT: UnitDescription = UnitDescription {
+    name: UnitType::Boat,
+    is_unownable: false,
+    is_purchasable: true,
+    purchase_cost: 20,
+    turn_cost: 0,
+    max_moves: STANDARD_MOVES_NUM,
+    defence: 0,
+    attack: 0,
+    attack_range: 0,
+    upgrade_levels: 0,
+    upgrades_to: None,
+    clears_trees: false,
+};
+
+pub const ARCHER: UnitDescription = UnitDescription {
+    name: UnitType::Archer,
+    is_unownable: false,
+    is_purchasable: true,
+    purchase_cost: 25,
+    turn_cost: 8,
+    max_moves: STANDARD_MOVES_NUM,
+    defence: 1,
+    attack: 2,
+    attack_range: 2,
+    upgrade_levels: 0,
+    upgrades_to: None,
+    clears_trees: false,
+};
+
+pub const ENGINEER: UnitDescription = UnitDescription {
+    name: UnitType::Engineer,
+    is_unownable: false,
+    is_purchasable: true,
+    purchase_cost: 15,
+    turn_cost: 4,
+    max_moves: STANDARD_MOVES_NUM,
+    defence: 1,
+    attack: 1,
+    attack_range: 0,
+    upgrade_levels: 0,
+    upgrades_to: None,
+    clears_trees: true,
 };
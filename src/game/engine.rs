@@ -1,19 +1,29 @@
-use std::cmp::max;
+use std::cmp::{max, min};
 use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
 
+use super::clock::{TimeControl, TimeSource, TurnClock};
 use super::consts::*;
 use super::ids::{IdProducer, ID};
 use super::location::{
-    Coord, Location, LocationModificationError, LocationValidationError, Player, Region,
-    RegionTransformation, Unit, UnitType,
+    Coord, Location, LocationModificationError, LocationValidationError, Player, PlayerProfile,
+    Region, RegionTransformation, Unit, UnitType,
 };
 use super::rules::{
-    validate_location, validate_regions, LocationRulesValidationError, RegionsValidationError,
+    has_controlled_size, needs_capital, validate_location_for, validate_regions,
+    LocationRulesValidationError, RegionsValidationError, RulesVariant,
 };
-use super::unit::{can_defeat, can_step_on, description, merge_result, UnitInfo};
+use super::stats::{GameStats, PlayerStats, RegionStats, TurnStats};
+use super::unit::{
+    can_defeat, can_step_on, description, is_naval, is_tree, merge_result, merge_result_info,
+    UnitInfo,
+};
+use super::view::PlayerView;
 
 /// An error that can be returned as a result of game engine self validation process.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum EngineValidationError {
     LocationError(LocationRulesValidationError),
     RegionsError(RegionsValidationError),
@@ -41,16 +51,65 @@ impl From<RegionsValidationError> for EngineValidationError {
     }
 }
 
-/// Description of actions that player can do
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+impl fmt::Display for EngineValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EngineValidationError::LocationError(e) => write!(f, "Invalid location: {}", e),
+            EngineValidationError::RegionsError(e) => write!(f, "Invalid regions: {}", e),
+            EngineValidationError::RegionWithoutInfo(id) => {
+                write!(f, "Region {} has no information about its money", id)
+            }
+            EngineValidationError::UnitWithoutInfo(id) => {
+                write!(f, "Unit {} has no information about its moves", id)
+            }
+            EngineValidationError::UnlinkedRegionInfo(id) => {
+                write!(f, "There is information about region {} that does not exist", id)
+            }
+            EngineValidationError::UnlinkedUnitInfo(id) => {
+                write!(f, "There is information about unit {} that does not exist", id)
+            }
+        }
+    }
+}
+
+impl Error for EngineValidationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EngineValidationError::LocationError(e) => Some(e),
+            EngineValidationError::RegionsError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Description of actions that player can do.
+///
+/// Actions are serialized as objects with the name of the action in `type` field and its
+/// arguments in `data` field, e.g. `{"type":"UpgradeUnit","data":{"x":1,"y":-2}}`,
+/// `{"type":"MoveUnit","data":{"src":{"x":0,"y":0},"dst":{"x":1,"y":0}}}` or
+/// `{"type":"EndTurn"}`. Actions with several unnamed arguments have them listed in an array.
+/// Errors below are serialized the same way.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum PlayerAction {
     PlaceNewUnit(ID, UnitType, Coord),
     UpgradeUnit(Coord),
     MoveUnit { src: Coord, dst: Coord },
     EndTurn,
+    /// Offer another player a truce for provided number of turns
+    ProposeTruce { player_id: ID, turns: u32 },
+    /// Accept a truce proposed by another player
+    AcceptTruce(ID),
+    /// Break a truce with another player before it ends
+    BreakTruce(ID),
+    /// Shoot a unit on `dst` with the unit on `src`, see `UnitDescription::attack_range`
+    Shoot { src: Coord, dst: Coord },
+    /// Leave the game, regions of the player with everything on them become neutral
+    Resign,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum PlayerActionError {
     OtherPlayersTurn(ID),
     LocationError(LocationModificationError),
@@ -64,6 +123,12 @@ pub enum PlayerActionError {
     NoUnit(Coord),
     NoUpgrade(UnitType),
     GameAlreadyFinished,
+    CannotNegotiateWith(ID),
+    AlreadyInTruce(ID),
+    InvalidTruceDuration(u32),
+    NoTruceProposal(ID),
+    NoTruce(ID),
+    CannotShoot(UnitType),
 }
 
 impl From<LocationModificationError> for PlayerActionError {
@@ -72,6 +137,189 @@ impl From<LocationModificationError> for PlayerActionError {
     }
 }
 
+impl fmt::Display for PlayerActionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlayerActionError::OtherPlayersTurn(id) => {
+                write!(f, "It is the turn of player {} now", id)
+            }
+            PlayerActionError::LocationError(e) => e.fmt(f),
+            PlayerActionError::InaccessibleLocation(c) => {
+                write!(f, "Tile ({}, {}) cannot be reached", c.x, c.y)
+            }
+            PlayerActionError::AlreadyOccupied(c) => {
+                write!(f, "Tile ({}, {}) is already occupied", c.x, c.y)
+            }
+            PlayerActionError::CannotAttack(c) => {
+                write!(f, "Tile ({}, {}) is too well defended to attack", c.x, c.y)
+            }
+            PlayerActionError::NotEnoughMoney(id) => {
+                write!(f, "Region {} does not have enough money", id)
+            }
+            PlayerActionError::NotEnoughMoves(left, needed) => write!(
+                f,
+                "Unit has {} moves left, but needs {} to get there",
+                left, needed
+            ),
+            PlayerActionError::NotOwned(c) => {
+                write!(f, "Tile ({}, {}) is not owned by the player", c.x, c.y)
+            }
+            PlayerActionError::CannotBePlacedByPlayer(unit_type) => {
+                write!(f, "{:?} cannot be placed by players", unit_type)
+            }
+            PlayerActionError::NoUnit(c) => write!(f, "There is no unit at ({}, {})", c.x, c.y),
+            PlayerActionError::NoUpgrade(unit_type) => {
+                write!(f, "{:?} cannot be upgraded", unit_type)
+            }
+            PlayerActionError::GameAlreadyFinished => write!(f, "The game is already finished"),
+            PlayerActionError::CannotNegotiateWith(id) => {
+                write!(f, "Player {} cannot take part in a truce", id)
+            }
+            PlayerActionError::AlreadyInTruce(id) => {
+                write!(f, "There is already a truce with player {}", id)
+            }
+            PlayerActionError::InvalidTruceDuration(turns) => {
+                write!(f, "Truce cannot last {} turns", turns)
+            }
+            PlayerActionError::NoTruceProposal(id) => {
+                write!(f, "Player {} has not proposed a truce", id)
+            }
+            PlayerActionError::NoTruce(id) => write!(f, "There is no truce with player {}", id),
+            PlayerActionError::CannotShoot(unit_type) => write!(f, "{:?} cannot shoot", unit_type),
+        }
+    }
+}
+
+impl Error for PlayerActionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PlayerActionError::LocationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Notable changes of the game state that happen as consequences of players' actions or turn
+/// changes and cannot be easily noticed by looking at the location
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum GameEvent {
+    /// Unit has moved along the path, that starts with its old coordinate and ends with the new
+    /// one. If it has merged with another unit, `unit_type` is the type before merge.
+    UnitMoved {
+        player_id: ID,
+        unit_type: UnitType,
+        path: Vec<Coord>,
+    },
+    /// Unit was shot by a unit of another player and left a grave on its place
+    UnitShot {
+        player_id: ID,
+        unit_type: UnitType,
+        coordinate: Coord,
+    },
+    /// Unit died because its region could not pay for it and left a grave on its place
+    UnitStarved {
+        player_id: ID,
+        unit_type: UnitType,
+        coordinate: Coord,
+    },
+    /// Player has lost all of their active regions and does not take part in the game anymore,
+    /// their remaining land becomes neutral
+    PlayerDefeated(ID),
+    /// Player has left the game and their regions have become neutral
+    PlayerResigned(ID),
+    /// Player has won the game, alone or together with their allies
+    GameWon(ID),
+    /// Player has offered a truce to another player
+    TruceProposed { from: ID, to: ID, turns: u32 },
+    /// Player has accepted a truce, it lasts until the provided turn starts
+    TruceAccepted { from: ID, to: ID, ends_on_turn: u32 },
+    /// Player has broken a truce before its end
+    TruceBroken { by: ID, with: ID },
+    /// Truce between two players has ended
+    TruceExpired(ID, ID),
+    /// Player has run out of time and their turn has been ended
+    TurnTimedOut(ID),
+}
+
+/// Agreement of two players not to attack each other
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct Truce {
+    /// Players of the truce, the smaller id first
+    pub players: (ID, ID),
+    /// The first turn when the truce is not in force anymore
+    pub ends_on_turn: u32,
+}
+
+impl Truce {
+    fn new(first: ID, second: ID, ends_on_turn: u32) -> Self {
+        Truce {
+            players: (first.min(second), first.max(second)),
+            ends_on_turn,
+        }
+    }
+
+    /// Return true if the truce is made between provided players
+    pub fn binds(&self, first: ID, second: ID) -> bool {
+        self.players == (first.min(second), first.max(second))
+    }
+}
+
+/// Truce that one player has offered to another one and that is not accepted yet
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct TruceProposal {
+    pub from: ID,
+    pub to: ID,
+    pub turns: u32,
+}
+
+/// What happens when a unit moves to a tile
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum MoveKind {
+    /// Unit moves to an empty tile of its region
+    Move,
+    /// Unit merges with another unit of its region
+    Merge,
+    /// Unit captures a tile of another player
+    Attack,
+}
+
+/// Tile that a unit can move to during the current turn
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct ReachableTile {
+    /// Moves that the unit will have after getting to the tile
+    pub moves_left: u32,
+    pub kind: MoveKind,
+}
+
+/// Unit id, moves to subtract, region id, relocation flag, id of unit to merge with, type of
+/// merged unit and path to the destination
+type MovingUnitPreparation = (ID, u32, ID, bool, Option<ID>, Option<UnitType>, Vec<Coord>);
+
+/// Player of the game with their profile
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    #[serde(flatten)]
+    player: Player,
+    /// Games saved before profiles appeared have no profiles, `GameEngine::repair` gives them the
+    /// default ones
+    #[serde(default = "PlayerProfile::unnamed")]
+    profile: PlayerProfile,
+}
+
+impl PlayerInfo {
+    pub fn id(&self) -> ID {
+        self.player.id()
+    }
+
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    pub fn profile(&self) -> &PlayerProfile {
+        &self.profile
+    }
+}
+
 /// Regional information that is stored on game engine level
 /// money_balance value is stored only here, other values are recountable and stored only for caching purposes
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
@@ -98,14 +346,17 @@ impl RegionInfo {
         self.money_balance += diff;
     }
 
-    fn recount(&mut self, region: &Region, location: &Location) {
+    fn recount(&mut self, region: &Region, location: &Location, cargo: &HashMap<ID, Unit>) {
         let mut new_income = 0;
         let mut new_maintenance = 0;
         for coordinate in region.coordinates().iter() {
             let tile = location.tile_at(*coordinate).unwrap();
-            new_income += EMPTY_TILE_INCOME;
+            new_income += tile.surface().income();
             if let Some(unit) = tile.unit() {
                 new_maintenance += description(unit.unit_type()).turn_cost;
+                if let Some(carried) = cargo.get(&unit.id()) {
+                    new_maintenance += description(carried.unit_type()).turn_cost;
+                }
             }
         }
         self.income_from_fields = new_income;
@@ -116,7 +367,7 @@ impl RegionInfo {
 /// Game engine struct stores the whole state of the game and allows players to make their turns
 #[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct GameEngine {
-    players: Vec<Player>,
+    players: Vec<PlayerInfo>,
     player_activity: HashMap<ID, bool>,
     winner: Option<ID>,
     current_turn: u32,
@@ -127,6 +378,43 @@ pub struct GameEngine {
     unit_info: HashMap<ID, UnitInfo>,
 
     id_producer: IdProducer,
+
+    /// Rules that decide what location can be played and what units can be bought
+    #[serde(default)]
+    rules_variant: RulesVariant,
+    /// Land units that are aboard boats, by ids of boats
+    #[serde(default)]
+    cargo: HashMap<ID, Unit>,
+
+    /// Groups of players that play as one team, each group is sorted by id
+    #[serde(default)]
+    alliances: Vec<Vec<ID>>,
+    #[serde(default)]
+    truces: Vec<Truce>,
+    #[serde(default)]
+    truce_proposals: Vec<TruceProposal>,
+
+    /// Distance from player's regions at which player can see the location in `PlayerView`
+    #[serde(default = "default_sight_radius")]
+    sight_radius: u32,
+
+    /// Statistics of players recorded at the end of each turn
+    #[serde(default)]
+    stats: GameStats,
+    /// Groups of players in the order they were defeated, each group is sorted by id
+    #[serde(default)]
+    defeated: Vec<Vec<ID>>,
+    /// Time used by players if turns are limited in time
+    #[serde(default)]
+    clock: Option<TurnClock>,
+
+    /// Events that have happened since the last call of `take_events`
+    #[serde(skip)]
+    events: Vec<GameEvent>,
+}
+
+fn default_sight_radius() -> u32 {
+    DEFAULT_SIGHT_RADIUS
 }
 
 impl GameEngine {
@@ -134,10 +422,20 @@ impl GameEngine {
         location: Location,
         players: Vec<Player>,
         id_producer: IdProducer,
+    ) -> Result<Self, EngineValidationError> {
+        Self::with_rules(location, players, id_producer, RulesVariant::default())
+    }
+
+    /// Create an engine that plays the location by the provided variant of rules
+    pub fn with_rules(
+        location: Location,
+        players: Vec<Player>,
+        id_producer: IdProducer,
+        rules_variant: RulesVariant,
     ) -> Result<Self, EngineValidationError> {
         let mut region_info = HashMap::default();
         for (id, region) in location.regions().iter() {
-            let money = if region.coordinates().len() >= MIN_CONTROLLED_REGION_SIZE {
+            let money = if needs_capital(&location, region) {
                 RegionInfo::new(CONTROLLED_REGION_STARTING_MONEY)
             } else {
                 RegionInfo::new(0)
@@ -145,6 +443,13 @@ impl GameEngine {
             region_info.insert(id.clone(), money);
         }
         let player_activity: HashMap<ID, bool> = players.iter().map(|p| (p.id(), true)).collect();
+        let players: Vec<PlayerInfo> = players
+            .into_iter()
+            .enumerate()
+            .map(|(i, player)| PlayerInfo {
+                player,
+                profile: PlayerProfile::numbered(i),
+            }).collect();
         let unit_info: HashMap<ID, UnitInfo> = location
             .map()
             .values()
@@ -158,6 +463,16 @@ impl GameEngine {
             unit_info,
             region_info,
             id_producer,
+            rules_variant,
+            cargo: HashMap::new(),
+            alliances: Vec::new(),
+            truces: Vec::new(),
+            truce_proposals: Vec::new(),
+            sight_radius: DEFAULT_SIGHT_RADIUS,
+            stats: GameStats::default(),
+            defeated: Vec::new(),
+            clock: None,
+            events: Vec::new(),
             winner: None,
             current_turn: 1,
             active_player_num: 0,
@@ -174,12 +489,23 @@ impl GameEngine {
     /// Fix all countable fields
     pub fn repair(&mut self) {
         self.recount_region_info();
-        let mut to_fix = Vec::new();
-        for tile in self.location().map().values() {
-            if let Some(unit) = tile.unit() {
-                to_fix.push((unit.id(), unit.unit_type()));
+        // Games saved before profiles appeared get the default ones, broken colors are replaced
+        // by the default ones too
+        for (i, info) in self.players.iter_mut().enumerate() {
+            if info.profile == PlayerProfile::unnamed() {
+                info.profile = PlayerProfile::numbered(i);
+            } else if !info.profile.has_valid_color() {
+                info.profile.color = PlayerProfile::numbered(i).color;
             }
         }
+        let to_fix: Vec<(ID, UnitType)> = self
+            .location
+            .map()
+            .values()
+            .filter_map(|t| t.unit())
+            .chain(self.cargo.values())
+            .map(|u| (u.id(), u.unit_type()))
+            .collect();
 
         for (id, unit_type) in to_fix.into_iter() {
             let info = self.unit_info.get_mut(&id).unwrap();
@@ -193,10 +519,10 @@ impl GameEngine {
             .players
             .iter()
             .filter(|p| self.player_activity[&p.id()])
-            .cloned()
+            .map(PlayerInfo::player)
             .collect();
 
-        validate_location(&self.location)?;
+        validate_location_for(&self.location, self.rules_variant)?;
         validate_regions(&self.location, &active_players.as_slice())?;
         self.validate_internal_consistency()?;
 
@@ -218,12 +544,13 @@ impl GameEngine {
         }
 
         let mut unit_ids: HashSet<ID> = self.unit_info.keys().cloned().collect();
-        for tile in self.location.map().values() {
-            let unit = tile.unit();
-            if unit.is_none() {
-                continue;
-            }
-            let unit = unit.unwrap();
+        let units = self
+            .location
+            .map()
+            .values()
+            .filter_map(|t| t.unit())
+            .chain(self.cargo.values());
+        for unit in units {
             if !unit_ids.contains(&unit.id()) {
                 return Err(EngineValidationError::UnitWithoutInfo(unit.id()));
             }
@@ -237,7 +564,8 @@ impl GameEngine {
         Ok(())
     }
 
-    pub fn players(&self) -> &Vec<Player> {
+    /// Return players in the order of their turns, with their profiles
+    pub fn players(&self) -> &Vec<PlayerInfo> {
         &self.players
     }
 
@@ -245,24 +573,328 @@ impl GameEngine {
         &self.location
     }
 
+    /// Return the profile of the player, every player of the game has one
+    pub fn profile(&self, player_id: ID) -> Option<&PlayerProfile> {
+        self.players
+            .iter()
+            .find(|p| p.id() == player_id)
+            .map(PlayerInfo::profile)
+    }
+
+    /// Replace profiles of provided players, profiles of unknown players and profiles with
+    /// colors not in the `#rrggbb` form are ignored
+    pub fn set_profiles(&mut self, mut profiles: HashMap<ID, PlayerProfile>) {
+        for info in self.players.iter_mut() {
+            match profiles.remove(&info.id()) {
+                Some(profile) if profile.has_valid_color() => info.profile = profile,
+                _ => (),
+            }
+        }
+    }
+
+    pub fn rules_variant(&self) -> RulesVariant {
+        self.rules_variant
+    }
+
+    /// Return a unit that is aboard a boat on provided coordinate, if any
+    pub fn cargo_at(&self, coordinate: Coord) -> Option<&Unit> {
+        let boat = self.location.tile_at(coordinate)?.unit()?;
+        self.cargo.get(&boat.id())
+    }
+
     pub fn current_turn(&self) -> u32 {
         self.current_turn
     }
 
+    /// Return the player who has won the game. When the game is won by an alliance, the first of
+    /// its players in the order of turns is returned, see `winners` for the whole alliance.
     pub fn winner(&self) -> Option<ID> {
         self.winner
     }
 
+    /// Return all players who have won the game in the order of turns: the winner and all of their
+    /// allies, including the defeated ones
+    pub fn winners(&self) -> Vec<ID> {
+        match self.winner {
+            Some(winner) => self
+                .players
+                .iter()
+                .map(|p| p.id())
+                .filter(|&id| self.are_allies(winner, id))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Return groups of players in the order they were defeated, players of one group were
+    /// defeated by the same action
+    pub fn defeated_players(&self) -> &Vec<Vec<ID>> {
+        &self.defeated
+    }
+
+    /// Check that the player still has regions to play with and was not defeated
+    pub fn is_player_active(&self, player_id: ID) -> bool {
+        self.player_activity.get(&player_id) == Some(&true)
+    }
+
+    pub fn alliances(&self) -> &Vec<Vec<ID>> {
+        &self.alliances
+    }
+
+    /// Set groups of players that play as one team. Allies cannot attack each other and win the
+    /// game together.
+    pub fn set_alliances(&mut self, mut alliances: Vec<Vec<ID>>) {
+        for alliance in alliances.iter_mut() {
+            alliance.sort();
+        }
+        self.alliances = alliances;
+    }
+
+    /// Return true if players are the same player or belong to the same alliance
+    pub fn are_allies(&self, first: ID, second: ID) -> bool {
+        first == second
+            || self
+                .alliances
+                .iter()
+                .any(|a| a.contains(&first) && a.contains(&second))
+    }
+
+    /// Return truces that are in force now
+    pub fn truces(&self) -> &Vec<Truce> {
+        &self.truces
+    }
+
+    /// Return truces that were proposed, but not accepted yet
+    pub fn truce_proposals(&self) -> &Vec<TruceProposal> {
+        &self.truce_proposals
+    }
+
+    pub fn have_truce(&self, first: ID, second: ID) -> bool {
+        self.truces.iter().any(|t| t.binds(first, second))
+    }
+
     pub fn region_money(&self, region_id: ID) -> Option<i32> {
         self.region_info.get(&region_id).map(|ri| ri.money_balance)
     }
 
+    /// Return how much money region will earn (or lose, if the result is negative) at the end of
+    /// the turn
+    pub fn region_profit(&self, region_id: ID) -> Option<i32> {
+        self.region_info
+            .get(&region_id)
+            .map(|ri| ri.income_from_fields - ri.maintenance_cost)
+    }
+
+    /// Give money to the biggest region of the player, e.g. a bonus carried over from a previous
+    /// game. Returns the id of the region that got the money.
+    pub fn grant_money(&mut self, player_id: ID, amount: i32) -> Option<ID> {
+        let region_id = self
+            .location
+            .regions()
+            .values()
+            .filter(|r| r.owner().id() == player_id)
+            .max_by_key(|r| (r.coordinates().len(), r.id()))?
+            .id();
+        self.modify_money(region_id, amount);
+        Some(region_id)
+    }
+
+    /// Return a number of moves unit with provided ID can make during this turn
+    pub fn unit_moves_left(&self, unit_id: ID) -> Option<u32> {
+        self.unit_info.get(&unit_id).map(UnitInfo::moves_left)
+    }
+
+    pub fn unit_is_veteran(&self, unit_id: ID) -> Option<bool> {
+        self.unit_info.get(&unit_id).map(UnitInfo::is_veteran)
+    }
+
+    pub fn unit_kills(&self, unit_id: ID) -> Option<u32> {
+        self.unit_info.get(&unit_id).map(UnitInfo::kills)
+    }
+
+    /// Return statistics of players over all finished turns
+    pub fn stats(&self) -> &GameStats {
+        &self.stats
+    }
+
+    /// Return all events that have happened since the previous call of this method
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        self.events.drain(..).collect()
+    }
+
     pub fn active_player_num(&self) -> usize {
         self.active_player_num
     }
 
     pub fn active_player(&self) -> &Player {
-        &self.players[self.active_player_num]
+        &self.players[self.active_player_num].player
+    }
+
+    pub fn clock(&self) -> Option<&TurnClock> {
+        self.clock.as_ref()
+    }
+
+    /// Limit the time of turns, or remove the limit with `None`. Players start with no time used.
+    pub fn set_time_control(&mut self, control: Option<TimeControl>) {
+        let player_ids: Vec<ID> = self.players.iter().map(|p| p.id()).collect();
+        self.clock = control.map(|c| TurnClock::new(c, &player_ids));
+    }
+
+    /// Return the time left to the player for the current turn if they are active, or for their
+    /// next turn otherwise. Returns `None` if turns are not limited in time.
+    pub fn time_left(&self, player_id: ID) -> Option<u64> {
+        let clock = self.clock.as_ref()?;
+        let budget = clock.budget(player_id);
+        if player_id == self.active_player().id() {
+            Some(budget.saturating_sub(clock.elapsed()))
+        } else {
+            Some(budget)
+        }
+    }
+
+    /// Count the time passed since the previous check as used by the active player, and end
+    /// their turn if they have run out of time. Returns the id of the player whose turn has been
+    /// ended.
+    ///
+    /// Time is only counted by this method and `act_with_time`, so one of them should be called
+    /// before each action and this one periodically while the game waits for a player.
+    pub fn check_time(&mut self, time: &dyn TimeSource) -> Option<ID> {
+        if self.winner.is_some() {
+            return None;
+        }
+        let player_id = self.active_player().id();
+        let clock = self.clock.as_mut()?;
+        clock.update(time.now());
+        if !clock.is_expired(player_id) {
+            return None;
+        }
+
+        self.events.push(GameEvent::TurnTimedOut(player_id));
+        self.act(player_id, PlayerAction::EndTurn)
+            .expect("Active player should always be able to end their turn");
+        Some(player_id)
+    }
+
+    /// Make an action at the current time. The time passed since the previous check is counted
+    /// as used by the active player, and if the action ends their turn, the turn of the next player
+    /// starts at the current time.
+    pub fn act_with_time(
+        &mut self,
+        player_id: ID,
+        action: PlayerAction,
+        time: &dyn TimeSource,
+    ) -> Result<(), PlayerActionError> {
+        if let Some(clock) = self.clock.as_mut() {
+            clock.update(time.now());
+        }
+        self.act(player_id, action)
+    }
+
+    pub fn sight_radius(&self) -> u32 {
+        self.sight_radius
+    }
+
+    pub fn set_sight_radius(&mut self, sight_radius: u32) {
+        self.sight_radius = sight_radius;
+    }
+
+    /// Return the part of the game that provided player can see. See `PlayerView` for details.
+    pub fn view_for(&self, player_id: ID) -> PlayerView {
+        PlayerView::new(self, player_id)
+    }
+
+    /// Return tiles that a unit on provided coordinate can move to during this turn. Tiles are
+    /// checked with the same rules as `PlayerAction::MoveUnit` uses, so the move to any of them
+    /// succeeds if the game has not changed. Merges and attacks take all moves of the unit.
+    ///
+    /// Returns an empty map if there is no unit of a region on the coordinate.
+    ///
+    /// If there is a boat with a unit aboard on the coordinate, the map also contains land tiles
+    /// that the unit aboard can land on.
+    pub fn reachable_tiles(&self, src: Coord) -> HashMap<Coord, ReachableTile> {
+        let unit = self.location.tile_at(src).and_then(|t| t.unit());
+        let (unit, region) = match (unit, self.location.region_at(src)) {
+            (Some(unit), Some(region)) => (unit, region),
+            _ => return HashMap::new(),
+        };
+        let mut reachable = self.reachable_tiles_of(unit, region, src);
+        if let Some(carried) = self.cargo.get(&unit.id()) {
+            // Unit aboard leaves the boat only for tiles the boat cannot sail to
+            let landing = self
+                .reachable_tiles_of(carried, region, src)
+                .into_iter()
+                .filter(|(c, _)| {
+                    !can_step_on(unit.unit_type(), self.location.tile_at(*c).unwrap())
+                });
+            reachable.extend(landing);
+        }
+        reachable
+    }
+
+    fn reachable_tiles_of(
+        &self,
+        unit: &Unit,
+        region: &Region,
+        src: Coord,
+    ) -> HashMap<Coord, ReachableTile> {
+        let mut reachable = HashMap::new();
+        let moves_left = self.unit_info(unit.id()).moves_left();
+        let move_cost = |c| self.location.tile_at(c).unwrap().surface().move_cost();
+
+        // Unit passes only through tiles of its region, but can finish its move next to them
+        let can_pass =
+            |c| c == src || self.unit_can_step_on_coord(unit.unit_type(), c, region.id(), false);
+        let costs: HashMap<Coord, u32> = if self.location.has_uniform_move_costs() {
+            self.location
+                .bfs_iter(src, can_pass)
+                .map(|(distance, c)| (c, distance))
+                .collect()
+        } else {
+            self.location.path_costs(src, |c| {
+                if can_pass(c) {
+                    Some(move_cost(c))
+                } else {
+                    None
+                }
+            })
+        };
+        let mut distances: HashMap<Coord, u32> = HashMap::new();
+        for (&coordinate, &cost) in costs.iter() {
+            for &dst in coordinate.neighbors().iter() {
+                if dst == src || self.location.tile_at(dst).is_none() {
+                    continue;
+                }
+                let distance = cost + move_cost(dst);
+                let best = distances.entry(dst).or_insert(distance);
+                *best = (*best).min(distance);
+            }
+        }
+
+        for (dst, distance) in distances {
+            if distance > moves_left {
+                continue;
+            }
+            let placing =
+                self.prepare_placing_unit(region.owner().id(), region.id(), unit.unit_type(), dst);
+            let clears_tree = self.clears_tree(unit.unit_type(), dst);
+            let tile = match placing {
+                Ok((false, Some(_), _)) if !clears_tree => ReachableTile {
+                    moves_left: 0,
+                    kind: MoveKind::Merge,
+                },
+                Ok((true, _, _)) if !is_naval(unit.unit_type()) => ReachableTile {
+                    moves_left: 0,
+                    kind: MoveKind::Attack,
+                },
+                Ok(_) => ReachableTile {
+                    moves_left: moves_left - distance,
+                    kind: MoveKind::Move,
+                },
+                Err(_) => continue,
+            };
+            reachable.insert(dst, tile);
+        }
+        reachable
     }
 
     /// Perform an action for specified player
@@ -276,6 +908,14 @@ impl GameEngine {
             }
             PlayerAction::UpgradeUnit(dst) => self.upgrade_unit(player_id, dst)?,
             PlayerAction::EndTurn => self.end_players_turn(),
+            PlayerAction::ProposeTruce {
+                player_id: other_id,
+                turns,
+            } => self.propose_truce(player_id, other_id, turns)?,
+            PlayerAction::AcceptTruce(other_id) => self.accept_truce(player_id, other_id)?,
+            PlayerAction::BreakTruce(other_id) => self.break_truce(player_id, other_id)?,
+            PlayerAction::Shoot { src, dst } => self.shoot(player_id, src, dst)?,
+            PlayerAction::Resign => self.resign(player_id),
         }
 
         self.recount_region_info();
@@ -286,18 +926,55 @@ impl GameEngine {
         Ok(())
     }
 
+    /// Check the action of the player without making it. Returns the error that `act` would
+    /// return for it, or `Ok` if `act` would succeed, so it is cheaper than acting on a clone of
+    /// the engine.
+    pub fn check_action(
+        &self,
+        player_id: ID,
+        action: &PlayerAction,
+    ) -> Result<(), PlayerActionError> {
+        self.validate_action(player_id, action)?;
+
+        match *action {
+            PlayerAction::MoveUnit { src, dst } => {
+                self.prepare_moving_unit(player_id, src, dst)?;
+            }
+            PlayerAction::PlaceNewUnit(orig_region_id, unit, dst) => {
+                self.prepare_buying_unit(player_id, orig_region_id, unit, dst)?;
+            }
+            PlayerAction::UpgradeUnit(dst) => {
+                self.prepare_upgrading_unit(player_id, dst)?;
+            }
+            PlayerAction::ProposeTruce {
+                player_id: other_id,
+                turns,
+            } => self.check_truce_proposal(player_id, other_id, turns)?,
+            PlayerAction::AcceptTruce(other_id) => {
+                self.find_truce_proposal(player_id, other_id)?;
+            }
+            PlayerAction::BreakTruce(other_id) => self.check_truce(player_id, other_id)?,
+            PlayerAction::Shoot { src, dst } => {
+                self.prepare_shooting(player_id, src, dst)?;
+            }
+            PlayerAction::EndTurn | PlayerAction::Resign => (),
+        }
+
+        Ok(())
+    }
+
     /// Check if some active players became unactive and update engine information about them
     fn check_for_active_players(&mut self) {
         let mut owner_to_active_regions_num: HashMap<ID, u32> = HashMap::new();
         for region in self.location.regions().values() {
-            if region.coordinates().len() < MIN_CONTROLLED_REGION_SIZE {
-                let coordinate = *region.coordinates().iter().next().unwrap();
-                // If there is a moving unit on last tile of region - it is still active
-                if let Some(unit) = self.location.tile_at(coordinate).unwrap().unit() {
-                    if description(unit.unit_type()).max_moves == 0 {
-                        continue;
-                    }
-                } else {
+            if !has_controlled_size(&self.location, region) {
+                // If there is a moving unit on last land of region - it is still active
+                let has_moving_unit = region
+                    .coordinates()
+                    .iter()
+                    .filter_map(|&c| self.location.tile_at(c).unwrap().unit())
+                    .any(|u| description(u.unit_type()).max_moves > 0);
+                if !has_moving_unit {
                     continue;
                 }
             }
@@ -320,23 +997,78 @@ impl GameEngine {
             .collect();
 
         if !set_inactive.is_empty() {
-            for &id in set_inactive.iter() {
+            let mut defeated: Vec<ID> = set_inactive.iter().cloned().collect();
+            defeated.sort();
+            for &id in defeated.iter() {
                 self.player_activity.insert(id, false);
+                self.events.push(GameEvent::PlayerDefeated(id));
             }
-            for (id, region) in self.location.regions() {
-                if set_inactive.contains(&region.owner().id()) {
-                    let info = self.region_info.get_mut(id).unwrap();
-                    info.money_balance = 0;
-                }
+            for &id in defeated.iter() {
+                self.make_regions_neutral(id);
+                self.drop_truces_of(id);
+            }
+            self.defeated.push(defeated);
+        }
+    }
+
+    /// Give all regions of the player to the neutral owner. The regions lose their capitals and
+    /// money, and units that can move leave graves like starving ones, as nobody pays for them
+    /// anymore. Other units and boats stay.
+    fn make_regions_neutral(&mut self, player_id: ID) {
+        let mut region_ids: Vec<ID> = self
+            .location
+            .regions()
+            .values()
+            .filter(|r| r.owner().id() == player_id)
+            .map(|r| r.id())
+            .collect();
+        region_ids.sort();
+        let mut coordinates: Vec<Coord> = region_ids
+            .iter()
+            .flat_map(|id| self.location.regions()[id].coordinates().iter().cloned())
+            .collect();
+        coordinates.sort();
+        for coordinate in coordinates.into_iter() {
+            let tile = self.location.tile_at(coordinate).unwrap();
+            let is_land = tile.surface().is_land();
+            let unit = match tile.unit() {
+                Some(&unit) => unit,
+                None => continue,
+            };
+            // Boats stay on the water, but units aboard them are gone
+            if let Some(carried) = self.cargo.remove(&unit.id()) {
+                self.unit_info.remove(&carried.id());
+            }
+            if is_land && description(unit.unit_type()).max_moves > 0 {
+                self.maybe_remove_unit(coordinate);
+                self.create_and_place_unit(UnitType::Grave, coordinate)
+                    .unwrap();
             }
         }
+        for region_id in region_ids.into_iter() {
+            let changes = self
+                .location
+                .set_region_owner(region_id, Player::neutral())
+                .expect("Region should exist as its id is taken from the location");
+            self.apply_region_transformations(changes);
+            self.fix_capital(region_id);
+            self.region_info.get_mut(&region_id).unwrap().money_balance = 0;
+        }
+    }
+
+    /// Remove truces and truce proposals of a player who has left the game
+    fn drop_truces_of(&mut self, player_id: ID) {
+        self.truces
+            .retain(|t| t.players.0 != player_id && t.players.1 != player_id);
+        self.truce_proposals
+            .retain(|p| p.from != player_id && p.to != player_id);
     }
 
     /// Update region info for each region on the map
     fn recount_region_info(&mut self) {
         for (id, region) in self.location.regions() {
             let info = self.region_info.get_mut(&id).unwrap();
-            info.recount(region, &self.location);
+            info.recount(region, &self.location, &self.cargo);
         }
     }
 
@@ -351,8 +1083,8 @@ impl GameEngine {
     fn region_at(&self, coordinate: Coord) -> Result<&Region, PlayerActionError> {
         self.location
             .region_at(coordinate)
-            // According to game rules region can be only on land, so this also checks if we're
-            // trying to place unit on water. We will need to change that if rules change
+            // According to game rules all land belongs to regions, so this also checks if we're
+            // trying to place unit on water that has no bridge or boat on it
             .ok_or_else(|| PlayerActionError::InaccessibleLocation(coordinate))
     }
 
@@ -371,14 +1103,18 @@ impl GameEngine {
         if !self.unit_can_step_on_coord(unit_type, dst, originating_region_id, true) {
             return Err(PlayerActionError::InaccessibleLocation(dst));
         }
-        let dst_region = self.region_at(dst)?;
-        let need_relocation = dst_region.id() != originating_region_id;
+        if self.can_board(unit_type, dst) {
+            return Ok((false, None, None));
+        }
+        // Naval units can step on water that does not belong to any region
+        let dst_region = self.location.region_at(dst);
+        let need_relocation = dst_region.map(|r| r.id()) != Some(originating_region_id);
 
         let tile = self.location.tile_at(dst).unwrap();
         let mut upgrade_to: Option<UnitType> = None;
         let old_unit_to_remove = if let Some(current_unit) = tile.unit() {
             // We cannot replace unit of the same owner
-            if dst_region.owner().id() == player_id {
+            if dst_region.map(|r| r.owner().id()) == Some(player_id) {
                 let possible_merge_result = merge_result(unit_type, current_unit.unit_type());
                 if possible_merge_result.is_none() {
                     return Err(PlayerActionError::AlreadyOccupied(dst));
@@ -386,7 +1122,7 @@ impl GameEngine {
                 if possible_merge_result.unwrap() != unit_type {
                     upgrade_to = possible_merge_result;
                 }
-            } else if !can_defeat(unit_type, current_unit.unit_type()) {
+            } else if !can_defeat(unit_type, self.unit_info(current_unit.id())) {
                 return Err(PlayerActionError::CannotAttack(dst));
             }
 
@@ -406,7 +1142,9 @@ impl GameEngine {
         dst: Coord,
     ) -> Result<(bool, Option<ID>), PlayerActionError> {
         let unit_description = description(unit_type);
-        if !unit_description.is_purchasable {
+        if !unit_description.is_purchasable
+            || (is_naval(unit_type) && self.rules_variant != RulesVariant::Archipelago)
+        {
             return Err(PlayerActionError::CannotBePlacedByPlayer(unit_type));
         }
         let (need_relocation, old_unit_to_remove, upgrade_to) =
@@ -416,6 +1154,18 @@ impl GameEngine {
         if upgrade_to.is_some() {
             return Err(PlayerActionError::InaccessibleLocation(dst));
         }
+        // Nor to put it aboard a boat
+        let tile = self.location.tile_at(dst).unwrap();
+        if tile.unit().map(|u| is_naval(u.unit_type())) == Some(true) {
+            return Err(PlayerActionError::AlreadyOccupied(dst));
+        }
+        // Water is taken only next to the region, as there is no path to check
+        let region = &self.location.regions()[&originating_region_id];
+        if self.location.region_at(dst).is_none()
+            && !dst.neighbors().iter().any(|c| region.coordinates().contains(c))
+        {
+            return Err(PlayerActionError::InaccessibleLocation(dst));
+        }
         let region_info = self.region_info[&originating_region_id];
         if !region_info.can_afford(unit_description.purchase_cost) {
             return Err(PlayerActionError::NotEnoughMoney(originating_region_id));
@@ -434,13 +1184,16 @@ impl GameEngine {
         let (need_relocation, old_unit_to_remove) =
             self.prepare_buying_unit(player_id, originating_region_id, unit_type, dst)?;
 
-        self.create_and_place_unit(unit_type, dst)?;
+        let unit_id = self.create_and_place_unit(unit_type, dst)?;
         if need_relocation {
             self.add_tile_to_region(dst, originating_region_id)?;
         }
 
         if let Some(old_unit_id) = old_unit_to_remove {
-            self.unit_info.remove(&old_unit_id);
+            let old_info = self.unit_info.remove(&old_unit_id).unwrap();
+            if need_relocation && old_info.description().max_moves > 0 {
+                self.unit_info.get_mut(&unit_id).unwrap().add_kill();
+            }
         }
         self.modify_money(
             originating_region_id,
@@ -455,34 +1208,60 @@ impl GameEngine {
         coordinate: Coord,
         region_id: ID,
     ) -> Result<(), PlayerActionError> {
-        let old_region_id = self.location.region_at(coordinate).unwrap().id();
+        // Water taken by a naval unit does not belong to any region before
+        let old_region = self.location.region_at(coordinate);
+        let old_region_id = old_region.map(|r| r.id());
+        let old_owner_id = old_region.map(|r| r.owner().id());
+        let owner_id = self.location.regions()[&region_id].owner().id();
+        if old_owner_id != Some(owner_id)
+            && !self.location.tile_at(coordinate).unwrap().surface().is_water()
+        {
+            self.stats.record_capture(owner_id);
+        }
         // We need to handle region changes after it.
         let res = self
             .location
             .add_tile_to_region(coordinate, region_id, &mut self.id_producer)?;
-        if res.is_empty() {
-            self.fix_capital(old_region_id);
-        }
-        for change in res.iter() {
-            match change {
-                RegionTransformation::Delete(id) => {
-                    self.region_info.remove(&id);
-                }
-                RegionTransformation::Merge { from, into } => self.merge_regions(*from, *into),
-                RegionTransformation::Split { from, into } => {
-                    self.split_region(*from, into.clone())
-                }
+        self.apply_region_transformations(res);
+        // Region that lost the tile could lose its capital with it, and region that got the tile
+        // could become big enough to need one
+        if let Some(old_region_id) = old_region_id {
+            if self.location.regions().contains_key(&old_region_id) {
+                self.fix_capital(old_region_id);
             }
         }
+        self.fix_capital(region_id);
 
         Ok(())
     }
 
-    fn merge_regions(&mut self, from: ID, into: ID) {
-        self.fix_capital(into);
-        let src = self.region_info.remove(&from).unwrap();
-        let dst = self.region_info.get_mut(&into).unwrap();
-        dst.change_balance(src.money_balance);
+    /// Remove a tile from its region, when a naval unit leaves the water
+    fn remove_tile_from_region(&mut self, coordinate: Coord) -> Result<(), PlayerActionError> {
+        let res = self
+            .location
+            .remove_tile_from_region(coordinate, &mut self.id_producer)?;
+        self.apply_region_transformations(res);
+
+        Ok(())
+    }
+
+    fn apply_region_transformations(&mut self, changes: Vec<RegionTransformation>) {
+        for change in changes.into_iter() {
+            match change {
+                RegionTransformation::Delete(id) => {
+                    self.region_info.remove(&id);
+                }
+                RegionTransformation::Merge { from, into } => self.merge_regions(from, into),
+                RegionTransformation::Split { from, into } => self.split_region(from, into),
+            }
+        }
+    }
+
+    fn merge_regions(&mut self, from: ID, into: ID) {
+        self.fix_capital(into);
+        let src = self.region_info.remove(&from).unwrap();
+        let dst = self.region_info.get_mut(&into).unwrap();
+        dst.change_balance(src.money_balance);
         dst.maintenance_cost += src.maintenance_cost;
         dst.income_from_fields += src.income_from_fields;
     }
@@ -494,7 +1273,7 @@ impl GameEngine {
         for region_id in into.into_iter() {
             self.fix_capital(region_id);
             let region = &self.location.regions()[&region_id];
-            if region.coordinates().len() < MIN_CONTROLLED_REGION_SIZE {
+            if !has_controlled_size(&self.location, region) {
                 insert.push((region_id, RegionInfo::new(0)));
             } else {
                 new_money_owners.push(region_id);
@@ -537,11 +1316,8 @@ impl GameEngine {
     }
 
     fn fix_capital(&mut self, region_id: ID) {
-        let capitals: Vec<Coord> = self
-            .location
-            .regions()
-            .get(&region_id)
-            .unwrap()
+        let region = &self.location.regions()[&region_id];
+        let mut capitals: Vec<Coord> = region
             .coordinates()
             .iter()
             .map(|c| (c, self.location.tile_at(*c).unwrap()))
@@ -549,73 +1325,63 @@ impl GameEngine {
             .filter(|(_, tile)| tile.unit().unwrap().unit_type() == UnitType::Village)
             .map(|(c, _)| *c)
             .collect();
-        let size = self
-            .location
-            .regions()
-            .get(&region_id)
-            .unwrap()
-            .coordinates()
-            .len();
-        if size == 1 {
-            if capitals.is_empty() {
-                return;
+        capitals.sort();
+        if !needs_capital(&self.location, region) {
+            for &c in capitals.iter() {
+                self.maybe_remove_unit(c).unwrap();
             }
-            let c = *self
-                .location
-                .regions()
-                .get(&region_id)
-                .unwrap()
-                .coordinates()
-                .iter()
-                .next()
-                .unwrap();
-            self.maybe_remove_unit(c).unwrap();
         } else if capitals.is_empty() {
-            // TODO: now capital to create is somehow random. We need to make selection predictable one day
-            let coord = self
-                .location
-                .regions()
-                .get(&region_id)
-                .unwrap()
+            // Capital is placed on the first free land tile, or on the first land tile if there
+            // are no free ones, so the same game always goes the same way
+            let mut coordinates: Vec<Coord> = region
                 .coordinates()
                 .iter()
-                .map(|c| (c, self.location.tile_at(*c).unwrap()))
-                .find(|(_, tile)| tile.unit().is_none())
-                .map_or_else(
-                    || {
-                        *self
-                            .location
-                            .regions()
-                            .get(&region_id)
-                            .unwrap()
-                            .coordinates()
-                            .iter()
-                            .next()
-                            .unwrap()
-                    },
-                    |(c, _)| *c,
-                );
+                .filter(|&&c| self.location.tile_at(c).unwrap().surface().is_land())
+                .cloned()
+                .collect();
+            coordinates.sort();
+            let coord = coordinates
+                .iter()
+                .find(|&&c| self.location.tile_at(c).unwrap().unit().is_none())
+                .map_or(coordinates[0], |&c| c);
 
             self.maybe_remove_unit(coord);
             self.create_and_place_unit(UnitType::Village, coord)
                 .unwrap();
         } else if capitals.len() > 1 {
-            // TODO: now capital to keep is somehow random. We need to make selection predictable one day
-            // The best way is to keep a capital of biggest and richest region.
+            // TODO: The best way is to keep a capital of biggest and richest region, for now the
+            // first one is kept
             for &c in capitals.iter().skip(1) {
                 self.maybe_remove_unit(c).unwrap();
             }
         }
     }
 
+    /// Return true if land unit can board a boat on provided coordinate, which has no other unit
+    /// aboard
+    fn can_board(&self, unit_type: UnitType, coordinate: Coord) -> bool {
+        match self.location.tile_at(coordinate).and_then(|t| t.unit()) {
+            Some(boat) => {
+                !is_naval(unit_type)
+                    && boat.unit_type() == UnitType::Boat
+                    && !self.cargo.contains_key(&boat.id())
+            }
+            None => false,
+        }
+    }
+
     /// Return true if unit can step on tile with specified coordinate
     ///
-    /// Unit can step on tile if tile's surface is land and one of the following is true:
+    /// Unit can step on tile if `can_step_on` allows it and one of the following is true:
     ///
-    /// - tile is a part of region unit belongs to and there is no unit on tile
-    /// - tile is adjacent to the region unit belongs to and tile defence is lower than unit attack
-    ///   (tile defence is the defence of unit on this tile or max defence of neighbour tile that
-    ///   belongs to the same region)
+    /// - tile is water that does not belong to any region
+    /// - tile is a part of region unit belongs to and there is no unit on tile, or there is an
+    ///   empty boat that unit can board
+    /// - tile is land adjacent to the region unit belongs to, does not belong to an ally or a
+    ///   player with a truce and tile defence is lower than unit attack (tile defence is the
+    ///   defence of unit on this tile or max defence of neighbour tile that belongs to the same
+    ///   region, plus defence of tile's surface; veterans defend better, see
+    ///   `UnitInfo::defence`, but no tile defends better than `MAX_TILE_DEFENCE`)
     ///
     fn unit_can_step_on_coord(
         &self,
@@ -629,18 +1395,27 @@ impl GameEngine {
             return false;
         }
         let tile = tile.unwrap();
-        let dst_region = self.region_at(coordinate).unwrap();
+        let dst_region = match self.location.region_at(coordinate) {
+            Some(region) => region,
+            None => return true,
+        };
 
         if dst_region.id() == original_region_id {
             return !is_last_step
                 || tile.unit().is_none()
+                || self.can_board(unit_type, coordinate)
                 || merge_result(unit_type, tile.unit().unwrap().unit_type()).is_some();
         }
-        if !is_last_step {
+        // Bridges and boats cannot be captured
+        if !is_last_step || tile.surface().is_water() {
             return false;
         }
-        let neighbours = coordinate.neighbors();
         let original_region = &self.location.regions()[&original_region_id];
+        let (owner_id, dst_owner_id) = (original_region.owner().id(), dst_region.owner().id());
+        if self.are_allies(owner_id, dst_owner_id) || self.have_truce(owner_id, dst_owner_id) {
+            return false;
+        }
+        let neighbours = coordinate.neighbors();
         let neighbour_from_original_region = neighbours
             .iter()
             .find(|c| original_region.coordinates().contains(c));
@@ -648,22 +1423,48 @@ impl GameEngine {
         if neighbour_from_original_region.is_none() {
             return false;
         }
-        let unit_defence = tile
-            .unit()
-            .map_or(EMPTY_TILE_DEFENCE, |u| description(u.unit_type()).defence);
-        let max_defence = neighbours
+        let (defence, _) = self.tile_defence(coordinate).unwrap();
+        defence < description(unit_type).attack
+    }
+
+    /// Return the defence of a tile that belongs to a region and the coordinate of the unit that
+    /// gives it: the unit on the tile or the strongest unit of the same region next to it, if any
+    /// of them defends. The defence of tile's surface is included, and the total is capped at
+    /// `MAX_TILE_DEFENCE`, see `unit_can_step_on_coord`.
+    pub fn tile_defence(&self, coordinate: Coord) -> Option<(u8, Option<Coord>)> {
+        let tile = self.location.tile_at(coordinate)?;
+        let region = self.location.region_at(coordinate)?;
+        let mut defenders: Vec<Coord> = coordinate
+            .neighbors()
             .iter()
-            .filter(|&n| {
-                self.location
-                    .region_at(*n)
-                    .map_or(false, |r| r.id() == dst_region.id())
-            }).filter_map(|&n| self.location.tile_at(n))
-            .filter_map(|t| t.unit())
-            .map(|u| description(u.unit_type()).defence)
-            .max()
-            .unwrap_or(EMPTY_TILE_DEFENCE);
+            .filter(|&&n| self.location.region_at(n).map(|r| r.id()) == Some(region.id()))
+            .cloned()
+            .collect();
+        defenders.sort();
+        defenders.insert(0, coordinate);
+
+        let mut best: (u8, Option<Coord>) = (EMPTY_TILE_DEFENCE, None);
+        for c in defenders {
+            if let Some(unit) = self.location.tile_at(c).and_then(|t| t.unit()) {
+                let defence = self.unit_info(unit.id()).defence();
+                if defence > best.0 {
+                    best = (defence, Some(c));
+                }
+            }
+        }
+        let defence = best.0 + tile.surface().defence();
+        Some((min(defence, MAX_TILE_DEFENCE), best.1))
+    }
 
-        max(max_defence, unit_defence) < description(unit_type).attack
+    /// Return the defence of the unit on a tile against shots, or `None` if there is no unit that
+    /// can be shot. Only units in the field can be shot, not buildings, trees or units on water,
+    /// and neighbours do not protect them.
+    pub fn shot_defence(&self, coordinate: Coord) -> Option<u8> {
+        let tile = self.location.tile_at(coordinate)?;
+        let unit = tile
+            .unit()
+            .filter(|u| description(u.unit_type()).max_moves > 0 && !is_naval(u.unit_type()))?;
+        Some(self.unit_info(unit.id()).defence() + tile.surface().defence())
     }
 
     fn prepare_moving_unit(
@@ -671,7 +1472,11 @@ impl GameEngine {
         player_id: ID,
         src: Coord,
         dst: Coord,
-    ) -> Result<(ID, u32, ID, bool, Option<ID>, Option<UnitType>), PlayerActionError> {
+    ) -> Result<MovingUnitPreparation, PlayerActionError> {
+        // Otherwise the unit would be merged with itself
+        if src == dst {
+            return Err(PlayerActionError::AlreadyOccupied(dst));
+        }
         let unit = self
             .location
             .tile_at(src)
@@ -681,6 +1486,15 @@ impl GameEngine {
         if region.owner().id() != player_id {
             return Err(PlayerActionError::NotOwned(src));
         }
+        // Unit aboard a boat moves to tiles that the boat itself cannot sail to
+        let unit = match (unit, self.location.tile_at(dst)) {
+            (Some(boat), Some(tile))
+                if self.cargo.contains_key(&boat.id()) && !can_step_on(boat.unit_type(), tile) =>
+            {
+                self.cargo.get(&boat.id())
+            }
+            (unit, _) => unit,
+        };
         if unit.is_none() {
             return Err(PlayerActionError::NoUnit(dst));
         }
@@ -689,22 +1503,37 @@ impl GameEngine {
         let (need_relocation, old_unit_id_to_remove, upgrade_to) =
             self.prepare_placing_unit(player_id, region.id(), unit.unit_type(), dst)?;
 
-        let distance = self.location.bfs_distance(src, dst, |c| {
-            self.unit_can_step_on_coord(unit.unit_type(), c, region.id(), c == dst)
-        });
+        let can_step_on = |c| {
+            c == src || self.unit_can_step_on_coord(unit.unit_type(), c, region.id(), c == dst)
+        };
+        let (distance, path) = if self.location.has_uniform_move_costs() {
+            self.location
+                .bfs_path(src, dst, can_step_on)
+                .map(|path| (path.len() as u32 - 1, path))
+        } else {
+            self.location.shortest_path(src, dst, |c| {
+                if can_step_on(c) {
+                    Some(self.location.tile_at(c).unwrap().surface().move_cost())
+                } else {
+                    None
+                }
+            })
+        }.ok_or(PlayerActionError::InaccessibleLocation(dst))?;
         let unit_info = self.unit_info(unit.id());
-        if distance.is_none() {
-            return Err(PlayerActionError::InaccessibleLocation(dst));
-        } else if unit_info.moves_left() < distance.unwrap() {
+        if unit_info.moves_left() < distance {
             return Err(PlayerActionError::NotEnoughMoves(
                 unit_info.moves_left(),
-                distance.unwrap(),
+                distance,
             ));
         }
-        let moves_to_subtract = if need_relocation || old_unit_id_to_remove.is_some() {
+        // Naval units take water without fighting, so it does not end their move
+        let captures = need_relocation && !is_naval(unit.unit_type());
+        let removes_unit =
+            old_unit_id_to_remove.is_some() && !self.clears_tree(unit.unit_type(), dst);
+        let moves_to_subtract = if captures || removes_unit {
             unit_info.moves_left()
         } else {
-            distance.unwrap()
+            distance
         };
 
         Ok((
@@ -714,6 +1543,7 @@ impl GameEngine {
             need_relocation,
             old_unit_id_to_remove,
             upgrade_to,
+            path,
         ))
     }
 
@@ -723,12 +1553,31 @@ impl GameEngine {
         src: Coord,
         dst: Coord,
     ) -> Result<(), PlayerActionError> {
-        let (unit_id, moves_num, region_id, need_relocation, old_unit_id_to_remove, upgrade_to) =
-            self.prepare_moving_unit(player_id, src, dst)?;
-
-        self.location.move_unit(src, dst)?;
-        if need_relocation {
-            self.add_tile_to_region(dst, region_id)?;
+        let (
+            unit_id,
+            moves_num,
+            region_id,
+            need_relocation,
+            old_unit_id_to_remove,
+            upgrade_to,
+            path,
+        ) = self.prepare_moving_unit(player_id, src, dst)?;
+        let unit_type = self.unit_info(unit_id).description().name;
+
+        if is_naval(unit_type) {
+            // Naval unit holds only the water it stands on, so it takes each tile of the path and
+            // leaves the previous one
+            for step in path.windows(2) {
+                let step_region_id = self.region_at(step[0])?.id();
+                self.location.move_unit(step[0], step[1])?;
+                self.add_tile_to_region(step[1], step_region_id)?;
+                self.remove_tile_from_region(step[0])?;
+            }
+        } else {
+            self.relocate_land_unit(unit_id, src, dst)?;
+            if need_relocation {
+                self.add_tile_to_region(dst, region_id)?;
+            }
         }
         self.unit_info
             .get_mut(&unit_id)
@@ -739,15 +1588,122 @@ impl GameEngine {
         } else {
             None
         };
+        if need_relocation && old_unit_info.map(|i| i.description().max_moves > 0) == Some(true) {
+            self.unit_info.get_mut(&unit_id).unwrap().add_kill();
+        }
         if let Some(unit_type) = upgrade_to {
-            self.maybe_remove_unit(dst).unwrap();
+            let (_, info) = self.maybe_remove_unit(dst).unwrap();
             let unit_id = self.create_and_place_unit(unit_type, dst).unwrap();
             let old_info = old_unit_info.unwrap();
+            let mut merged_info = merge_result_info(&info, &old_info).unwrap();
             if old_info.moves_left() == old_info.description().max_moves {
-                self.unit_info.get_mut(&unit_id).unwrap().refill_moves();
+                merged_info.refill_moves();
             }
+            self.unit_info.insert(unit_id, merged_info);
+        }
+        self.events.push(GameEvent::UnitMoved {
+            player_id,
+            unit_type,
+            path,
+        });
+
+        Ok(())
+    }
+
+    /// Return true if unit removes a tree on provided coordinate without ending its move
+    fn clears_tree(&self, unit_type: UnitType, coordinate: Coord) -> bool {
+        let tile_unit = self.location.tile_at(coordinate).and_then(|t| t.unit());
+        description(unit_type).clears_trees
+            && tile_unit.map(|u| is_tree(u.unit_type())) == Some(true)
+    }
+
+    /// Move land unit from its tile or from the boat it is aboard to the destination tile or
+    /// aboard the boat on destination tile
+    fn relocate_land_unit(
+        &mut self,
+        unit_id: ID,
+        src: Coord,
+        dst: Coord,
+    ) -> Result<(), PlayerActionError> {
+        let boat_id = self.location.tile_at(src).unwrap().unit().unwrap().id();
+        let unit = match self.cargo.get(&boat_id) {
+            Some(carried) if carried.id() == unit_id => self.cargo.remove(&boat_id).unwrap(),
+            _ => self.location.remove_unit(src)?.unwrap(),
+        };
+        if self.can_board(unit.unit_type(), dst) {
+            let boat_id = self.location.tile_at(dst).unwrap().unit().unwrap().id();
+            self.cargo.insert(boat_id, unit);
+        } else {
+            self.location.place_unit(unit, dst)?;
+        }
+
+        Ok(())
+    }
+
+    /// Check that unit on `src` can shoot the unit on `dst`, return id of the shooter and the unit
+    /// to be shot
+    fn prepare_shooting(
+        &self,
+        player_id: ID,
+        src: Coord,
+        dst: Coord,
+    ) -> Result<(ID, Unit), PlayerActionError> {
+        let region = self.region_at(src)?;
+        if region.owner().id() != player_id {
+            return Err(PlayerActionError::NotOwned(src));
+        }
+        let unit = *self
+            .location
+            .tile_at(src)
+            .unwrap()
+            .unit()
+            .ok_or(PlayerActionError::NoUnit(src))?;
+        let unit_description = description(unit.unit_type());
+        if unit_description.attack_range == 0 {
+            return Err(PlayerActionError::CannotShoot(unit.unit_type()));
+        }
+        let moves_left = self.unit_info(unit.id()).moves_left();
+        if moves_left == 0 {
+            return Err(PlayerActionError::NotEnoughMoves(moves_left, 1));
+        }
+        let tile = self
+            .location
+            .tile_at(dst)
+            .ok_or(PlayerActionError::InaccessibleLocation(dst))?;
+        if src.distance(dst) as u32 > unit_description.attack_range {
+            return Err(PlayerActionError::InaccessibleLocation(dst));
+        }
+
+        let defence = self
+            .shot_defence(dst)
+            .ok_or(PlayerActionError::NoUnit(dst))?;
+        let target = tile.unit().unwrap();
+        let target_owner_id = self.location.region_at(dst).unwrap().owner().id();
+        if self.are_allies(player_id, target_owner_id)
+            || self.have_truce(player_id, target_owner_id)
+            || defence >= unit_description.attack
+        {
+            return Err(PlayerActionError::CannotAttack(dst));
         }
 
+        Ok((unit.id(), *target))
+    }
+
+    fn shoot(&mut self, player_id: ID, src: Coord, dst: Coord) -> Result<(), PlayerActionError> {
+        let (unit_id, target) = self.prepare_shooting(player_id, src, dst)?;
+
+        self.maybe_remove_unit(dst).unwrap();
+        self.create_and_place_unit(UnitType::Grave, dst)?;
+        let info = self.unit_info.get_mut(&unit_id).unwrap();
+        let moves_left = info.moves_left();
+        info.subtract_moves(moves_left);
+        info.add_kill();
+        self.events.push(GameEvent::UnitShot {
+            player_id,
+            unit_type: target.unit_type(),
+            coordinate: dst,
+        });
+
         Ok(())
     }
 
@@ -793,21 +1749,163 @@ impl GameEngine {
     }
 
     fn check_for_winner(&mut self) {
-        // Winner is the last player standing
+        // Winner is the last player or alliance standing
         let active_players: Vec<ID> = self
             .players
             .iter()
             .filter(|p| self.player_activity[&p.id()])
             .map(|p| p.id())
             .collect();
-        if active_players.len() == 1 {
+        if !active_players.is_empty()
+            && active_players
+                .iter()
+                .all(|&id| self.are_allies(active_players[0], id))
+        {
             self.winner = Some(active_players[0]);
+            for id in self.winners() {
+                self.events.push(GameEvent::GameWon(id));
+            }
             return;
         }
 
         // TODO: add win condition: player, owning more than 65% of territory
     }
 
+    /// Give all regions of the player to the neutral owner and pass the turn to the next player
+    fn resign(&mut self, player_id: ID) {
+        // Player who has already defeated all opponents during this turn wins instead
+        self.check_for_winner();
+        if self.winner.is_some() {
+            return;
+        }
+        self.make_regions_neutral(player_id);
+        self.drop_truces_of(player_id);
+        self.player_activity.insert(player_id, false);
+        self.defeated.push(vec![player_id]);
+        self.events.push(GameEvent::PlayerResigned(player_id));
+        self.check_for_winner();
+        if self.winner.is_none() {
+            self.end_players_turn();
+        }
+    }
+
+    /// Check that players can make a truce: other player is still in the game and is not an ally
+    fn check_negotiation(&self, player_id: ID, other_id: ID) -> Result<(), PlayerActionError> {
+        let is_active = self.player_activity.get(&other_id) == Some(&true);
+        if !is_active || self.are_allies(player_id, other_id) {
+            return Err(PlayerActionError::CannotNegotiateWith(other_id));
+        } else if self.have_truce(player_id, other_id) {
+            return Err(PlayerActionError::AlreadyInTruce(other_id));
+        }
+
+        Ok(())
+    }
+
+    fn check_truce_proposal(
+        &self,
+        player_id: ID,
+        other_id: ID,
+        turns: u32,
+    ) -> Result<(), PlayerActionError> {
+        self.check_negotiation(player_id, other_id)?;
+        if turns == 0 {
+            return Err(PlayerActionError::InvalidTruceDuration(turns));
+        }
+
+        Ok(())
+    }
+
+    fn propose_truce(
+        &mut self,
+        player_id: ID,
+        other_id: ID,
+        turns: u32,
+    ) -> Result<(), PlayerActionError> {
+        self.check_truce_proposal(player_id, other_id, turns)?;
+
+        // New proposal replaces the previous one
+        self.truce_proposals
+            .retain(|p| p.from != player_id || p.to != other_id);
+        self.truce_proposals.push(TruceProposal {
+            from: player_id,
+            to: other_id,
+            turns,
+        });
+        self.events.push(GameEvent::TruceProposed {
+            from: player_id,
+            to: other_id,
+            turns,
+        });
+
+        Ok(())
+    }
+
+    /// Return the truce that other player has proposed to the player, if they can make it
+    fn find_truce_proposal(
+        &self,
+        player_id: ID,
+        other_id: ID,
+    ) -> Result<TruceProposal, PlayerActionError> {
+        let proposal = *self
+            .truce_proposals
+            .iter()
+            .find(|p| p.from == other_id && p.to == player_id)
+            .ok_or(PlayerActionError::NoTruceProposal(other_id))?;
+        self.check_negotiation(player_id, other_id)?;
+
+        Ok(proposal)
+    }
+
+    fn accept_truce(&mut self, player_id: ID, other_id: ID) -> Result<(), PlayerActionError> {
+        let proposal = self.find_truce_proposal(player_id, other_id)?;
+
+        let truce = Truce::new(player_id, other_id, self.current_turn + proposal.turns);
+        self.truce_proposals
+            .retain(|p| !truce.binds(p.from, p.to));
+        self.truces.push(truce);
+        self.events.push(GameEvent::TruceAccepted {
+            from: other_id,
+            to: player_id,
+            ends_on_turn: truce.ends_on_turn,
+        });
+
+        Ok(())
+    }
+
+    fn check_truce(&self, player_id: ID, other_id: ID) -> Result<(), PlayerActionError> {
+        if !self.have_truce(player_id, other_id) {
+            return Err(PlayerActionError::NoTruce(other_id));
+        }
+
+        Ok(())
+    }
+
+    fn break_truce(&mut self, player_id: ID, other_id: ID) -> Result<(), PlayerActionError> {
+        self.check_truce(player_id, other_id)?;
+
+        self.truces.retain(|t| !t.binds(player_id, other_id));
+        self.events.push(GameEvent::TruceBroken {
+            by: player_id,
+            with: other_id,
+        });
+
+        Ok(())
+    }
+
+    /// Remove truces that end with the start of the current turn
+    fn expire_truces(&mut self) {
+        let current_turn = self.current_turn;
+        let (expired, truces): (Vec<Truce>, Vec<Truce>) = self
+            .truces
+            .iter()
+            .partition(|t| t.ends_on_turn <= current_turn);
+        self.truces = truces;
+        for truce in expired {
+            self.events
+                .push(GameEvent::TruceExpired(truce.players.0, truce.players.1));
+        }
+    }
+
     fn validate_action(
         &self,
         player_id: u32,
@@ -825,6 +1923,10 @@ impl GameEngine {
     }
 
     fn end_players_turn(&mut self) {
+        let player_id = self.active_player().id();
+        if let Some(clock) = self.clock.as_mut() {
+            clock.finish_turn(player_id);
+        }
         self.active_player_num += 1;
         self.rewind_to_active_player();
         if self.active_player_num as usize >= self.players.len() {
@@ -850,6 +1952,7 @@ impl GameEngine {
                 existing_graves.push(coord);
             }
         }
+        existing_graves.sort();
         for coordinate in existing_graves.into_iter() {
             self.maybe_remove_unit(coordinate).unwrap();
             self.create_and_place_unit(UnitType::PineTree, coordinate)
@@ -859,9 +1962,15 @@ impl GameEngine {
 
     fn apply_income(&mut self) {
         for (id, region) in self.location.regions() {
-            if region.coordinates().len() < MIN_CONTROLLED_REGION_SIZE {
-                let c = *region.coordinates().iter().next().unwrap();
-                if self.location().tile_at(c).unwrap().unit().is_none() {
+            if region.owner().is_neutral() {
+                continue;
+            }
+            if !has_controlled_size(&self.location, region) {
+                let has_units = region
+                    .coordinates()
+                    .iter()
+                    .any(|&c| self.location.tile_at(c).unwrap().unit().is_some());
+                if !has_units {
                     continue;
                 }
             }
@@ -877,6 +1986,13 @@ impl GameEngine {
         }
     }
 
+    /// Count one more turn for every unit that has survived it
+    fn gain_experience(&mut self) {
+        for info in self.unit_info.values_mut() {
+            info.survive_turn();
+        }
+    }
+
     fn kill_starving_units(&mut self) {
         let regions_to_check: Vec<ID> = self
             .region_info
@@ -884,21 +2000,52 @@ impl GameEngine {
             .filter(|(_, r)| r.money_balance < 0)
             .map(|(id, _)| *id)
             .collect();
-        let kill_coordinates: Vec<Coord> = regions_to_check
+        let mut kill_coordinates: Vec<(Coord, ID, UnitType)> = regions_to_check
             .iter()
             .filter_map(|id| self.location.regions().get(id))
-            .flat_map(Region::coordinates)
-            .filter_map(|&c| self.location.tile_at(c).unwrap().unit().map(|u| (c, u)))
-            .filter(|(_, u)| {
-                let d = description(u.unit_type());
+            .flat_map(|r| r.coordinates().iter().map(move |&c| (c, r.owner().id())))
+            .filter_map(|(c, owner_id)| {
+                self.location
+                    .tile_at(c)
+                    .unwrap()
+                    .unit()
+                    .map(|u| (c, owner_id, u.unit_type()))
+            }).filter(|(_, _, unit_type)| {
+                let d = description(*unit_type);
                 // We don't kill units that are not owned by player and the ones that have no turn cost
                 !d.is_unownable && d.turn_cost > 0
-            }).map(|(c, _)| c)
-            .collect();
-        for coordinate in kill_coordinates.into_iter() {
+            }).collect();
+        let mut starving_cargo: Vec<(Coord, ID, ID)> = regions_to_check
+            .iter()
+            .filter_map(|id| self.location.regions().get(id))
+            .flat_map(|r| r.coordinates().iter().map(move |&c| (c, r.owner().id())))
+            .filter_map(|(c, owner_id)| {
+                let boat = self.location.tile_at(c).unwrap().unit()?;
+                self.cargo.get(&boat.id()).map(|_| (c, owner_id, boat.id()))
+            }).collect();
+        kill_coordinates.sort();
+        for (coordinate, player_id, unit_type) in kill_coordinates.into_iter() {
             self.maybe_remove_unit(coordinate).unwrap();
             self.create_and_place_unit(UnitType::Grave, coordinate)
                 .unwrap();
+            self.stats.record_starvation(player_id);
+            self.events.push(GameEvent::UnitStarved {
+                player_id,
+                unit_type,
+                coordinate,
+            });
+        }
+        // Units aboard boats have no place for graves, they just disappear
+        starving_cargo.sort();
+        for (coordinate, player_id, boat_id) in starving_cargo.into_iter() {
+            let unit = self.cargo.remove(&boat_id).unwrap();
+            self.unit_info.remove(&unit.id());
+            self.stats.record_starvation(player_id);
+            self.events.push(GameEvent::UnitStarved {
+                player_id,
+                unit_type: unit.unit_type(),
+                coordinate,
+            });
         }
     }
 
@@ -926,7 +2073,8 @@ impl GameEngine {
         }
     }
 
-    fn add_tree(&mut self, coordinates: Vec<Coord>, unit_type: UnitType) {
+    fn add_tree(&mut self, mut coordinates: Vec<Coord>, unit_type: UnitType) {
+        coordinates.sort();
         for c in coordinates {
             self.create_and_place_unit(unit_type, c).unwrap();
         }
@@ -955,6 +2103,52 @@ impl GameEngine {
         self.add_tree(coordinates_for_pines, UnitType::PineTree);
     }
 
+    /// Add statistics of all players at the end of the current turn to the timeline
+    fn record_stats(&mut self) {
+        let mut players: Vec<PlayerStats> = self
+            .players
+            .iter()
+            .map(|p| PlayerStats {
+                player_id: p.id(),
+                ..PlayerStats::default()
+            }).collect();
+        let mut region_ids: Vec<ID> = self.location.regions().keys().cloned().collect();
+        region_ids.sort();
+        for region_id in region_ids {
+            let region = &self.location.regions()[&region_id];
+            let player = match players.iter_mut().find(|p| p.player_id == region.owner().id()) {
+                Some(player) => player,
+                None => continue,
+            };
+            let info = self.region_info[&region_id];
+            player.tiles += region.coordinates().len();
+            player.income += info.income_from_fields;
+            player.maintenance += info.maintenance_cost;
+            player.regions.push(RegionStats {
+                region_id,
+                money: info.money_balance,
+                income: info.income_from_fields,
+                maintenance: info.maintenance_cost,
+            });
+            for &c in region.coordinates().iter() {
+                let unit = match self.location.tile_at(c).unwrap().unit() {
+                    Some(unit) => unit,
+                    None => continue,
+                };
+                let carried = self.cargo.get(&unit.id());
+                for u in Some(unit).into_iter().chain(carried) {
+                    if !description(u.unit_type()).is_unownable {
+                        *player.units.entry(u.unit_type()).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        self.stats.record_turn(TurnStats {
+            turn: self.current_turn,
+            players,
+        });
+    }
+
     fn end_turn(&mut self) {
         // Set of end-of-turn actions. Order is important.
         self.apply_income();
@@ -962,11 +2156,14 @@ impl GameEngine {
         self.spread_forests();
         self.replace_graves_with_pine_trees();
         self.kill_starving_units();
+        self.gain_experience();
         self.check_for_active_players();
         self.check_for_winner();
+        self.record_stats();
 
         // Now we can change turn number and find next active player to move
         self.current_turn += 1;
+        self.expire_truces();
         self.active_player_num = 0;
         self.rewind_to_active_player();
     }
@@ -974,12 +2171,26 @@ impl GameEngine {
 
 #[cfg(test)]
 mod test {
-    use super::{GameEngine, PlayerAction, PlayerActionError};
+    use std::collections::HashSet;
+    use std::error::Error;
+
+    use super::{
+        EngineValidationError, GameEngine, GameEvent, MoveKind, PlayerAction, PlayerActionError,
+        ReachableTile, TruceProposal,
+    };
+    use crate::game::clock::{ManualTimeSource, TimeControl};
     use crate::game::consts::*;
     use crate::game::ids::ID;
-    use crate::game::location::{Coord, Player, UnitType};
+    use crate::game::location::{
+        Controller, Coord, LocationModificationError, LocationValidationError, Player,
+        PlayerProfile, UnitType,
+    };
+    use crate::game::rules::{
+        LocationRulesValidationError, RegionsValidationError, RulesVariant,
+    };
     use crate::game::test_util::create_valid_engine;
     use crate::game::unit::description;
+    use crate::init::ascii::parse;
 
     #[test]
     fn create_engine_correct() {
@@ -1361,6 +2572,21 @@ mod test {
         assert_eq!(info.moves_left(), info.description().max_moves);
     }
 
+    #[test]
+    fn move_unit_to_its_own_tile_error() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+
+        let src = Coord::new(1, 0);
+        let action = PlayerAction::MoveUnit { src, dst: src };
+        let res = game_engine.act(pl[0].id(), action);
+
+        assert_eq!(res, Err(PlayerActionError::AlreadyOccupied(src)));
+        let unit = game_engine.location().tile_at(src).unwrap().unit().unwrap();
+        let info = game_engine.unit_info(unit.id());
+        assert_eq!(unit.unit_type(), UnitType::Soldier);
+        assert_eq!(info.moves_left(), info.description().max_moves);
+    }
+
     #[test]
     fn move_unit_inside_region_error_dst_outside_location() {
         let (pl, _, mut game_engine) = create_valid_engine();
@@ -1507,6 +2733,44 @@ mod test {
         assert_eq!(game_engine.player_activity[&pl[2].id()], false);
     }
 
+    #[test]
+    fn move_unit_outside_region_growing_region_gets_capital() {
+        let (pl, _, mut game_engine) = successful_attack(Coord::new(1, 0), Coord::new(1, 1));
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+
+        // Militia is left alone in a region of one tile, it captures another one
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(0, 1),
+            dst: Coord::new(-1, 1),
+        };
+        assert_eq!(game_engine.act(pl[1].id(), action), Ok(()));
+
+        let region = game_engine.location().region_at(Coord::new(0, 1)).unwrap();
+        assert_eq!(region.coordinates().len(), 2);
+        assert_eq!(
+            game_engine
+                .location()
+                .tile_at(Coord::new(0, 1))
+                .unwrap()
+                .unit()
+                .map(|u| u.unit_type()),
+            Some(UnitType::Village)
+        );
+    }
+
+    #[test]
+    fn move_unit_outside_region_error_allied_region() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        game_engine.set_alliances(vec![vec![pl[0].id(), pl[1].id()]]);
+        let (src, dst) = (Coord::new(1, 0), Coord::new(1, 1));
+
+        let res = game_engine.act(pl[0].id(), PlayerAction::MoveUnit { src, dst });
+
+        assert_eq!(res, Err(PlayerActionError::InaccessibleLocation(dst)));
+        assert!(game_engine.are_allies(pl[1].id(), pl[0].id()));
+        assert!(!game_engine.are_allies(pl[0].id(), pl[2].id()));
+    }
+
     #[test]
     fn move_unit_and_merge_all_ok_goal_not_moved_before() {
         let (pl, _, mut game_engine) = create_valid_engine();
@@ -1712,6 +2976,8 @@ mod test {
         assert_eq!(game_engine.current_turn(), 2);
         assert_eq!(*game_engine.active_player(), pl[0]);
         assert_eq!(game_engine.player_activity[&pl[1].id()], false);
+        let region = game_engine.location.region_at(Coord::new(0, 1)).unwrap();
+        assert!(region.owner().is_neutral());
 
         let grave = game_engine
             .location
@@ -1791,28 +3057,1130 @@ mod test {
     }
 
     #[test]
-    fn end_turn_spawns_trees_on_top_of_graves() {
-        let (pl, _ri, mut game_engine) = create_valid_engine();
-        let coordinate = Coord::new(2, -1);
-        game_engine
-            .create_and_place_unit(UnitType::Grave, coordinate)
-            .unwrap();
+    fn end_turn_reports_starved_units() {
+        let (pl, ri, mut game_engine) = create_valid_engine();
+        game_engine.modify_money(ri[0], -CONTROLLED_REGION_STARTING_MONEY);
         game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
         game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
-        game_engine.act(pl[2].id(), PlayerAction::EndTurn).unwrap();
+        assert_eq!(game_engine.take_events(), vec![]);
 
-        assert_eq!(game_engine.current_turn(), 2);
-        assert_eq!(*game_engine.active_player(), pl[0]);
+        game_engine.act(pl[2].id(), PlayerAction::EndTurn).unwrap();
 
-        let tree = game_engine.location.tile_at(coordinate).unwrap().unit();
-        assert!(tree.is_some());
-        assert_eq!(tree.unwrap().unit_type(), UnitType::PineTree);
+        assert_eq!(
+            game_engine.take_events(),
+            vec![GameEvent::UnitStarved {
+                player_id: pl[0].id(),
+                unit_type: UnitType::Soldier,
+                coordinate: Coord::new(1, 0),
+            }]
+        );
+        assert_eq!(game_engine.take_events(), vec![]);
     }
 
     #[test]
-    fn end_turn_dont_spreads_trees_on_existing_units() {
-        let (pl, _ri, mut game_engine) = create_valid_engine();
-        let coordinate = Coord::new(1, 0);
+    fn end_turn_records_stats_of_players() {
+        let (pl, ri, mut game_engine) = create_valid_engine();
+        game_engine
+            .act(
+                pl[0].id(),
+                PlayerAction::MoveUnit {
+                    src: Coord::new(1, 0),
+                    dst: Coord::new(1, 1),
+                },
+            ).unwrap();
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+        assert!(game_engine.stats().turns().is_empty());
+        game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[2].id(), PlayerAction::EndTurn).unwrap();
+
+        let turns = game_engine.stats().turns();
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0].turn, 1);
+        let players: Vec<ID> = turns[0].players.iter().map(|p| p.player_id).collect();
+        assert_eq!(players, pl.iter().map(|p| p.id()).collect::<Vec<_>>());
+
+        let stats = &turns[0].players[0];
+        assert_eq!(stats.tiles_captured, 1);
+        assert_eq!(stats.starvation_deaths, 0);
+        assert_eq!(stats.units.get(&UnitType::Soldier), Some(&1));
+        let region = &stats.regions[0];
+        assert_eq!(region.region_id, ri[0]);
+        assert_eq!(Some(region.money), game_engine.region_money(ri[0]));
+        let tiles: usize = game_engine
+            .location()
+            .regions()
+            .values()
+            .filter(|r| r.owner().id() == pl[0].id())
+            .map(|r| r.coordinates().len())
+            .sum();
+        assert_eq!(stats.tiles, tiles);
+        assert_eq!(turns[0].players[1].tiles_captured, 0);
+    }
+
+    #[test]
+    fn end_turn_records_starvation_deaths() {
+        let (pl, ri, mut game_engine) = create_valid_engine();
+        game_engine.modify_money(ri[0], -CONTROLLED_REGION_STARTING_MONEY);
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[2].id(), PlayerAction::EndTurn).unwrap();
+
+        let timeline = game_engine.stats().player_timeline(pl[0].id());
+        assert_eq!(timeline.len(), 1);
+        assert_eq!(timeline[0].starvation_deaths, 1);
+        assert_eq!(timeline[0].units.get(&UnitType::Soldier), None);
+    }
+
+    #[test]
+    fn players_get_numbered_profiles_by_default() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        let profile = game_engine.profile(pl[1].id()).unwrap().clone();
+        assert_eq!(profile, PlayerProfile::numbered(1));
+        assert_eq!(profile.name, "Player 2");
+        assert_eq!(game_engine.profile(100), None);
+
+        let bot = PlayerProfile {
+            name: "Greedy".to_owned(),
+            color: "#000000".to_owned(),
+            controller: Controller::Bot {
+                name: "greedy".to_owned(),
+                seed: 7,
+            },
+        };
+        let broken = PlayerProfile {
+            color: "red\"/><script/>".to_owned(),
+            ..bot.clone()
+        };
+        let profiles = vec![(pl[0].id(), bot.clone()), (pl[1].id(), broken), (100, bot.clone())];
+        game_engine.set_profiles(profiles.into_iter().collect());
+        assert_eq!(game_engine.profile(pl[0].id()), Some(&bot));
+        assert_eq!(game_engine.profile(100), None);
+        assert_eq!(game_engine.players()[0].profile(), &bot);
+        assert_eq!(game_engine.players()[1].profile(), &profile);
+
+        // Players of games saved before profiles appeared are only ids
+        let json = format!("{{\"id\":{}}}", pl[0].id());
+        game_engine.players[0] = serde_json::from_str(&json).unwrap();
+        assert_eq!(game_engine.players()[0].player(), pl[0]);
+        game_engine.repair();
+        assert_eq!(game_engine.profile(pl[0].id()), Some(&PlayerProfile::numbered(0)));
+
+        // Saved games may be edited by hand, so colors are checked again after loading
+        game_engine.players[1].profile.color = "red\"/><script/>".to_owned();
+        game_engine.repair();
+        assert_eq!(game_engine.profile(pl[1].id()), Some(&profile));
+    }
+
+    #[test]
+    fn check_time_ends_turn_of_player_out_of_time() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        let time = ManualTimeSource::new(5000);
+        assert_eq!(game_engine.check_time(&time), None);
+        assert_eq!(game_engine.time_left(pl[0].id()), None);
+
+        game_engine.set_time_control(Some(TimeControl::PerTurn { limit: 1000 }));
+        assert_eq!(game_engine.check_time(&time), None);
+        time.advance(999);
+        assert_eq!(game_engine.check_time(&time), None);
+        assert_eq!(game_engine.time_left(pl[0].id()), Some(1));
+        assert_eq!(game_engine.time_left(pl[1].id()), Some(1000));
+
+        time.advance(1);
+        assert_eq!(game_engine.check_time(&time), Some(pl[0].id()));
+        assert_eq!(game_engine.active_player().id(), pl[1].id());
+        assert_eq!(game_engine.time_left(pl[1].id()), Some(1000));
+        assert!(game_engine
+            .take_events()
+            .contains(&GameEvent::TurnTimedOut(pl[0].id())));
+    }
+
+    #[test]
+    fn chess_clock_keeps_time_left_between_turns() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        let time = ManualTimeSource::new(0);
+        game_engine.set_time_control(Some(TimeControl::ChessClock {
+            initial: 1000,
+            increment: 100,
+        }));
+
+        game_engine.check_time(&time);
+        time.advance(300);
+        assert_eq!(game_engine.check_time(&time), None);
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+        assert_eq!(game_engine.time_left(pl[0].id()), Some(800));
+
+        time.advance(1200);
+        assert_eq!(game_engine.check_time(&time), Some(pl[1].id()));
+        assert_eq!(game_engine.time_left(pl[1].id()), Some(100));
+        assert_eq!(game_engine.active_player().id(), pl[2].id());
+    }
+
+    #[test]
+    fn act_with_time_starts_next_turn_at_current_time() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        let time = ManualTimeSource::new(0);
+        game_engine.set_time_control(Some(TimeControl::PerTurn { limit: 100 }));
+
+        game_engine.check_time(&time);
+        time.advance(50);
+        game_engine
+            .act_with_time(pl[0].id(), PlayerAction::EndTurn, &time)
+            .unwrap();
+        assert_eq!(game_engine.time_left(pl[1].id()), Some(100));
+
+        time.advance(60);
+        assert_eq!(game_engine.check_time(&time), None);
+        assert_eq!(game_engine.active_player().id(), pl[1].id());
+        assert_eq!(game_engine.time_left(pl[1].id()), Some(40));
+    }
+
+    #[test]
+    fn resigned_player_leaves_regions_to_neutral_owner() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        let coordinates: HashSet<Coord> = game_engine
+            .location()
+            .regions()
+            .values()
+            .filter(|r| r.owner().id() == pl[2].id())
+            .flat_map(|r| r.coordinates().clone())
+            .collect();
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[2].id(), PlayerAction::Resign).unwrap();
+
+        assert_eq!(game_engine.current_turn(), 2);
+        assert_eq!(game_engine.active_player().id(), pl[0].id());
+        assert!(!game_engine.is_player_active(pl[2].id()));
+        assert_eq!(game_engine.winner(), None);
+        assert_eq!(game_engine.defeated_players(), &vec![vec![pl[2].id()]]);
+        for &c in coordinates.iter() {
+            let region = game_engine.location().region_at(c).unwrap();
+            assert!(region.owner().is_neutral());
+        }
+        assert!(game_engine
+            .take_events()
+            .contains(&GameEvent::PlayerResigned(pl[2].id())));
+    }
+
+    #[test]
+    fn resigned_player_leaves_truces() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        let propose = |player_id| PlayerAction::ProposeTruce { player_id, turns: 3 };
+        game_engine.act(pl[0].id(), propose(pl[2].id())).unwrap();
+        game_engine.act(pl[0].id(), propose(pl[1].id())).unwrap();
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[1].id(), propose(pl[2].id())).unwrap();
+        game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
+        game_engine
+            .act(pl[2].id(), PlayerAction::AcceptTruce(pl[1].id()))
+            .unwrap();
+        assert_eq!(game_engine.truces().len(), 1);
+        assert_eq!(game_engine.truce_proposals().len(), 2);
+
+        game_engine.act(pl[2].id(), PlayerAction::Resign).unwrap();
+        assert!(game_engine.truces().is_empty());
+        assert_eq!(
+            game_engine.truce_proposals(),
+            &vec![TruceProposal {
+                from: pl[0].id(),
+                to: pl[1].id(),
+                turns: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn defeated_player_leaves_truces() {
+        let text = "players 3\norigin 0 0\n1V  1S  2.  2V  3.  3V\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let pl: Vec<ID> = game_engine.players().iter().map(|p| p.id()).collect();
+        game_engine.act(pl[0], PlayerAction::EndTurn).unwrap();
+        let propose = |player_id| PlayerAction::ProposeTruce { player_id, turns: 3 };
+        game_engine.act(pl[1], propose(pl[0])).unwrap();
+        game_engine.act(pl[1], propose(pl[2])).unwrap();
+        game_engine.act(pl[1], PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[2], PlayerAction::AcceptTruce(pl[1])).unwrap();
+        game_engine.act(pl[2], PlayerAction::EndTurn).unwrap();
+        assert_eq!(game_engine.truces().len(), 1);
+        assert_eq!(game_engine.truce_proposals().len(), 1);
+
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(2, 0),
+        };
+        game_engine.act(pl[0], action).unwrap();
+        assert!(!game_engine.is_player_active(pl[1]));
+        assert!(game_engine.truces().is_empty());
+        assert!(game_engine.truce_proposals().is_empty());
+    }
+
+    #[test]
+    fn neutral_regions_can_be_captured() {
+        let text = "players 3\norigin 0 0\n1V  1S  2.  2V  3.  3V\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let pl: Vec<ID> = game_engine.players().iter().map(|p| p.id()).collect();
+        game_engine.act(pl[0], PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[1], PlayerAction::Resign).unwrap();
+        assert_eq!(game_engine.active_player().id(), pl[2]);
+        game_engine.act(pl[2], PlayerAction::EndTurn).unwrap();
+
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(2, 0),
+        };
+        game_engine.act(pl[0], action).unwrap();
+        let region = game_engine.location().region_at(Coord::new(2, 0)).unwrap();
+        assert_eq!(region.owner().id(), pl[0]);
+        let region = game_engine.location().region_at(Coord::new(3, 0)).unwrap();
+        assert!(region.owner().is_neutral());
+    }
+
+    #[test]
+    fn units_of_resigned_player_leave_graves() {
+        let text = "players 3\norigin 0 0\n1V  1S  2K  2V  3.  3V\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let pl: Vec<ID> = game_engine.players().iter().map(|p| p.id()).collect();
+        let (src, dst) = (Coord::new(1, 0), Coord::new(2, 0));
+        assert_eq!(
+            game_engine.act(pl[0], PlayerAction::MoveUnit { src, dst }),
+            Err(PlayerActionError::InaccessibleLocation(dst))
+        );
+        game_engine.act(pl[0], PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[1], PlayerAction::Resign).unwrap();
+        let unit = game_engine.location().tile_at(dst).unwrap().unit();
+        assert_eq!(unit.map(|u| u.unit_type()), Some(UnitType::Grave));
+        game_engine.act(pl[2], PlayerAction::EndTurn).unwrap();
+
+        game_engine
+            .act(pl[0], PlayerAction::MoveUnit { src, dst })
+            .unwrap();
+        let region = game_engine.location().region_at(dst).unwrap();
+        assert_eq!(region.owner().id(), pl[0]);
+    }
+
+    #[test]
+    fn neutral_regions_have_no_capitals_and_no_income() {
+        let text = "players 2\norigin 0 0\n1V  1S  0.  0.  0.  2.  2V\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let pl: Vec<ID> = game_engine.players().iter().map(|p| p.id()).collect();
+        let region_id = game_engine
+            .location()
+            .region_at(Coord::new(3, 0))
+            .unwrap()
+            .id();
+        let has_village = (2..5).any(|x| {
+            let tile = game_engine.location().tile_at(Coord::new(x, 0)).unwrap();
+            tile.unit().map(|u| u.unit_type()) == Some(UnitType::Village)
+        });
+        assert!(!has_village);
+
+        game_engine.act(pl[0], PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[1], PlayerAction::EndTurn).unwrap();
+        assert_eq!(game_engine.region_money(region_id), Some(0));
+
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(2, 0),
+        };
+        game_engine.act(pl[0], action).unwrap();
+        let region = game_engine.location().region_at(Coord::new(2, 0)).unwrap();
+        assert_eq!(region.owner().id(), pl[0]);
+    }
+
+    #[test]
+    fn resign_of_last_opponent_selects_winner() {
+        let text = "players 2\norigin 0 0\n1V  1S  2.  2V\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let pl: Vec<ID> = game_engine.players().iter().map(|p| p.id()).collect();
+        game_engine.act(pl[0], PlayerAction::Resign).unwrap();
+
+        assert_eq!(game_engine.winner(), Some(pl[1]));
+        assert_eq!(
+            game_engine.act(pl[0], PlayerAction::EndTurn),
+            Err(PlayerActionError::GameAlreadyFinished)
+        );
+    }
+
+    #[test]
+    fn resign_after_defeating_last_opponent_wins() {
+        let text = "players 2\norigin 0 0\n1V  1S  2.  2V\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let pl: Vec<ID> = game_engine.players().iter().map(|p| p.id()).collect();
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(2, 0),
+        };
+        game_engine.act(pl[0], action).unwrap();
+        assert!(!game_engine.is_player_active(pl[1]));
+        game_engine.act(pl[0], PlayerAction::Resign).unwrap();
+
+        assert_eq!(game_engine.winner(), Some(pl[0]));
+        assert!(game_engine.is_player_active(pl[0]));
+        assert!(game_engine
+            .take_events()
+            .contains(&GameEvent::GameWon(pl[0])));
+    }
+
+    #[test]
+    fn end_turn_reports_defeated_players_and_winner() {
+        let (pl, ri, mut game_engine) = create_valid_engine();
+        game_engine.modify_money(ri[0], description(UnitType::Soldier).purchase_cost);
+        game_engine
+            .act(
+                pl[0].id(),
+                PlayerAction::MoveUnit {
+                    src: Coord::new(1, 0),
+                    dst: Coord::new(1, 1),
+                },
+            ).unwrap();
+        game_engine
+            .act(
+                pl[0].id(),
+                PlayerAction::PlaceNewUnit(ri[0], UnitType::Soldier, Coord::new(-1, 0)),
+            ).unwrap();
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
+
+        let events = game_engine.take_events();
+        assert!(events.contains(&GameEvent::PlayerDefeated(pl[1].id())));
+        assert!(events.contains(&GameEvent::PlayerDefeated(pl[2].id())));
+        assert_eq!(events.last(), Some(&GameEvent::GameWon(pl[0].id())));
+    }
+
+    #[test]
+    fn end_turn_selects_winning_alliance() {
+        let (pl, ri, mut game_engine) = create_valid_engine();
+        game_engine.set_alliances(vec![vec![pl[1].id(), pl[0].id()]]);
+        game_engine.modify_money(ri[0], description(UnitType::Soldier).purchase_cost);
+        game_engine
+            .act(
+                pl[0].id(),
+                PlayerAction::PlaceNewUnit(ri[0], UnitType::Soldier, Coord::new(-1, 0)),
+            ).unwrap();
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+        assert_eq!(game_engine.winner(), None);
+        game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
+
+        assert_eq!(game_engine.winner(), Some(pl[0].id()));
+        assert_eq!(game_engine.winners(), vec![pl[0].id(), pl[1].id()]);
+        let events = game_engine.take_events();
+        assert!(events.contains(&GameEvent::PlayerDefeated(pl[2].id())));
+        let won = [GameEvent::GameWon(pl[0].id()), GameEvent::GameWon(pl[1].id())];
+        assert!(events.ends_with(&won));
+    }
+
+    /// Make a truce between the first two players: the first one proposes it and the second one
+    /// accepts it during the first turn
+    fn make_truce(game_engine: &mut GameEngine, pl: &[Player], turns: u32) {
+        let proposal = PlayerAction::ProposeTruce {
+            player_id: pl[1].id(),
+            turns,
+        };
+        game_engine.act(pl[0].id(), proposal).unwrap();
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+        game_engine
+            .act(pl[1].id(), PlayerAction::AcceptTruce(pl[0].id()))
+            .unwrap();
+    }
+
+    #[test]
+    fn truce_blocks_attacks_until_it_expires() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        make_truce(&mut game_engine, &pl, 2);
+        game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[2].id(), PlayerAction::EndTurn).unwrap();
+
+        let attack = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(1, 1),
+        };
+        assert!(game_engine.have_truce(pl[1].id(), pl[0].id()));
+        assert_eq!(
+            game_engine.act(pl[0].id(), attack),
+            Err(PlayerActionError::InaccessibleLocation(Coord::new(1, 1)))
+        );
+        game_engine.take_events();
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[2].id(), PlayerAction::EndTurn).unwrap();
+
+        assert_eq!(game_engine.current_turn(), 3);
+        assert!(!game_engine.have_truce(pl[0].id(), pl[1].id()));
+        assert!(game_engine
+            .take_events()
+            .contains(&GameEvent::TruceExpired(pl[0].id(), pl[1].id())));
+        assert_eq!(game_engine.act(pl[0].id(), attack), Ok(()));
+    }
+
+    #[test]
+    fn truce_events_are_reported() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        make_truce(&mut game_engine, &pl, 5);
+        game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[2].id(), PlayerAction::EndTurn).unwrap();
+        game_engine
+            .act(pl[0].id(), PlayerAction::BreakTruce(pl[1].id()))
+            .unwrap();
+
+        assert_eq!(
+            game_engine.take_events(),
+            vec![
+                GameEvent::TruceProposed {
+                    from: pl[0].id(),
+                    to: pl[1].id(),
+                    turns: 5,
+                },
+                GameEvent::TruceAccepted {
+                    from: pl[0].id(),
+                    to: pl[1].id(),
+                    ends_on_turn: 6,
+                },
+                GameEvent::TruceBroken {
+                    by: pl[0].id(),
+                    with: pl[1].id(),
+                },
+            ]
+        );
+        assert!(game_engine.truces().is_empty());
+        assert!(game_engine.truce_proposals().is_empty());
+        let attack = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(1, 1),
+        };
+        assert_eq!(game_engine.act(pl[0].id(), attack), Ok(()));
+    }
+
+    #[test]
+    fn truce_actions_errors() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        game_engine.set_alliances(vec![vec![pl[0].id(), pl[2].id()]]);
+        let propose = |player_id, turns| PlayerAction::ProposeTruce { player_id, turns };
+
+        assert_eq!(
+            game_engine.act(pl[0].id(), propose(pl[0].id(), 3)),
+            Err(PlayerActionError::CannotNegotiateWith(pl[0].id()))
+        );
+        assert_eq!(
+            game_engine.act(pl[0].id(), propose(pl[2].id(), 3)),
+            Err(PlayerActionError::CannotNegotiateWith(pl[2].id()))
+        );
+        assert_eq!(
+            game_engine.act(pl[0].id(), propose(pl[1].id(), 0)),
+            Err(PlayerActionError::InvalidTruceDuration(0))
+        );
+        assert_eq!(
+            game_engine.act(pl[0].id(), PlayerAction::AcceptTruce(pl[1].id())),
+            Err(PlayerActionError::NoTruceProposal(pl[1].id()))
+        );
+        assert_eq!(
+            game_engine.act(pl[0].id(), PlayerAction::BreakTruce(pl[1].id())),
+            Err(PlayerActionError::NoTruce(pl[1].id()))
+        );
+
+        make_truce(&mut game_engine, &pl, 3);
+        assert_eq!(
+            game_engine.act(pl[1].id(), propose(pl[0].id(), 3)),
+            Err(PlayerActionError::AlreadyInTruce(pl[0].id()))
+        );
+    }
+
+    #[test]
+    fn check_action_agrees_with_act() {
+        let (pl, ri, game_engine) = create_valid_engine();
+        let coordinates: Vec<Coord> = game_engine.location().map().keys().cloned().collect();
+        let mut actions = vec![
+            PlayerAction::EndTurn,
+            PlayerAction::Resign,
+            PlayerAction::ProposeTruce {
+                player_id: pl[1].id(),
+                turns: 0,
+            },
+            PlayerAction::ProposeTruce {
+                player_id: pl[1].id(),
+                turns: 2,
+            },
+            PlayerAction::AcceptTruce(pl[1].id()),
+            PlayerAction::BreakTruce(pl[1].id()),
+        ];
+        for &src in coordinates.iter() {
+            actions.push(PlayerAction::UpgradeUnit(src));
+            actions.push(PlayerAction::PlaceNewUnit(ri[0], UnitType::Militia, src));
+            for &dst in coordinates.iter() {
+                actions.push(PlayerAction::MoveUnit { src, dst });
+                actions.push(PlayerAction::Shoot { src, dst });
+            }
+        }
+
+        let mut accepted = 0;
+        for player in pl.iter().take(2) {
+            for action in actions.iter() {
+                let result = game_engine.clone().act(player.id(), *action);
+                assert_eq!(
+                    game_engine.check_action(player.id(), action),
+                    result,
+                    "{:?}",
+                    action
+                );
+                if result.is_ok() {
+                    accepted += 1;
+                }
+            }
+        }
+        assert!(accepted > 0);
+    }
+
+    const TERRAIN_MAP: &str = "players 2
+origin 0 0
+1V  1S  1.^ 2.  2.
+  1.  1., 2.^ 2V  2.
+";
+
+    fn create_terrain_engine() -> (Vec<ID>, GameEngine) {
+        let engine = parse(TERRAIN_MAP).unwrap().build().unwrap();
+        let players = engine.players().iter().map(|p| p.id()).collect();
+        (players, engine)
+    }
+
+    #[test]
+    fn move_unit_to_mountain_costs_more_moves() {
+        let (pl, mut game_engine) = create_terrain_engine();
+        let (src, dst) = (Coord::new(1, 0), Coord::new(2, 0));
+        let unit_id = game_engine.location.tile_at(src).unwrap().unit().unwrap().id();
+
+        game_engine
+            .act(pl[0], PlayerAction::MoveUnit { src, dst })
+            .unwrap();
+
+        assert_eq!(
+            game_engine.unit_moves_left(unit_id),
+            Some(STANDARD_MOVES_NUM - MOUNTAIN_MOVE_COST)
+        );
+    }
+
+    #[test]
+    fn move_unit_goes_around_mountains_and_reports_path() {
+        let text = "players 2
+origin 0 0
+1V  1S  1.^ 1.^ 1.  2V
+  1.  1.  1.  1.  2.  2.
+";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let player_id = game_engine.players()[0].id();
+        let (src, dst) = (Coord::new(1, 0), Coord::new(4, 0));
+        let unit_id = game_engine.location.tile_at(src).unwrap().unit().unwrap().id();
+
+        game_engine
+            .act(player_id, PlayerAction::MoveUnit { src, dst })
+            .unwrap();
+
+        assert_eq!(game_engine.unit_moves_left(unit_id), Some(0));
+        assert_eq!(
+            game_engine.take_events(),
+            vec![GameEvent::UnitMoved {
+                player_id,
+                unit_type: UnitType::Soldier,
+                path: vec![
+                    src,
+                    Coord::new(1, 1),
+                    Coord::new(2, 1),
+                    Coord::new(3, 1),
+                    dst
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn move_unit_reports_path_on_plain_land() {
+        let text = "players 2
+origin 0 0
+1V  1S  1.  1.  1.  2V
+  1.  1.  1.  1.  2.  2.
+";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let player_id = game_engine.players()[0].id();
+        let (src, dst) = (Coord::new(1, 0), Coord::new(4, 0));
+        let unit_id = game_engine.location.tile_at(src).unwrap().unit().unwrap().id();
+
+        game_engine
+            .act(player_id, PlayerAction::MoveUnit { src, dst })
+            .unwrap();
+
+        assert_eq!(game_engine.unit_moves_left(unit_id), Some(STANDARD_MOVES_NUM - 3));
+        assert_eq!(
+            game_engine.take_events(),
+            vec![GameEvent::UnitMoved {
+                player_id,
+                unit_type: UnitType::Soldier,
+                path: vec![src, Coord::new(2, 0), Coord::new(3, 0), dst],
+            }]
+        );
+    }
+
+    #[test]
+    fn mountain_adds_defence_to_tile() {
+        let (pl, mut game_engine) = create_terrain_engine();
+        let src = Coord::new(1, 0);
+
+        // Soldier attacks with 2, and the village next to the mountain defends it with 1 + 1
+        assert_eq!(
+            game_engine.act(
+                pl[0],
+                PlayerAction::MoveUnit {
+                    src,
+                    dst: Coord::new(2, 1)
+                }
+            ),
+            Err(PlayerActionError::InaccessibleLocation(Coord::new(2, 1)))
+        );
+
+        // Knight attacks with 3 and climbs the mountain through the marsh
+        game_engine.maybe_remove_unit(src);
+        let unit_id = game_engine.create_and_place_unit(UnitType::Knight, src).unwrap();
+        game_engine.unit_info.get_mut(&unit_id).unwrap().refill_moves();
+        game_engine
+            .act(
+                pl[0],
+                PlayerAction::MoveUnit {
+                    src,
+                    dst: Coord::new(2, 1),
+                },
+            ).unwrap();
+    }
+
+    #[test]
+    fn reachable_tiles_are_marked_by_kind() {
+        let (_, game_engine) = create_terrain_engine();
+
+        let reachable = game_engine.reachable_tiles(Coord::new(1, 0));
+
+        assert_eq!(
+            reachable[&Coord::new(2, 0)],
+            ReachableTile {
+                moves_left: STANDARD_MOVES_NUM - MOUNTAIN_MOVE_COST,
+                kind: MoveKind::Move,
+            }
+        );
+        assert_eq!(reachable[&Coord::new(3, 0)].kind, MoveKind::Attack);
+        assert_eq!(reachable[&Coord::new(3, 0)].moves_left, 0);
+        assert!(!reachable.contains_key(&Coord::new(1, 0)));
+        // Village and mountain protect this tile from the soldier
+        assert!(!reachable.contains_key(&Coord::new(2, 1)));
+        assert!(game_engine.reachable_tiles(Coord::new(1, 1)).is_empty());
+    }
+
+    #[test]
+    fn reachable_tiles_match_moves() {
+        let (pl, _, game_engine) = create_valid_engine();
+        let src = Coord::new(1, 0);
+        let unit_id = game_engine.location.tile_at(src).unwrap().unit().unwrap().id();
+
+        let reachable = game_engine.reachable_tiles(src);
+
+        assert!(!reachable.is_empty());
+        for &dst in game_engine.location.map().keys().filter(|&&c| c != src) {
+            let mut copy = game_engine.clone();
+            let result = copy.act(pl[0].id(), PlayerAction::MoveUnit { src, dst });
+            assert_eq!(result.is_ok(), reachable.contains_key(&dst), "{:?}", dst);
+            if let Some(tile) = reachable.get(&dst) {
+                if tile.kind != MoveKind::Merge {
+                    assert_eq!(copy.unit_moves_left(unit_id), Some(tile.moves_left));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn marsh_brings_less_income() {
+        let (pl, game_engine) = create_terrain_engine();
+        let region_id = game_engine
+            .location
+            .region_at(Coord::new(0, 0))
+            .unwrap()
+            .id();
+        assert_eq!(game_engine.location.regions()[&region_id].owner().id(), pl[0]);
+
+        let income = 3 * EMPTY_TILE_INCOME + MARSH_TILE_INCOME + EMPTY_TILE_INCOME;
+        assert_eq!(
+            game_engine.region_profit(region_id),
+            Some(income - description(UnitType::Soldier).turn_cost)
+        );
+    }
+
+    const ISLANDS_MAP: &str = "players 2
+origin 0 0
+1V  1S  ~~  ~~  2.  2V
+  1.  1.  ~~  ~~  2.  2.
+";
+
+    fn create_islands_engine() -> (Vec<ID>, ID, GameEngine) {
+        let mut builder = parse(ISLANDS_MAP).unwrap();
+        builder.set_rules_variant(RulesVariant::Archipelago);
+        let mut engine = builder.build().unwrap();
+        let players = engine.players().iter().map(|p| p.id()).collect();
+        let region_id = engine.location.region_at(Coord::new(0, 0)).unwrap().id();
+        engine.modify_money(region_id, 40);
+        (players, region_id, engine)
+    }
+
+    #[test]
+    fn naval_units_are_bought_only_in_archipelago() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        let region_id = game_engine.location.region_at(Coord::new(1, 0)).unwrap().id();
+        game_engine.modify_money(region_id, 40);
+
+        assert_eq!(
+            game_engine.act(
+                pl[0].id(),
+                PlayerAction::PlaceNewUnit(region_id, UnitType::Bridge, Coord::new(1, 1))
+            ),
+            Err(PlayerActionError::CannotBePlacedByPlayer(UnitType::Bridge))
+        );
+    }
+
+    #[test]
+    fn bridge_does_not_count_towards_size_of_region() {
+        let text = "players 2\norigin 0 0\n1V  1K  2.  2B  2.\n  1.  1.  ~~  ~~  ~~\n";
+        let mut builder = parse(text).unwrap();
+        builder.set_rules_variant(RulesVariant::Archipelago);
+        let mut game_engine = builder.build().unwrap();
+        let pl: Vec<ID> = game_engine.players().iter().map(|p| p.id()).collect();
+
+        // Knight takes one of two land tiles of the second player, the other one is left with the
+        // bridge
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(2, 0),
+        };
+        game_engine.act(pl[0], action).unwrap();
+        game_engine.act(pl[0], PlayerAction::EndTurn).unwrap();
+
+        let region = game_engine.location.region_at(Coord::new(4, 0)).unwrap();
+        assert_eq!(region.coordinates().len(), 2);
+        assert!(!game_engine.player_activity[&pl[1]]);
+        assert_eq!(game_engine.winner(), Some(pl[0]));
+    }
+
+    #[test]
+    fn land_units_cross_water_over_bridges() {
+        let (pl, region_id, mut game_engine) = create_islands_engine();
+        assert_eq!(
+            game_engine.act(
+                pl[0],
+                PlayerAction::PlaceNewUnit(region_id, UnitType::Bridge, Coord::new(3, 0))
+            ),
+            Err(PlayerActionError::InaccessibleLocation(Coord::new(3, 0)))
+        );
+        for &x in [2, 3].iter() {
+            game_engine
+                .act(
+                    pl[0],
+                    PlayerAction::PlaceNewUnit(region_id, UnitType::Bridge, Coord::new(x, 0)),
+                ).unwrap();
+        }
+        assert_eq!(
+            game_engine.location.region_at(Coord::new(3, 0)).unwrap().id(),
+            region_id
+        );
+
+        let src = Coord::new(1, 0);
+        assert_eq!(
+            game_engine.act(
+                pl[0],
+                PlayerAction::MoveUnit {
+                    src,
+                    dst: Coord::new(2, 0)
+                }
+            ),
+            Err(PlayerActionError::InaccessibleLocation(Coord::new(2, 0)))
+        );
+        let dst = Coord::new(4, 0);
+        game_engine
+            .act(pl[0], PlayerAction::MoveUnit { src, dst })
+            .unwrap();
+
+        assert_eq!(game_engine.location.region_at(dst).unwrap().id(), region_id);
+        let unit = game_engine.location.tile_at(dst).unwrap().unit();
+        assert_eq!(unit.map(|u| u.unit_type()), Some(UnitType::Soldier));
+    }
+
+    #[test]
+    fn boat_carries_land_unit_across_water() {
+        let (pl, region_id, mut game_engine) = create_islands_engine();
+        let (shore, sea, landing) = (Coord::new(1, 0), Coord::new(2, 0), Coord::new(4, 0));
+        game_engine
+            .act(
+                pl[0],
+                PlayerAction::PlaceNewUnit(region_id, UnitType::Boat, sea),
+            ).unwrap();
+        game_engine.refill_moves();
+
+        game_engine
+            .act(pl[0], PlayerAction::MoveUnit { src: shore, dst: sea })
+            .unwrap();
+        assert!(game_engine.location.tile_at(shore).unwrap().unit().is_none());
+        let cargo = game_engine.cargo_at(sea).map(|u| u.unit_type());
+        assert_eq!(cargo, Some(UnitType::Soldier));
+        let reachable = game_engine.reachable_tiles(sea);
+        assert_eq!(reachable[&Coord::new(3, 0)].kind, MoveKind::Move);
+        assert_eq!(reachable[&shore].kind, MoveKind::Move);
+
+        let dst = Coord::new(3, 0);
+        game_engine
+            .act(pl[0], PlayerAction::MoveUnit { src: sea, dst })
+            .unwrap();
+        assert!(game_engine.location.region_at(sea).is_none());
+        assert_eq!(game_engine.cargo_at(dst).map(|u| u.unit_type()), cargo);
+        // Boat has left its island and holds its own region now
+        let boat_region_id = game_engine.location.region_at(dst).unwrap().id();
+        let island_region_id = game_engine.location.region_at(shore).unwrap().id();
+        assert_ne!(boat_region_id, island_region_id);
+
+        game_engine
+            .act(pl[0], PlayerAction::MoveUnit { src: dst, dst: landing })
+            .unwrap();
+        assert_eq!(game_engine.cargo_at(dst), None);
+        let unit = game_engine.location.tile_at(landing).unwrap().unit();
+        assert_eq!(unit.map(|u| u.unit_type()), Some(UnitType::Soldier));
+        assert_eq!(
+            game_engine.location.region_at(landing).unwrap().id(),
+            boat_region_id
+        );
+        assert_eq!(
+            game_engine.take_events().last(),
+            Some(&GameEvent::UnitMoved {
+                player_id: pl[0],
+                unit_type: UnitType::Soldier,
+                path: vec![dst, landing],
+            })
+        );
+    }
+
+    const ROSTER_MAP: &str = "players 2
+origin 0 0
+1V  1A  1.  2M  2V
+  1.  1E  1P  2S  2.
+";
+
+    #[test]
+    fn archer_shoots_units_at_distance() {
+        let mut game_engine = parse(ROSTER_MAP).unwrap().build().unwrap();
+        let player_id = game_engine.players()[0].id();
+        let src = Coord::new(1, 0);
+        let shoot = |dst| PlayerAction::Shoot { src, dst };
+
+        assert_eq!(
+            game_engine.act(player_id, shoot(Coord::new(3, 1))),
+            Err(PlayerActionError::InaccessibleLocation(Coord::new(3, 1)))
+        );
+        assert_eq!(
+            game_engine.act(player_id, shoot(Coord::new(2, 0))),
+            Err(PlayerActionError::NoUnit(Coord::new(2, 0)))
+        );
+        game_engine
+            .act(player_id, shoot(Coord::new(3, 0)))
+            .unwrap();
+
+        let unit = game_engine.location.tile_at(Coord::new(3, 0)).unwrap().unit();
+        assert_eq!(unit.map(|u| u.unit_type()), Some(UnitType::Grave));
+        assert_eq!(
+            game_engine.take_events(),
+            vec![GameEvent::UnitShot {
+                player_id,
+                unit_type: UnitType::Militia,
+                coordinate: Coord::new(3, 0),
+            }]
+        );
+        assert_eq!(
+            game_engine.act(player_id, shoot(Coord::new(3, 0))),
+            Err(PlayerActionError::NotEnoughMoves(0, 1))
+        );
+        assert_eq!(
+            game_engine.act(
+                player_id,
+                PlayerAction::Shoot {
+                    src: Coord::new(1, 1),
+                    dst: Coord::new(3, 0)
+                }
+            ),
+            Err(PlayerActionError::CannotShoot(UnitType::Engineer))
+        );
+    }
+
+    #[test]
+    fn engineer_clears_trees_without_spending_all_moves() {
+        let mut game_engine = parse(ROSTER_MAP).unwrap().build().unwrap();
+        let player_id = game_engine.players()[0].id();
+        let (src, dst) = (Coord::new(1, 1), Coord::new(2, 1));
+        let unit_id = game_engine.location.tile_at(src).unwrap().unit().unwrap().id();
+
+        assert_eq!(
+            game_engine.reachable_tiles(src)[&dst],
+            ReachableTile {
+                moves_left: STANDARD_MOVES_NUM - 1,
+                kind: MoveKind::Move,
+            }
+        );
+        game_engine
+            .act(player_id, PlayerAction::MoveUnit { src, dst })
+            .unwrap();
+
+        assert_eq!(
+            game_engine.unit_moves_left(unit_id),
+            Some(STANDARD_MOVES_NUM - 1)
+        );
+    }
+
+    #[test]
+    fn tower_is_upgraded_to_castle() {
+        let (pl, _, mut game_engine) = create_valid_engine();
+        let dst = Coord::new(2, -1);
+        let region_id = game_engine.location.region_at(dst).unwrap().id();
+        game_engine.modify_money(region_id, 50);
+        game_engine
+            .act(
+                pl[0].id(),
+                PlayerAction::PlaceNewUnit(region_id, UnitType::Tower, dst),
+            ).unwrap();
+        game_engine
+            .act(pl[0].id(), PlayerAction::UpgradeUnit(dst))
+            .unwrap();
+
+        let unit = game_engine.location.tile_at(dst).unwrap().unit();
+        assert_eq!(unit.map(|u| u.unit_type()), Some(UnitType::Castle));
+        assert_eq!(
+            game_engine.act(pl[0].id(), PlayerAction::UpgradeUnit(dst)),
+            Err(PlayerActionError::NoUpgrade(UnitType::Castle))
+        );
+    }
+
+    #[test]
+    fn soldier_merged_into_tower_makes_castle() {
+        let text = "players 2\norigin 0 0\n1V  1S  1T  2.  2V\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let player_id = game_engine.players()[0].id();
+        let (src, dst) = (Coord::new(1, 0), Coord::new(2, 0));
+        game_engine
+            .act(player_id, PlayerAction::MoveUnit { src, dst })
+            .unwrap();
+
+        assert!(game_engine.location.tile_at(src).unwrap().unit().is_none());
+        let unit = game_engine.location.tile_at(dst).unwrap().unit();
+        assert_eq!(unit.map(|u| u.unit_type()), Some(UnitType::Castle));
+    }
+
+    #[test]
+    fn veterans_defend_better_and_attackers_count_kills() {
+        let text = "players 2\norigin 0 0\n1V  1K  2S  2V\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let player_id = game_engine.players()[0].id();
+        let (src, dst) = (Coord::new(1, 0), Coord::new(2, 0));
+        let knight_id = game_engine.location.tile_at(src).unwrap().unit().unwrap().id();
+        let soldier_id = game_engine.location.tile_at(dst).unwrap().unit().unwrap().id();
+
+        let mut copy = game_engine.clone();
+        copy.act(player_id, PlayerAction::MoveUnit { src, dst })
+            .unwrap();
+        assert_eq!(copy.unit_info(knight_id).kills(), 1);
+
+        for _ in 0..VETERAN_KILLS {
+            game_engine.unit_info.get_mut(&soldier_id).unwrap().add_kill();
+        }
+        assert_eq!(game_engine.unit_is_veteran(soldier_id), Some(true));
+        assert_eq!(
+            game_engine.act(player_id, PlayerAction::MoveUnit { src, dst }),
+            Err(PlayerActionError::InaccessibleLocation(dst))
+        );
+    }
+
+    #[test]
+    fn great_knights_capture_tiles_of_veterans() {
+        let text = "players 2\norigin 0 0\n1V  1G  2K  2V\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let player_id = game_engine.players()[0].id();
+        let (src, dst) = (Coord::new(1, 0), Coord::new(2, 0));
+        let knight_id = game_engine.location.tile_at(dst).unwrap().unit().unwrap().id();
+
+        for _ in 0..VETERAN_KILLS {
+            game_engine.unit_info.get_mut(&knight_id).unwrap().add_kill();
+        }
+        assert_eq!(game_engine.unit_info(knight_id).defence(), 4);
+        game_engine
+            .act(player_id, PlayerAction::MoveUnit { src, dst })
+            .unwrap();
+        let unit = game_engine.location.tile_at(dst).unwrap().unit();
+        assert_eq!(unit.map(|u| u.unit_type()), Some(UnitType::GreatKnight));
+    }
+
+    #[test]
+    fn capturing_capital_adds_no_kill() {
+        let text = "players 2\norigin 0 0\n1V  1K  2V  2.\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let player_id = game_engine.players()[0].id();
+        let region_id = game_engine.location.region_at(Coord::new(0, 0)).unwrap().id();
+        let (src, dst) = (Coord::new(1, 0), Coord::new(2, 0));
+        let knight_id = game_engine.location.tile_at(src).unwrap().unit().unwrap().id();
+
+        let mut copy = game_engine.clone();
+        copy.act(player_id, PlayerAction::MoveUnit { src, dst })
+            .unwrap();
+        assert_eq!(copy.unit_info(knight_id).kills(), 0);
+
+        game_engine.modify_money(region_id, description(UnitType::Soldier).purchase_cost);
+        game_engine
+            .act(
+                player_id,
+                PlayerAction::PlaceNewUnit(region_id, UnitType::Soldier, dst),
+            ).unwrap();
+        let soldier_id = game_engine.location.tile_at(dst).unwrap().unit().unwrap().id();
+        assert_eq!(game_engine.unit_info(soldier_id).kills(), 0);
+    }
+
+    #[test]
+    fn units_in_the_field_become_veterans_with_time() {
+        let text = "players 2\norigin 0 0\n1V  1S  1.  1.  1.  1.  1.  2.  2V\n";
+        let mut game_engine = parse(text).unwrap().build().unwrap();
+        let pl = game_engine.players().to_vec();
+        let unit_id = game_engine
+            .location
+            .tile_at(Coord::new(1, 0))
+            .unwrap()
+            .unit()
+            .unwrap()
+            .id();
+
+        for _ in 0..VETERAN_TURNS {
+            assert_eq!(game_engine.unit_is_veteran(unit_id), Some(false));
+            for p in pl.iter() {
+                game_engine.act(p.id(), PlayerAction::EndTurn).unwrap();
+            }
+        }
+        assert_eq!(game_engine.unit_is_veteran(unit_id), Some(true));
+    }
+
+    #[test]
+    fn end_turn_spawns_trees_on_top_of_graves() {
+        let (pl, _ri, mut game_engine) = create_valid_engine();
+        let coordinate = Coord::new(2, -1);
+        game_engine
+            .create_and_place_unit(UnitType::Grave, coordinate)
+            .unwrap();
+        game_engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[1].id(), PlayerAction::EndTurn).unwrap();
+        game_engine.act(pl[2].id(), PlayerAction::EndTurn).unwrap();
+
+        assert_eq!(game_engine.current_turn(), 2);
+        assert_eq!(*game_engine.active_player(), pl[0]);
+
+        let tree = game_engine.location.tile_at(coordinate).unwrap().unit();
+        assert!(tree.is_some());
+        assert_eq!(tree.unwrap().unit_type(), UnitType::PineTree);
+    }
+
+    #[test]
+    fn end_turn_dont_spreads_trees_on_existing_units() {
+        let (pl, _ri, mut game_engine) = create_valid_engine();
+        let coordinate = Coord::new(1, 0);
         game_engine.maybe_remove_unit(coordinate).unwrap();
         game_engine
             .create_and_place_unit(UnitType::PalmTree, coordinate)
@@ -2003,4 +4371,90 @@ mod test {
             .unwrap();
         assert_eq!(unit.unit_type(), UnitType::Soldier);
     }
+
+    #[test]
+    fn check_actions_have_tagged_json_representation() {
+        let actions = [
+            (
+                PlayerAction::PlaceNewUnit(3, UnitType::Militia, Coord::new(1, -1)),
+                r#"{"type":"PlaceNewUnit","data":[3,"Militia",{"x":1,"y":-1}]}"#,
+            ),
+            (
+                PlayerAction::UpgradeUnit(Coord::new(0, 2)),
+                r#"{"type":"UpgradeUnit","data":{"x":0,"y":2}}"#,
+            ),
+            (
+                PlayerAction::MoveUnit {
+                    src: Coord::new(0, 0),
+                    dst: Coord::new(1, 0),
+                },
+                r#"{"type":"MoveUnit","data":{"src":{"x":0,"y":0},"dst":{"x":1,"y":0}}}"#,
+            ),
+            (PlayerAction::EndTurn, r#"{"type":"EndTurn"}"#),
+            (
+                PlayerAction::ProposeTruce {
+                    player_id: 2,
+                    turns: 3,
+                },
+                r#"{"type":"ProposeTruce","data":{"player_id":2,"turns":3}}"#,
+            ),
+            (PlayerAction::BreakTruce(2), r#"{"type":"BreakTruce","data":2}"#),
+        ];
+
+        for &(action, json) in actions.iter() {
+            assert_eq!(serde_json::to_string(&action).unwrap(), json);
+            assert_eq!(serde_json::from_str::<PlayerAction>(json).unwrap(), action);
+        }
+    }
+
+    #[test]
+    fn check_errors_have_tagged_json_representation() {
+        let error = PlayerActionError::LocationError(LocationModificationError::InvalidResult(
+            LocationValidationError::SameOwnerBorderingRegions(1, 2),
+        ));
+        let json = r#"{"type":"LocationError","data":{"type":"InvalidResult","data":{"type":"SameOwnerBorderingRegions","data":[1,2]}}}"#;
+
+        assert_eq!(serde_json::to_string(&error).unwrap(), json);
+        assert_eq!(
+            serde_json::from_str::<PlayerActionError>(json).unwrap(),
+            error
+        );
+
+        let error = EngineValidationError::RegionsError(RegionsValidationError::NoActiveRegions(4));
+        let json = serde_json::to_string(&error).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"RegionsError","data":{"type":"NoActiveRegions","data":4}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<EngineValidationError>(&json).unwrap(),
+            error
+        );
+    }
+
+    #[test]
+    fn check_errors_have_readable_messages() {
+        let error = PlayerActionError::NotEnoughMoves(1, 2);
+        assert_eq!(
+            error.to_string(),
+            "Unit has 1 moves left, but needs 2 to get there"
+        );
+
+        let error = PlayerActionError::LocationError(LocationModificationError::InvalidResult(
+            LocationValidationError::SplitRegions(5),
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Location would become invalid: Region 5 consists of several separate parts"
+        );
+        assert!(error.source().is_some());
+
+        let error = EngineValidationError::LocationError(
+            LocationRulesValidationError::NotCoveredWithRegions(Coord::new(-1, 2)),
+        );
+        assert_eq!(
+            error.to_string(),
+            "Invalid location: Land at (-1, 2) is not a part of any region"
+        );
+    }
 }
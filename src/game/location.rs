@@ -1,7 +1,11 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
 
 use hex2d::Coordinate;
 
+use super::consts::*;
 use super::ids::{IdProducer, ID, NO_ID};
 
 pub type Coord = Coordinate<i32>;
@@ -17,6 +21,16 @@ pub enum UnitType {
     Knight,
     Soldier,
     Militia,
+    /// Stands on water and lets land units cross it
+    Bridge,
+    /// Sails over water and carries one land unit
+    Boat,
+    /// Tower upgraded to defend better
+    Castle,
+    /// Shoots units of other players from a distance
+    Archer,
+    /// Removes trees without spending all moves
+    Engineer,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
@@ -43,6 +57,10 @@ impl Unit {
 pub enum TileSurface {
     Water,
     Land,
+    /// Land that takes more moves to step on and is easier to defend
+    Mountain,
+    /// Land that gives less income
+    Marsh,
 }
 
 impl TileSurface {
@@ -55,10 +73,11 @@ impl TileSurface {
     ///
     /// assert_eq!(TileSurface::Water.is_land(), false);
     /// assert_eq!(TileSurface::Land.is_land(), true);
+    /// assert_eq!(TileSurface::Mountain.is_land(), true);
     /// ```
     ///
     pub fn is_land(self) -> bool {
-        self == TileSurface::Land
+        self != TileSurface::Water
     }
 
     /// Returns true if surface is land
@@ -75,6 +94,31 @@ impl TileSurface {
     pub fn is_water(self) -> bool {
         self == TileSurface::Water
     }
+
+    /// Returns a number of moves unit spends to step on a tile with this surface
+    pub fn move_cost(self) -> u32 {
+        match self {
+            TileSurface::Mountain => MOUNTAIN_MOVE_COST,
+            _ => 1,
+        }
+    }
+
+    /// Returns defence that surface adds to defence of a tile
+    pub fn defence(self) -> u8 {
+        match self {
+            TileSurface::Mountain => MOUNTAIN_DEFENCE,
+            _ => 0,
+        }
+    }
+
+    /// Returns money that a tile with this surface brings to its region each turn
+    pub fn income(self) -> i32 {
+        match self {
+            TileSurface::Water => 0,
+            TileSurface::Marsh => MARSH_TILE_INCOME,
+            TileSurface::Land | TileSurface::Mountain => EMPTY_TILE_INCOME,
+        }
+    }
 }
 
 /// This struct represents contents of one tile of the hexagonal map
@@ -134,6 +178,69 @@ impl Player {
     pub fn id(self) -> ID {
         self.id
     }
+
+    /// Owner of regions left by players who have resigned. It never makes turns and is not one of
+    /// the players of a game.
+    pub fn neutral() -> Self {
+        Self { id: NO_ID }
+    }
+
+    pub fn is_neutral(self) -> bool {
+        self.id == NO_ID
+    }
+}
+
+/// Colors of players who have not chosen their own, in the order of turns
+pub const DEFAULT_PLAYER_COLORS: [&str; 6] = [
+    "#d9534f", "#5cb85c", "#f0ad4e", "#8e6bbf", "#5bc0de", "#c77c2e",
+];
+
+/// Who makes turns for a player
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum Controller {
+    /// A person at this computer
+    Human,
+    /// A computer controlled player with the name of the bot and its seed, see `bots::create`
+    Bot { name: String, seed: u64 },
+    /// A person connected over the network, see `net::Server`
+    Remote,
+}
+
+/// How a player is shown to others and who makes the turns. Profiles are kept by the engine in
+/// `PlayerInfo` apart from `Player`, which is only an id stored in every region.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    pub name: String,
+    /// Color in the `#rrggbb` form
+    pub color: String,
+    pub controller: Controller,
+}
+
+impl PlayerProfile {
+    /// Profile without a name that only marks a missing profile
+    pub(crate) fn unnamed() -> Self {
+        PlayerProfile {
+            name: String::new(),
+            color: String::new(),
+            controller: Controller::Human,
+        }
+    }
+
+    /// Profile of a human player with provided index in the order of turns, starting from 0
+    pub fn numbered(index: usize) -> Self {
+        PlayerProfile {
+            name: format!("Player {}", index + 1),
+            color: DEFAULT_PLAYER_COLORS[index % DEFAULT_PLAYER_COLORS.len()].to_owned(),
+            controller: Controller::Human,
+        }
+    }
+
+    /// Return true if color is in the `#rrggbb` form, so it can be put into SVG as is
+    pub fn has_valid_color(&self) -> bool {
+        let bytes = self.color.as_bytes();
+        bytes.len() == 7 && bytes[0] == b'#' && bytes[1..].iter().all(u8::is_ascii_hexdigit)
+    }
 }
 
 /// This represent some connected set of tiles on a hexagonal map. It should be always not empty and
@@ -181,7 +288,8 @@ pub enum RegionTransformation {
     Split { from: ID, into: Vec<ID> },
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum LocationValidationError {
     DuplicateRegionId(ID),
     SplitRegions(ID),
@@ -189,12 +297,14 @@ pub enum LocationValidationError {
     SameOwnerBorderingRegions(ID, ID),
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum LocationModificationError {
     CoordinateOutOfLocation(Coord),
     NoUnitAtCoordinate(Coord),
     CoordinateNotAdjacentToRegion(Coord),
     NoSuchRegion(ID),
+    NoRegionAtCoordinate(Coord),
     InvalidResult(LocationValidationError),
 }
 
@@ -211,6 +321,61 @@ impl From<LocationValidationError> for LocationModificationError {
     }
 }
 
+impl fmt::Display for LocationValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LocationValidationError::DuplicateRegionId(id) => {
+                write!(f, "There are several regions with id {}", id)
+            }
+            LocationValidationError::SplitRegions(id) => {
+                write!(f, "Region {} consists of several separate parts", id)
+            }
+            LocationValidationError::IntersectingRegions(c) => {
+                write!(f, "Several regions contain ({}, {})", c.x, c.y)
+            }
+            LocationValidationError::SameOwnerBorderingRegions(first, second) => write!(
+                f,
+                "Regions {} and {} have the same owner and border each other",
+                first, second
+            ),
+        }
+    }
+}
+
+impl Error for LocationValidationError {}
+
+impl fmt::Display for LocationModificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LocationModificationError::CoordinateOutOfLocation(c) => {
+                write!(f, "({}, {}) is out of location", c.x, c.y)
+            }
+            LocationModificationError::NoUnitAtCoordinate(c) => {
+                write!(f, "There is no unit at ({}, {})", c.x, c.y)
+            }
+            LocationModificationError::CoordinateNotAdjacentToRegion(c) => {
+                write!(f, "({}, {}) is not adjacent to the region", c.x, c.y)
+            }
+            LocationModificationError::NoSuchRegion(id) => write!(f, "There is no region {}", id),
+            LocationModificationError::NoRegionAtCoordinate(c) => {
+                write!(f, "There is no region at ({}, {})", c.x, c.y)
+            }
+            LocationModificationError::InvalidResult(e) => {
+                write!(f, "Location would become invalid: {}", e)
+            }
+        }
+    }
+}
+
+impl Error for LocationModificationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LocationModificationError::InvalidResult(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl Location {
     /// Create new location represented by specified map and regions.
     /// Return error if resulting location is not valid. See validness description in `validate`
@@ -219,27 +384,35 @@ impl Location {
         map: HashMap<Coord, Tile>,
         regions_vec: Vec<Region>,
     ) -> Result<Self, LocationValidationError> {
-        let mut coordinate_to_region = HashMap::default();
-        let mut regions = HashMap::default();
-        for region in regions_vec.into_iter() {
-            if regions.contains_key(&region.id) {
+        let mut region_ids = HashSet::new();
+        for region in regions_vec.iter() {
+            if !region_ids.insert(region.id) {
                 return Err(LocationValidationError::DuplicateRegionId(region.id));
             }
+        }
+        let location = Self::new_unvalidated(map, regions_vec);
+        Self::validate(&location)?;
+
+        Ok(location)
+    }
 
+    /// Create location without checking if it is valid. It is used for parts of valid locations,
+    /// where regions can be cut into pieces by the edge of the part.
+    pub(crate) fn new_unvalidated(map: HashMap<Coord, Tile>, regions_vec: Vec<Region>) -> Self {
+        let mut coordinate_to_region = HashMap::default();
+        let mut regions = HashMap::default();
+        for region in regions_vec.into_iter() {
             for &coordinate in region.coordinates.iter() {
                 coordinate_to_region.insert(coordinate, region.id);
             }
             regions.insert(region.id, region);
         }
 
-        let location = Self {
+        Self {
             map,
             regions,
             coordinate_to_region,
-        };
-        Self::validate(&location)?;
-
-        Ok(location)
+        }
     }
 
     /// Validate if location provided does not contain any errors. This method only ensures there
@@ -436,6 +609,84 @@ impl Location {
         Ok(performed_actions)
     }
 
+    /// Give a region with all its tiles to another owner. The region is merged with bordering
+    /// regions of the new owner.
+    ///
+    /// This method can return error with `LocationModificationError::NoSuchRegion` if there is no
+    /// region with provided ID. If everything went ok, this method will return a list of merges.
+    pub fn set_region_owner(
+        &mut self,
+        region_id: ID,
+        owner: Player,
+    ) -> Result<Vec<RegionTransformation>, LocationModificationError> {
+        let region = self
+            .regions
+            .get_mut(&region_id)
+            .ok_or(LocationModificationError::NoSuchRegion(region_id))?;
+        region.owner = owner;
+
+        let mut merge_ids: Vec<ID> = self.regions[&region_id]
+            .coordinates
+            .iter()
+            .flat_map(|c| c.neighbors().to_vec())
+            .filter_map(|c| self.region_at(c))
+            .filter(|r| r.id != region_id && r.owner == owner)
+            .map(|r| r.id)
+            .collect();
+        merge_ids.sort();
+        merge_ids.dedup();
+        let performed_actions = merge_ids
+            .iter()
+            .map(|&id| RegionTransformation::Merge {
+                from: id,
+                into: region_id,
+            }).collect();
+        self.merge_regions(merge_ids.into_iter().collect(), region_id);
+
+        Self::validate(self).expect("Changing owner of region never should make location invalid");
+
+        Ok(performed_actions)
+    }
+
+    /// Remove a tile with specified coordinate from its region, so it does not belong to any
+    /// region anymore. If removing the tile makes the region empty, it is deleted, and if it makes
+    /// the region separated, it is split into several regions.
+    ///
+    /// This method can return error with `LocationModificationError::NoRegionAtCoordinate` if
+    /// tile does not belong to any region, or `LocationModificationError::CoordinateOutOfLocation`
+    /// if coordinate is not inside the location bounds.
+    ///
+    /// If this method returns any kind of error, no changes to locations were made.
+    /// If everything went ok, this method will return a list of changes made into regions structure.
+    pub fn remove_tile_from_region(
+        &mut self,
+        coordinate: Coord,
+        id_producer: &mut IdProducer,
+    ) -> Result<Vec<RegionTransformation>, LocationModificationError> {
+        if !self.map.contains_key(&coordinate) {
+            return Err(LocationModificationError::CoordinateOutOfLocation(
+                coordinate,
+            ));
+        }
+        let region_id = *self
+            .coordinate_to_region
+            .get(&coordinate)
+            .ok_or(LocationModificationError::NoRegionAtCoordinate(coordinate))?;
+
+        let mut performed_actions = Vec::new();
+        self.remove_coordinate_from_region(region_id, coordinate);
+        if let Some(action) = self.maybe_remove_region(region_id) {
+            performed_actions.push(action);
+        }
+        if let Some(action) = self.maybe_split_region(region_id, id_producer) {
+            performed_actions.push(action);
+        }
+
+        Self::validate(self).expect("Removing tile from region never should make location invalid");
+
+        Ok(performed_actions)
+    }
+
     fn validate_and_prepare_add_tile(
         &self,
         coordinate: Coord,
@@ -564,7 +815,8 @@ impl Location {
     /// not connected to other region. If there are no such parts, return None
     fn region_part_to_remove(&self, region_id: ID) -> Option<HashSet<Coord>> {
         let region = &self.regions[&region_id];
-        let start = *region.coordinates.iter().next().unwrap();
+        // The part with the smallest coordinate always keeps the region
+        let start = *region.coordinates.iter().min().unwrap();
         let coords = self.bfs_set(start, |c| {
             self.coordinate_to_region.contains_key(&c)
                 && self.coordinate_to_region[&c].eq(&region_id)
@@ -627,6 +879,131 @@ impl Location {
             .find(|(_, coord)| *coord == to)
             .map(|(dist, _)| dist)
     }
+
+    /// Perform BFS to return a shortest path between two coordinates using only coordinates that
+    /// match the predicate. Returns the path from `from` to `to` including both of them, or `None`
+    /// if there is no path between coordinates.
+    pub fn bfs_path<P>(&self, from: Coord, to: Coord, predicate: P) -> Option<Vec<Coord>>
+    where
+        P: Fn(Coord) -> bool,
+    {
+        let mut distances = HashMap::new();
+        for (distance, coordinate) in self.bfs_iter(from, predicate) {
+            distances.insert(coordinate, distance);
+            if coordinate == to {
+                break;
+            }
+        }
+
+        let mut distance = *distances.get(&to)?;
+        let mut path = vec![to];
+        let mut current = to;
+        while distance > 0 {
+            distance -= 1;
+            // Ties are broken by coordinates, so paths do not depend on the order of the map
+            current = current
+                .neighbors()
+                .iter()
+                .filter(|c| distances.get(c) == Some(&distance))
+                .min()
+                .cloned()
+                .unwrap();
+            path.push(current);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Returns true if stepping on any tile of the location costs the same number of moves, so
+    /// paths can be found with BFS
+    pub fn has_uniform_move_costs(&self) -> bool {
+        self.map.values().all(|t| t.surface().move_cost() == 1)
+    }
+
+    /// Find the cheapest path between two coordinates with Dijkstra's algorithm. `cost` returns
+    /// a cost of stepping on a coordinate or `None` if it cannot be stepped on. The start
+    /// coordinate has to match `cost` too, but its cost is not counted.
+    /// Returns the total cost and the path from `from` to `to` including both of them, or `None`
+    /// if there is no path between coordinates.
+    ///
+    /// When all steps cost the same, `bfs_distance` gives the same distance.
+    pub fn shortest_path<C>(&self, from: Coord, to: Coord, cost: C) -> Option<(u32, Vec<Coord>)>
+    where
+        C: Fn(Coord) -> Option<u32>,
+    {
+        let processed = self.dijkstra(from, Some(to), cost);
+        let total = processed.get(&to)?.0;
+        let mut path = vec![to];
+        let mut current = to;
+        while current != from {
+            current = processed[&current].1;
+            path.push(current);
+        }
+        path.reverse();
+        Some((total, path))
+    }
+
+    /// Return costs of the cheapest paths from a coordinate to all coordinates that can be reached
+    /// from it, found with Dijkstra's algorithm. `cost` returns a cost of stepping on a coordinate
+    /// or `None` if it cannot be stepped on. The start coordinate has to match `cost` too, but it
+    /// has zero cost.
+    ///
+    /// When all steps cost the same, `bfs_iter` gives the same distances.
+    pub fn path_costs<C>(&self, from: Coord, cost: C) -> HashMap<Coord, u32>
+    where
+        C: Fn(Coord) -> Option<u32>,
+    {
+        self.dijkstra(from, None, cost)
+            .into_iter()
+            .map(|(c, (distance, _))| (c, distance))
+            .collect()
+    }
+
+    /// Run Dijkstra's algorithm from a coordinate until `target` is processed or there is nothing
+    /// left to process. Return processed coordinates with their costs and previous coordinates on
+    /// the cheapest paths to them, the start coordinate is previous to itself.
+    fn dijkstra<C>(
+        &self,
+        from: Coord,
+        target: Option<Coord>,
+        cost: C,
+    ) -> HashMap<Coord, (u32, Coord)>
+    where
+        C: Fn(Coord) -> Option<u32>,
+    {
+        let mut processed = HashMap::new();
+        let mut best = HashMap::new();
+        // Ties are broken by coordinates, so paths do not depend on the order of the map
+        let mut queue = BinaryHeap::new();
+        if self.tile_at(from).is_some() && cost(from).is_some() {
+            best.insert(from, (0, from));
+            queue.push(Reverse((0, from)));
+        }
+
+        while let Some(Reverse((distance, coordinate))) = queue.pop() {
+            if processed.contains_key(&coordinate) {
+                continue;
+            }
+            processed.insert(coordinate, best[&coordinate]);
+            if Some(coordinate) == target {
+                break;
+            }
+            for &neighbor in coordinate.neighbors().iter() {
+                if processed.contains_key(&neighbor) || self.tile_at(neighbor).is_none() {
+                    continue;
+                }
+                let neighbor_distance = match cost(neighbor) {
+                    Some(step) => distance + step,
+                    None => continue,
+                };
+                if best.get(&neighbor).map(|&(d, _)| d <= neighbor_distance) != Some(true) {
+                    best.insert(neighbor, (neighbor_distance, coordinate));
+                    queue.push(Reverse((neighbor_distance, neighbor)));
+                }
+            }
+        }
+        processed
+    }
 }
 
 pub struct BfsIter<'a, P> {
@@ -1057,6 +1434,88 @@ mod test {
         assert!(region.coordinates.contains(&Coord::new(-1, 0)));
     }
 
+    #[test]
+    fn location_set_region_owner_merges_regions() {
+        let mut location = create_valid_location();
+
+        let actions = location.set_region_owner(13, Player::new(21)).unwrap();
+        assert_eq!(
+            actions,
+            vec![
+                RegionTransformation::Merge { from: 11, into: 13 },
+                RegionTransformation::Merge { from: 14, into: 13 },
+            ]
+        );
+        assert_eq!(location.regions.len(), 2);
+        assert_eq!(location.regions[&13].coordinates.len(), 6);
+        assert_eq!(location.region_at(Coord::new(0, 1)).unwrap().id(), 13);
+
+        assert_eq!(
+            location.set_region_owner(11, Player::neutral()),
+            Err(LocationModificationError::NoSuchRegion(11))
+        );
+    }
+
+    #[test]
+    fn location_remove_tile_from_region_correct() {
+        let mut location = create_valid_location();
+        let mut id_producer = IdProducer::default();
+
+        let actions = location
+            .remove_tile_from_region(Coord::new(0, 0), &mut id_producer)
+            .unwrap();
+        assert_eq!(actions, vec![]);
+        assert_eq!(location.region_at(Coord::new(0, 0)), None);
+        assert_eq!(location.regions[&13].coordinates.len(), 1);
+
+        let actions = location
+            .remove_tile_from_region(Coord::new(-1, 1), &mut id_producer)
+            .unwrap();
+        assert_eq!(actions, vec![RegionTransformation::Delete(12)]);
+        assert!(!location.regions.contains_key(&12));
+    }
+
+    #[test]
+    fn location_remove_tile_from_region_split() {
+        let mut location = create_valid_location();
+        let mut id_producer = IdProducer::default();
+        let c = Coord::new(-1, 1);
+        location
+            .add_tile_to_region(c, 11, &mut id_producer)
+            .unwrap();
+
+        let actions = location
+            .remove_tile_from_region(c, &mut id_producer)
+            .unwrap();
+
+        assert_eq!(actions.len(), 1);
+        match &actions[0] {
+            RegionTransformation::Split { from, into } => {
+                assert_eq!(*from, 11);
+                assert_eq!(into.len(), 2);
+            }
+            action => panic!("Unexpected action {:?}", action),
+        }
+        assert_eq!(location.regions[&11].coordinates.len(), 2);
+    }
+
+    #[test]
+    fn location_remove_tile_from_region_errors() {
+        let mut location = create_valid_location();
+        let mut id_producer = IdProducer::default();
+        let c = Coord::new(0, 0);
+        location.remove_tile_from_region(c, &mut id_producer).unwrap();
+
+        assert_eq!(
+            location.remove_tile_from_region(c, &mut id_producer),
+            Err(LocationModificationError::NoRegionAtCoordinate(c))
+        );
+        assert_eq!(
+            location.remove_tile_from_region(Coord::new(5, 5), &mut id_producer),
+            Err(LocationModificationError::CoordinateOutOfLocation(Coord::new(5, 5)))
+        );
+    }
+
     #[test]
     fn location_coord_to_region_correct_split() {
         let mut location = create_valid_location();
@@ -1232,4 +1691,109 @@ mod test {
         });
         assert_eq!(distance, None);
     }
+
+    #[test]
+    fn shortest_path_counts_move_cost_of_surface() {
+        let map = test_map([Land, Land, Water, Mountain, Marsh, Land, Land]);
+        let location = Location::new(map, Vec::new()).unwrap();
+        let cost = |c| {
+            location
+                .tile_at(c)
+                .filter(|t| t.surface().is_land())
+                .map(|t| t.surface().move_cost())
+        };
+
+        assert_eq!(
+            location.shortest_path(Coord::new(-1, 0), Coord::new(1, -1), cost),
+            Some((2, vec![Coord::new(-1, 0), Coord::new(0, -1), Coord::new(1, -1)]))
+        );
+        assert_eq!(
+            location.shortest_path(Coord::new(-1, 0), Coord::new(0, 1), cost),
+            Some((3, vec![Coord::new(-1, 0), Coord::new(0, 0), Coord::new(0, 1)]))
+        );
+        assert_eq!(
+            location.shortest_path(Coord::new(0, 1), Coord::new(0, 1), cost),
+            Some((0, vec![Coord::new(0, 1)]))
+        );
+    }
+
+    #[test]
+    fn path_costs_returns_everything_reachable() {
+        let map = test_map([Land, Land, Water, Mountain, Marsh, Land, Land]);
+        let location = Location::new(map, Vec::new()).unwrap();
+        let costs = location.path_costs(Coord::new(-1, 0), |c| {
+            location
+                .tile_at(c)
+                .filter(|t| t.surface().is_land())
+                .map(|t| t.surface().move_cost())
+        });
+
+        assert_eq!(costs.len(), 6);
+        assert_eq!(costs[&Coord::new(-1, 0)], 0);
+        assert_eq!(costs[&Coord::new(0, 0)], 2);
+        assert_eq!(costs[&Coord::new(1, -1)], 2);
+        assert_eq!(costs[&Coord::new(1, 0)], 3);
+    }
+
+    #[test]
+    fn bfs_path_returns_shortest_path() {
+        let map = test_map([Land, Land, Water, Water, Land, Land, Water]);
+        let location = Location::new(map, Vec::new()).unwrap();
+        let is_land = |c| location.tile_at(c).unwrap().surface().is_land();
+
+        assert_eq!(
+            location.bfs_path(Coord::new(0, 1), Coord::new(1, -1), is_land),
+            Some(vec![Coord::new(0, 1), Coord::new(1, 0), Coord::new(1, -1)])
+        );
+        assert_eq!(
+            location.bfs_path(Coord::new(0, 1), Coord::new(0, 1), is_land),
+            Some(vec![Coord::new(0, 1)])
+        );
+        assert_eq!(
+            location.bfs_path(Coord::new(0, 1), Coord::new(-1, 0), is_land),
+            None
+        );
+        assert!(location.has_uniform_move_costs());
+
+        let map = test_map([Land, Land, Water, Mountain, Marsh, Land, Land]);
+        let location = Location::new(map, Vec::new()).unwrap();
+        assert!(!location.has_uniform_move_costs());
+    }
+
+    #[test]
+    fn shortest_path_returns_none_no_passage() {
+        let map = test_map([Land, Land, Water, Water, Land, Land, Water]);
+        let location = Location::new(map, Vec::new()).unwrap();
+        let cost = |c| {
+            location
+                .tile_at(c)
+                .filter(|t| t.surface().is_land())
+                .map(|_| 1)
+        };
+
+        assert_eq!(
+            location.shortest_path(Coord::new(-1, 0), Coord::new(0, 1), cost),
+            None
+        );
+        assert_eq!(
+            location.shortest_path(Coord::new(-1, 1), Coord::new(0, 1), cost),
+            None
+        );
+        assert_eq!(
+            location.bfs_distance(Coord::new(0, 1), Coord::new(1, -1), |c| cost(c).is_some()),
+            location
+                .shortest_path(Coord::new(0, 1), Coord::new(1, -1), cost)
+                .map(|(d, _)| d)
+        );
+    }
+
+    #[test]
+    fn surfaces_have_costs_defence_and_income() {
+        assert_eq!(Land.move_cost(), 1);
+        assert_eq!(Marsh.move_cost(), 1);
+        assert!(Mountain.move_cost() > Land.move_cost());
+        assert!(Mountain.defence() > Land.defence());
+        assert!(Marsh.income() < Land.income());
+        assert!(Mountain.is_land() && Marsh.is_land());
+    }
 }
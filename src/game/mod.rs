@@ -1,16 +1,29 @@
+mod clock;
 mod consts;
 mod engine;
 mod ids;
 mod location;
 mod rules;
+mod stats;
 pub mod test_util;
 mod unit;
+mod view;
 
-pub use self::engine::{EngineValidationError, GameEngine, PlayerAction, PlayerActionError};
+pub use self::clock::{ManualTimeSource, SystemTimeSource, TimeControl, TimeSource, TurnClock};
+pub use self::engine::{
+    EngineValidationError, GameEngine, GameEvent, MoveKind, PlayerAction, PlayerActionError,
+    PlayerInfo, ReachableTile, Truce, TruceProposal,
+};
 pub use self::ids::{IdProducer, ID};
 pub use self::location::{
-    Coord, Location, LocationModificationError, LocationValidationError, Player, Region, Tile,
-    TileSurface, Unit, UnitType,
+    Controller, Coord, Location, LocationModificationError, LocationValidationError, Player,
+    PlayerProfile, Region, Tile, TileSurface, Unit, UnitType, DEFAULT_PLAYER_COLORS,
+};
+pub use self::rules::{
+    validate_location, validate_location_for, validate_regions, LocationRulesValidationError,
+    RulesVariant,
 };
-pub use self::rules::{validate_location, validate_regions, LocationRulesValidationError};
-pub use self::unit::{UnitDescription, UnitInfo};
+pub(crate) use self::rules::{has_controlled_size, needs_capital};
+pub use self::stats::{GameStats, PlayerStats, RegionStats, TurnStats};
+pub use self::unit::{description, is_naval, is_tree, UnitDescription, UnitInfo};
+pub use self::view::PlayerView;
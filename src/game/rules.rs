@@ -1,12 +1,30 @@
 //! This module contains util functions and classes that help enforcing game rules
 use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
 
 use super::consts::*;
 use super::ids::ID;
-use super::location::{Coord, Location, LocationValidationError, Player, UnitType};
-use super::unit::description;
+use super::location::{Coord, Location, LocationValidationError, Player, Region, UnitType};
+use super::unit::{description, is_naval};
+
+/// Variant of game rules that decides what maps can be played
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum RulesVariant {
+    /// All land is one continent, and bridges and boats cannot be built
+    Continent,
+    /// Land can consist of several islands, which are reached with bridges and boats
+    Archipelago,
+}
+
+impl Default for RulesVariant {
+    fn default() -> Self {
+        RulesVariant::Continent
+    }
+}
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum LocationRulesValidationError {
     NoLand,
     InsufficientLand(u8),
@@ -25,27 +43,97 @@ impl From<LocationValidationError> for LocationRulesValidationError {
     }
 }
 
+impl fmt::Display for LocationRulesValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LocationRulesValidationError::NoLand => write!(f, "There is no land"),
+            LocationRulesValidationError::InsufficientLand(pct) => {
+                write!(f, "Land covers only {}% of location", pct)
+            }
+            LocationRulesValidationError::UnconnectedLand => {
+                write!(f, "Land consists of several islands")
+            }
+            LocationRulesValidationError::NotCoveredWithRegions(c) => {
+                write!(f, "Land at ({}, {}) is not a part of any region", c.x, c.y)
+            }
+            LocationRulesValidationError::InitiationError(e) => e.fmt(f),
+            LocationRulesValidationError::MisplacedUnit(c) => {
+                write!(f, "Unit at ({}, {}) cannot stand there", c.x, c.y)
+            }
+            LocationRulesValidationError::RegionContainsWater(id) => {
+                write!(f, "Region {} contains water", id)
+            }
+            LocationRulesValidationError::ActiveRegionWithoutCapital(id) => {
+                write!(f, "Region {} has no capital", id)
+            }
+            LocationRulesValidationError::MultiplyCapitals(id) => {
+                write!(f, "Region {} has several capitals", id)
+            }
+        }
+    }
+}
+
+impl Error for LocationRulesValidationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LocationRulesValidationError::InitiationError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Return true if region has enough land to be controlled. Water under bridges and boats does
+/// not count.
+pub(crate) fn has_controlled_size(location: &Location, region: &Region) -> bool {
+    let land = region
+        .coordinates()
+        .iter()
+        .filter(|&&c| location.tile_at(c).unwrap().surface().is_land())
+        .count();
+    land >= MIN_CONTROLLED_REGION_SIZE
+}
+
+/// Return true if region is controlled, so it needs a capital. Neutral regions are never
+/// controlled.
+pub(crate) fn needs_capital(location: &Location, region: &Region) -> bool {
+    !region.owner().is_neutral() && has_controlled_size(location, region)
+}
+
+/// Check that location is valid according to the `RulesVariant::Continent` rules, see
+/// `validate_location_for`.
+pub fn validate_location(location: &Location) -> Result<(), LocationRulesValidationError> {
+    validate_location_for(location, RulesVariant::Continent)
+}
+
 /// This method checks that location is generally valid and constructed according to game rules:
 ///
-/// - There should be one and only one piece of land, covering more than
-///   `MIN_LOCATION_LAND_COVERAGE_PCT` of location;
+/// - There should be one and only one piece of land (or several islands, if rules variant
+///   allows it), covering more than `MIN_LOCATION_LAND_COVERAGE_PCT` of location;
 /// - Land should be fully covered with nonintersecting regions;
-/// - All regions should cover only land, not water;
-/// - All units should be places on land, not on water;
-/// - Each region should have one village capital;
+/// - Regions should cover only land and water under bridges and boats;
+/// - Bridges and boats should be placed on water, other units should be placed on land;
+/// - Each region with enough land should have one village capital;
 ///
-pub fn validate_location(location: &Location) -> Result<(), LocationRulesValidationError> {
+pub fn validate_location_for(
+    location: &Location,
+    variant: RulesVariant,
+) -> Result<(), LocationRulesValidationError> {
     // Start with checking general location consistency
     Location::validate(location)?;
 
     // Check if there are coordinates that are land and not part of any region
-    // Also check if there are coordinates that are water and part of region.
+    // Also check if there are coordinates that are water and part of region, only bridges and
+    // boats can hold water.
     for (coordinate, tile) in location.map().iter() {
+        let holds_water = tile.unit().map(|u| is_naval(u.unit_type())) == Some(true);
         if tile.surface().is_land() && location.region_at(*coordinate).is_none() {
             return Err(LocationRulesValidationError::NotCoveredWithRegions(
                 *coordinate,
             ));
-        } else if tile.surface().is_water() && location.region_at(*coordinate).is_some() {
+        } else if tile.surface().is_water()
+            && !holds_water
+            && location.region_at(*coordinate).is_some()
+        {
             return Err(LocationRulesValidationError::RegionContainsWater(
                 location.region_at(*coordinate).unwrap().id(),
             ));
@@ -66,19 +154,25 @@ pub fn validate_location(location: &Location) -> Result<(), LocationRulesValidat
     }
 
     // Check if there are pieces of land that do not have ground connection
-    let land = location.bfs_set(first_land.unwrap(), |c| {
-        location.tile_at(c).map_or(false, |t| t.surface().is_land())
-    });
-
-    for (coordinate, tile) in location.map().iter() {
-        if tile.surface().is_land() && !land.contains(coordinate) {
-            return Err(LocationRulesValidationError::UnconnectedLand);
+    if variant == RulesVariant::Continent {
+        let land = location.bfs_set(first_land.unwrap(), |c| {
+            location.tile_at(c).map(|t| t.surface().is_land()) == Some(true)
+        });
+
+        for (coordinate, tile) in location.map().iter() {
+            if tile.surface().is_land() && !land.contains(coordinate) {
+                return Err(LocationRulesValidationError::UnconnectedLand);
+            }
         }
     }
 
     // Check if land size is higher than min_coverage
-    // We can rely on land containing all land cells
-    let real_coverage = (land.len() * 100 / location.map().len()) as u8;
+    let land_size = location
+        .map()
+        .values()
+        .filter(|t| t.surface().is_land())
+        .count();
+    let real_coverage = (land_size * 100 / location.map().len()) as u8;
     if MIN_LOCATION_LAND_COVERAGE_PCT > real_coverage {
         return Err(LocationRulesValidationError::InsufficientLand(
             real_coverage,
@@ -86,16 +180,24 @@ pub fn validate_location(location: &Location) -> Result<(), LocationRulesValidat
     }
 
     // Check if there are unit that are placed on inappropriate surface
-    // (Currently you can place unit only on land)
+    // (Bridges and boats can be placed only on water that belongs to a region, other units can be
+    // placed only on land)
     for (coordinate, tile) in location.map().iter() {
-        if tile.unit().is_some() && tile.surface().is_water() {
+        let is_misplaced = match tile.unit() {
+            Some(unit) if is_naval(unit.unit_type()) => {
+                tile.surface().is_land() || location.region_at(*coordinate).is_none()
+            }
+            Some(_) => tile.surface().is_water(),
+            None => false,
+        };
+        if is_misplaced {
             return Err(LocationRulesValidationError::MisplacedUnit(*coordinate));
         }
     }
 
     // Check if there are regions without capitals
     for (id, region) in location.regions() {
-        if region.coordinates().len() < MIN_CONTROLLED_REGION_SIZE {
+        if !needs_capital(location, region) {
             continue;
         }
         let mut capitals = 0;
@@ -121,13 +223,30 @@ pub fn validate_location(location: &Location) -> Result<(), LocationRulesValidat
     Ok(())
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum RegionsValidationError {
     NoActiveRegions(ID),
     UnlistedPlayer(ID),
 }
 
-/// Validate that each active player has at least one active region
+impl fmt::Display for RegionsValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegionsValidationError::NoActiveRegions(id) => {
+                write!(f, "Player {} has no active regions", id)
+            }
+            RegionsValidationError::UnlistedPlayer(id) => {
+                write!(f, "Player {} owns active regions but is not in the game", id)
+            }
+        }
+    }
+}
+
+impl Error for RegionsValidationError {}
+
+/// Validate that each active player has at least one active region. Region that is too small to
+/// be controlled is still active while there is a unit in it that can move.
 pub fn validate_regions(
     location: &Location,
     active_players: &[Player],
@@ -135,13 +254,17 @@ pub fn validate_regions(
     let mut player_is_active: HashMap<ID, bool> = HashMap::default();
 
     for region in location.regions().values() {
-        let mut is_active = region.coordinates().len() >= MIN_CONTROLLED_REGION_SIZE;
+        // Regions of resigned players belong to nobody, so they are never active
+        if region.owner().is_neutral() {
+            continue;
+        }
+        let mut is_active = has_controlled_size(location, region);
         if !is_active {
             let unit_count = region
                 .coordinates()
                 .iter()
                 .filter_map(|&c| location.tile_at(c).unwrap().unit())
-                .filter(|u| !description(u.unit_type()).is_unownable)
+                .filter(|u| description(u.unit_type()).max_moves > 0)
                 .count();
             is_active = unit_count > 0;
         }
@@ -177,7 +300,8 @@ mod test {
     use crate::game::location::{Coord, Location, Player, Region, Unit, UnitType};
 
     use super::{
-        validate_location, validate_regions, LocationRulesValidationError, RegionsValidationError,
+        validate_location, validate_location_for, validate_regions, LocationRulesValidationError,
+        RegionsValidationError, RulesVariant,
     };
     use crate::game::test_util::create_simple_map as test_map;
 
@@ -312,6 +436,81 @@ mod test {
         assert_eq!(res, Err(LocationRulesValidationError::UnconnectedLand));
     }
 
+    #[test]
+    fn validate_location_islands_are_allowed_in_archipelago() {
+        let map = test_map([Land, Water, Land, Water, Land, Water, Land]);
+
+        let mut coords_one = HashSet::default();
+        coords_one.insert(Coord::new(-1, 1));
+        coords_one.insert(Coord::new(0, 1));
+        let region_one = Region::new(11, Player::new(21), coords_one);
+
+        let mut coords_two = HashSet::default();
+        coords_two.insert(Coord::new(1, -1));
+        coords_two.insert(Coord::new(0, -1));
+        let region_two = Region::new(12, Player::new(22), coords_two);
+        let mut location = Location::new(map, vec![region_one, region_two]).unwrap();
+        location
+            .place_unit(Unit::new(31, UnitType::Village), Coord::new(0, 1))
+            .unwrap();
+        location
+            .place_unit(Unit::new(32, UnitType::Village), Coord::new(0, -1))
+            .unwrap();
+
+        assert_eq!(
+            validate_location_for(&location, RulesVariant::Archipelago),
+            Ok(())
+        );
+        assert_eq!(
+            validate_location(&location),
+            Err(LocationRulesValidationError::UnconnectedLand)
+        );
+    }
+
+    #[test]
+    fn validate_location_bridges_and_boats_hold_water() {
+        let map = test_map([Water, Water, Land, Land, Land, Water, Land]);
+
+        let mut coords_one = HashSet::default();
+        coords_one.insert(Coord::new(-1, 1));
+        coords_one.insert(Coord::new(0, 0));
+        coords_one.insert(Coord::new(0, 1));
+        let region_one = Region::new(11, Player::new(21), coords_one);
+
+        let mut coords_two = HashSet::default();
+        coords_two.insert(Coord::new(1, -1));
+        coords_two.insert(Coord::new(0, -1));
+        let region_two = Region::new(12, Player::new(22), coords_two);
+        let mut location = Location::new(map, vec![region_one, region_two]).unwrap();
+        location
+            .place_unit(Unit::new(31, UnitType::Bridge), Coord::new(0, 1))
+            .unwrap();
+        location
+            .place_unit(Unit::new(33, UnitType::Village), Coord::new(-1, 1))
+            .unwrap();
+        location
+            .place_unit(Unit::new(34, UnitType::Village), Coord::new(1, -1))
+            .unwrap();
+        assert_eq!(validate_location(&location), Ok(()));
+
+        location
+            .place_unit(Unit::new(35, UnitType::Boat), Coord::new(1, 0))
+            .unwrap();
+        assert_eq!(
+            validate_location(&location),
+            Err(LocationRulesValidationError::MisplacedUnit(Coord::new(1, 0)))
+        );
+        location.remove_unit(Coord::new(1, 0)).unwrap();
+
+        location
+            .place_unit(Unit::new(36, UnitType::Boat), Coord::new(0, 0))
+            .unwrap();
+        assert_eq!(
+            validate_location(&location),
+            Err(LocationRulesValidationError::MisplacedUnit(Coord::new(0, 0)))
+        );
+    }
+
     #[test]
     fn validate_location_not_covered_with_region() {
         let map = test_map([Land, Water, Land, Land, Land, Water, Land]);
@@ -478,6 +677,34 @@ mod test {
         );
     }
 
+    #[test]
+    fn validate_regions_error_small_region_with_tower() {
+        let map = test_map([Water, Water, Land, Land, Land, Water, Land]);
+
+        let mut coords_one = HashSet::default();
+        coords_one.insert(Coord::new(-1, 1));
+        coords_one.insert(Coord::new(0, 0));
+        let player_one = Player::new(21);
+        let region_one = Region::new(11, player_one, coords_one);
+
+        let mut coords_two = HashSet::default();
+        coords_two.insert(Coord::new(1, -1));
+        let player_two = Player::new(22);
+        let region_two = Region::new(12, player_two, coords_two);
+        let mut location = Location::new(map, vec![region_one, region_two]).unwrap();
+        location
+            .place_unit(Unit::new(31, UnitType::Tower), Coord::new(1, -1))
+            .unwrap();
+
+        let players = [player_one, player_two];
+        let res = validate_regions(&location, &players);
+
+        assert_eq!(
+            res,
+            Err(RegionsValidationError::NoActiveRegions(player_two.id()))
+        );
+    }
+
     #[test]
     fn validate_regions_error_unlisted_player() {
         let map = test_map([Water, Water, Land, Land, Land, Water, Land]);
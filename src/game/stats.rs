@@ -0,0 +1,126 @@
+//! Statistics of players that the engine records at the end of each turn
+use std::collections::BTreeMap;
+
+use super::ids::ID;
+use super::location::UnitType;
+
+/// Money of one region at the end of a turn
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct RegionStats {
+    pub region_id: ID,
+    pub money: i32,
+    pub income: i32,
+    pub maintenance: i32,
+}
+
+/// State of one player at the end of a turn, and what happened to them during it
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub player_id: ID,
+    pub tiles: usize,
+    /// Regions of the player sorted by id
+    pub regions: Vec<RegionStats>,
+    /// Sum of income of all regions of the player
+    pub income: i32,
+    /// Sum of maintenance costs of all regions of the player
+    pub maintenance: i32,
+    /// Units of the player by type, including the ones aboard boats
+    pub units: BTreeMap<UnitType, u32>,
+    /// Units that died from starvation at the end of the turn
+    pub starvation_deaths: u32,
+    /// Tiles taken from other players or from nobody during the turn
+    pub tiles_captured: u32,
+}
+
+/// Statistics of all players at the end of one turn
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TurnStats {
+    pub turn: u32,
+    /// Statistics of players in the order of turns
+    pub players: Vec<PlayerStats>,
+}
+
+/// Timeline of statistics of the game, one entry per finished turn
+#[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct GameStats {
+    turns: Vec<TurnStats>,
+    /// Tiles captured by players since the last recorded turn
+    #[serde(default)]
+    captured: BTreeMap<ID, u32>,
+    /// Units starved to death since the last recorded turn
+    #[serde(default)]
+    starved: BTreeMap<ID, u32>,
+}
+
+impl GameStats {
+    pub fn turns(&self) -> &Vec<TurnStats> {
+        &self.turns
+    }
+
+    /// Return statistics of the player over all recorded turns
+    pub fn player_timeline(&self, player_id: ID) -> Vec<&PlayerStats> {
+        self.turns
+            .iter()
+            .filter_map(|t| t.players.iter().find(|p| p.player_id == player_id))
+            .collect()
+    }
+
+    pub(crate) fn record_capture(&mut self, player_id: ID) {
+        *self.captured.entry(player_id).or_insert(0) += 1;
+    }
+
+    pub(crate) fn record_starvation(&mut self, player_id: ID) {
+        *self.starved.entry(player_id).or_insert(0) += 1;
+    }
+
+    /// Add statistics of the finished turn, counters of captures and starvation deaths are
+    /// filled in and start over
+    pub(crate) fn record_turn(&mut self, mut turn: TurnStats) {
+        for player in turn.players.iter_mut() {
+            player.tiles_captured = self.captured.remove(&player.player_id).unwrap_or(0);
+            player.starvation_deaths = self.starved.remove(&player.player_id).unwrap_or(0);
+        }
+        self.captured.clear();
+        self.starved.clear();
+        self.turns.push(turn);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GameStats, PlayerStats, TurnStats};
+
+    fn turn(turn: u32) -> TurnStats {
+        let players = [1, 2]
+            .iter()
+            .map(|&player_id| PlayerStats {
+                player_id,
+                ..PlayerStats::default()
+            }).collect();
+        TurnStats { turn, players }
+    }
+
+    #[test]
+    fn counters_start_over_with_each_turn() {
+        let mut stats = GameStats::default();
+        stats.record_capture(1);
+        stats.record_capture(1);
+        stats.record_starvation(2);
+        stats.record_turn(turn(1));
+        stats.record_capture(2);
+        stats.record_turn(turn(2));
+
+        let captured: Vec<_> = stats
+            .player_timeline(1)
+            .iter()
+            .map(|p| (p.tiles_captured, p.starvation_deaths))
+            .collect();
+        assert_eq!(captured, vec![(2, 0), (0, 0)]);
+        let captured: Vec<_> = stats
+            .player_timeline(2)
+            .iter()
+            .map(|p| (p.tiles_captured, p.starvation_deaths))
+            .collect();
+        assert_eq!(captured, vec![(0, 1), (1, 0)]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::cmp::{max, min};
+
 use super::consts::*;
 use super::ids::ID;
 use super::location::{Tile, Unit, UnitType};
@@ -12,8 +14,12 @@ pub struct UnitDescription {
     pub max_moves: u32,
     pub defence: u8,
     pub attack: u8,
+    /// Distance at which unit shoots units of other players without moving, 0 if it cannot shoot
+    pub attack_range: u32,
     pub upgrade_levels: u8,
     pub upgrades_to: Option<&'static UnitDescription>,
+    /// Unit removes trees of its region without ending its move
+    pub clears_trees: bool,
 }
 
 fn default_description() -> &'static UnitDescription {
@@ -26,6 +32,12 @@ pub struct UnitInfo {
     #[serde(default = "default_description")]
     description: &'static UnitDescription,
     moves_left: u32,
+    /// Number of units of other players this unit has killed
+    #[serde(default)]
+    kills: u32,
+    /// Number of turns this unit has spent in the field
+    #[serde(default)]
+    turns_survived: u32,
 }
 
 impl UnitInfo {
@@ -36,6 +48,8 @@ impl UnitInfo {
         Self {
             description,
             moves_left,
+            kills: 0,
+            turns_survived: 0,
         }
     }
 
@@ -58,6 +72,54 @@ impl UnitInfo {
         self.description
     }
 
+    pub fn kills(&self) -> u32 {
+        self.kills
+    }
+
+    pub fn turns_survived(&self) -> u32 {
+        self.turns_survived
+    }
+
+    /// Return true if unit has killed `VETERAN_KILLS` units or has spent `VETERAN_TURNS` turns in
+    /// the field
+    pub fn is_veteran(&self) -> bool {
+        self.kills >= VETERAN_KILLS || self.turns_survived >= VETERAN_TURNS
+    }
+
+    /// Return defence of the unit, veterans defend better than other units of their type
+    ///
+    /// # Examples:
+    ///
+    /// ```rust
+    /// use yasc::game::{UnitType, UnitInfo};
+    ///
+    /// let (_, mut unit) = UnitInfo::new(1, UnitType::Soldier);
+    /// assert_eq!(unit.defence(), 2);
+    /// for _ in 0..3 {
+    ///     unit.add_kill();
+    /// }
+    /// assert_eq!(unit.defence(), 3);
+    /// ```
+    ///
+    pub fn defence(&self) -> u8 {
+        if self.is_veteran() {
+            self.description.defence + VETERAN_DEFENCE_BONUS
+        } else {
+            self.description.defence
+        }
+    }
+
+    pub fn add_kill(&mut self) {
+        self.kills += 1;
+    }
+
+    /// Count one more turn for a unit in the field. Buildings and trees do not gain experience.
+    pub fn survive_turn(&mut self) {
+        if self.description.max_moves > 0 {
+            self.turns_survived += 1;
+        }
+    }
+
     /// Subtract moves from this unit
     ///
     /// # Examples:
@@ -111,14 +173,30 @@ impl UnitInfo {
     }
 }
 
-/// Return true if this unit can defeat unit provided as argument
-pub fn can_defeat(attacker: UnitType, defender: UnitType) -> bool {
-    description(attacker).attack > description(defender).defence
+/// Return true if this unit can defeat unit provided as argument. Nobody defends better than
+/// `MAX_TILE_DEFENCE`.
+pub fn can_defeat(attacker: UnitType, defender: &UnitInfo) -> bool {
+    description(attacker).attack > min(defender.defence(), MAX_TILE_DEFENCE)
+}
+
+/// Return true if unit stands on water. Such units never step on land.
+pub fn is_naval(unit_type: UnitType) -> bool {
+    unit_type == UnitType::Bridge || unit_type == UnitType::Boat
 }
 
-/// Return true if unit can step on the tile
-pub fn can_step_on(_unit_type: UnitType, tile: &Tile) -> bool {
-    tile.surface().is_land()
+/// Return true if unit is a tree that grows by itself
+pub fn is_tree(unit_type: UnitType) -> bool {
+    unit_type == UnitType::PineTree || unit_type == UnitType::PalmTree
+}
+
+/// Return true if unit can step on the tile. Land units step on land and cross water over bridges
+/// and boats, naval units step on water that has no other unit on it.
+pub fn can_step_on(unit_type: UnitType, tile: &Tile) -> bool {
+    if is_naval(unit_type) {
+        tile.surface().is_water() && tile.unit().is_none()
+    } else {
+        tile.surface().is_land() || tile.unit().map(|u| is_naval(u.unit_type())) == Some(true)
+    }
 }
 
 /// Return a possible result of merging actor into goal (or replacing goal with actor)
@@ -126,24 +204,44 @@ pub fn can_step_on(_unit_type: UnitType, tile: &Tile) -> bool {
 pub fn merge_result(actor: UnitType, goal: UnitType) -> Option<UnitType> {
     let goal_description = description(goal);
 
-    if goal_description.is_unownable {
-        Some(actor)
-    } else if goal_description.upgrades_to.is_some() {
-        let actor_description = description(actor);
-        if actor_description.upgrade_levels == 0 {
-            None
-        } else {
-            let mut result = Some(goal_description);
-            for _ in 0..actor_description.upgrade_levels {
-                result = result.and_then(|r| r.upgrades_to)
+    match (actor, goal) {
+        _ if goal_description.is_unownable => Some(actor),
+        // Engineers and archers merge like militia and soldiers, merged unit loses their skills
+        (UnitType::Engineer, _) => merge_result(UnitType::Militia, goal),
+        (UnitType::Archer, _) => merge_result(UnitType::Soldier, goal),
+        (_, UnitType::Engineer) => merge_result(actor, UnitType::Militia),
+        (_, UnitType::Archer) => merge_result(actor, UnitType::Soldier),
+        // Soldier garrisons a tower and makes it a castle, which costs as much as both of them
+        (UnitType::Soldier, UnitType::Tower) => Some(UnitType::Castle),
+        (_, UnitType::Tower) => None,
+        _ if goal_description.upgrades_to.is_some() => {
+            let actor_description = description(actor);
+            if actor_description.upgrade_levels == 0 {
+                None
+            } else {
+                let mut result = Some(goal_description);
+                for _ in 0..actor_description.upgrade_levels {
+                    result = result.and_then(|r| r.upgrades_to)
+                }
+                result.map(|d| d.name)
             }
-            result.map(|d| d.name)
         }
-    } else {
-        None
+        _ => None,
     }
 }
 
+/// Return an info of unit that is a result of merging actor into goal, see `merge_result`. Merged
+/// unit keeps kills of both units and the longest service of them, and has no moves.
+pub fn merge_result_info(actor: &UnitInfo, goal: &UnitInfo) -> Option<UnitInfo> {
+    let unit_type = merge_result(actor.description.name, goal.description.name)?;
+    Some(UnitInfo {
+        description: description(unit_type),
+        moves_left: 0,
+        kills: actor.kills + goal.kills,
+        turns_survived: max(actor.turns_survived, goal.turns_survived),
+    })
+}
+
 /// Return a description of unit identified by enum entry
 pub fn description(unit_type: UnitType) -> &'static UnitDescription {
     match unit_type {
@@ -156,13 +254,25 @@ pub fn description(unit_type: UnitType) -> &'static UnitDescription {
         UnitType::Knight => &KNIGHT,
         UnitType::Soldier => &SOLDIER,
         UnitType::Militia => &MILITIA,
+        UnitType::Bridge => &BRIDGE,
+        UnitType::Boat => &BOAT,
+        UnitType::Castle => &CASTLE,
+        UnitType::Archer => &ARCHER,
+        UnitType::Engineer => &ENGINEER,
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::super::consts::*;
-    use super::{can_defeat, description, merge_result, UnitInfo, UnitType};
+    use super::{
+        can_defeat, can_step_on, description, merge_result, merge_result_info, UnitInfo, UnitType,
+    };
+    use crate::game::location::{Tile, TileSurface, Unit};
+
+    fn info(unit_type: UnitType) -> UnitInfo {
+        UnitInfo::new(1, unit_type).1
+    }
 
     #[test]
     fn check_description() {
@@ -189,11 +299,97 @@ mod test {
         assert_eq!(merge_result(UnitType::Grave, UnitType::Militia), None);
     }
 
+    #[test]
+    fn merge_result_of_extended_roster() {
+        let merges = [
+            (UnitType::Soldier, UnitType::Tower, Some(UnitType::Castle)),
+            (UnitType::Archer, UnitType::Tower, Some(UnitType::Castle)),
+            (UnitType::Militia, UnitType::Tower, None),
+            (UnitType::Knight, UnitType::Castle, None),
+            (UnitType::Archer, UnitType::Archer, Some(UnitType::GreatKnight)),
+            (UnitType::Archer, UnitType::Militia, Some(UnitType::Knight)),
+            (UnitType::Militia, UnitType::Archer, Some(UnitType::Knight)),
+            (UnitType::Engineer, UnitType::Soldier, Some(UnitType::Knight)),
+            (UnitType::Militia, UnitType::Engineer, Some(UnitType::Soldier)),
+            (UnitType::Engineer, UnitType::Engineer, Some(UnitType::Soldier)),
+            (UnitType::Archer, UnitType::Engineer, Some(UnitType::Knight)),
+            (UnitType::Engineer, UnitType::Knight, Some(UnitType::GreatKnight)),
+            (UnitType::Knight, UnitType::Archer, None),
+        ];
+        for &(actor, goal, result) in merges.iter() {
+            assert_eq!(merge_result(actor, goal), result, "{:?} into {:?}", actor, goal);
+        }
+        assert_eq!(
+            merge_result(UnitType::Engineer, UnitType::PineTree),
+            Some(UnitType::Engineer)
+        );
+        assert_eq!(
+            description(UnitType::Tower).upgrades_to.map(|d| d.name),
+            Some(UnitType::Castle)
+        );
+    }
+
     #[test]
     fn check_can_defeat() {
-        assert_eq!(can_defeat(UnitType::Soldier, UnitType::Knight), false);
-        assert_eq!(can_defeat(UnitType::Knight, UnitType::Soldier), true);
-        assert_eq!(can_defeat(UnitType::Soldier, UnitType::Soldier), false);
+        assert_eq!(can_defeat(UnitType::Soldier, &info(UnitType::Knight)), false);
+        assert_eq!(can_defeat(UnitType::Knight, &info(UnitType::Soldier)), true);
+        assert_eq!(can_defeat(UnitType::Soldier, &info(UnitType::Soldier)), false);
+    }
+
+    #[test]
+    fn veteran_defends_better() {
+        let mut veteran = info(UnitType::Soldier);
+        for _ in 0..VETERAN_TURNS {
+            assert!(can_defeat(UnitType::Knight, &veteran));
+            veteran.survive_turn();
+        }
+
+        assert!(veteran.is_veteran());
+        assert_eq!(veteran.defence(), SOLDIER.defence + VETERAN_DEFENCE_BONUS);
+        assert!(!can_defeat(UnitType::Knight, &veteran));
+
+        let mut great_veteran = info(UnitType::GreatKnight);
+        for _ in 0..VETERAN_TURNS {
+            great_veteran.survive_turn();
+        }
+        assert!(can_defeat(UnitType::GreatKnight, &great_veteran));
+
+        let mut tower = info(UnitType::Tower);
+        tower.survive_turn();
+        assert_eq!(tower.turns_survived(), 0);
+    }
+
+    #[test]
+    fn merged_unit_keeps_experience() {
+        let mut actor = info(UnitType::Militia);
+        actor.add_kill();
+        actor.survive_turn();
+        let mut goal = info(UnitType::Militia);
+        goal.add_kill();
+        goal.survive_turn();
+        goal.survive_turn();
+
+        let merged = merge_result_info(&actor, &goal).unwrap();
+        assert_eq!(merged.description().name, UnitType::Soldier);
+        assert_eq!(merged.kills(), 2);
+        assert_eq!(merged.turns_survived(), 2);
+        assert_eq!(merged.moves_left(), 0);
+        assert_eq!(merge_result_info(&actor, &info(UnitType::Tower)), None);
+    }
+
+    #[test]
+    fn check_can_step_on() {
+        let land = Tile::new(1, TileSurface::Land);
+        let water = Tile::new(2, TileSurface::Water);
+        let mut bridge = Tile::new(3, TileSurface::Water);
+        bridge.place_unit(Unit::new(4, UnitType::Bridge));
+
+        assert!(can_step_on(UnitType::Soldier, &land));
+        assert!(!can_step_on(UnitType::Soldier, &water));
+        assert!(can_step_on(UnitType::Soldier, &bridge));
+        assert!(!can_step_on(UnitType::Boat, &land));
+        assert!(can_step_on(UnitType::Boat, &water));
+        assert!(!can_step_on(UnitType::Boat, &bridge));
     }
 
     #[test]
@@ -238,17 +434,17 @@ mod test {
 
     #[test]
     fn can_defeat_when_unit_stronger() {
-        assert!(can_defeat(UnitType::Soldier, UnitType::Militia));
+        assert!(can_defeat(UnitType::Soldier, &info(UnitType::Militia)));
     }
 
     #[test]
     fn can_defeat_when_unit_weaker() {
-        assert!(!can_defeat(UnitType::Soldier, UnitType::GreatKnight));
+        assert!(!can_defeat(UnitType::Soldier, &info(UnitType::GreatKnight)));
     }
 
     #[test]
     fn can_defeat_when_unit_equal() {
-        assert!(!can_defeat(UnitType::Soldier, UnitType::Soldier));
+        assert!(!can_defeat(UnitType::Soldier, &info(UnitType::Soldier)));
     }
 
     #[test]
@@ -0,0 +1,296 @@
+//! Part of the game that one player is allowed to see
+use std::collections::{HashMap, HashSet};
+
+use super::engine::{GameEngine, PlayerInfo, Truce, TruceProposal};
+use super::ids::ID;
+use super::location::{Coord, Location, Player, Region, Unit};
+
+/// State of the game as one player sees it.
+///
+/// The location contains only tiles that are not farther than the sight radius of the engine from
+/// player's regions, so regions of other players can be cut by the edge of the view. Money and
+/// profit are known only for player's own regions, and moves are known only for player's own
+/// units. Units aboard boats are seen along with the boats. All truces are known to everybody, but
+/// only proposals made by the player or to them are visible.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PlayerView {
+    player_id: ID,
+    players: Vec<Player>,
+    winner: Option<ID>,
+    current_turn: u32,
+    active_player_num: usize,
+    alliances: Vec<Vec<ID>>,
+    truces: Vec<Truce>,
+    truce_proposals: Vec<TruceProposal>,
+
+    location: Location,
+    /// Units aboard visible boats by ids of the boats
+    #[serde(default)]
+    cargo: HashMap<ID, Unit>,
+    region_money: HashMap<ID, i32>,
+    region_profit: HashMap<ID, i32>,
+    unit_moves: HashMap<ID, u32>,
+}
+
+impl PlayerView {
+    pub(crate) fn new(engine: &GameEngine, player_id: ID) -> Self {
+        let full = engine.location();
+        let own_regions: Vec<&Region> = full
+            .regions()
+            .values()
+            .filter(|r| r.owner().id() == player_id)
+            .collect();
+
+        let radius = engine.sight_radius() as i32;
+        let mut visible: HashSet<Coord> = HashSet::new();
+        for &coordinate in own_regions.iter().flat_map(|r| r.coordinates().iter()) {
+            coordinate.for_each_in_range(radius, |c| {
+                if full.tile_at(c).is_some() {
+                    visible.insert(c);
+                }
+            });
+        }
+
+        let map = visible
+            .iter()
+            .map(|&c| (c, *full.tile_at(c).unwrap()))
+            .collect();
+        let cargo = visible
+            .iter()
+            .filter_map(|&c| {
+                let boat = full.tile_at(c).unwrap().unit()?;
+                engine.cargo_at(c).map(|&u| (boat.id(), u))
+            }).collect();
+        let regions = full
+            .regions()
+            .values()
+            .filter_map(|r| {
+                let coordinates: HashSet<Coord> = r
+                    .coordinates()
+                    .iter()
+                    .filter(|c| visible.contains(c))
+                    .cloned()
+                    .collect();
+                if coordinates.is_empty() {
+                    None
+                } else {
+                    Some(Region::new(r.id(), *r.owner(), coordinates))
+                }
+            }).collect();
+
+        let region_money = own_regions
+            .iter()
+            .filter_map(|r| engine.region_money(r.id()).map(|m| (r.id(), m)))
+            .collect();
+        let region_profit = own_regions
+            .iter()
+            .filter_map(|r| engine.region_profit(r.id()).map(|p| (r.id(), p)))
+            .collect();
+        let unit_moves = own_regions
+            .iter()
+            .flat_map(|r| r.coordinates().iter())
+            .flat_map(|&c| full.tile_at(c).unwrap().unit().into_iter().chain(engine.cargo_at(c)))
+            .filter_map(|u| engine.unit_moves_left(u.id()).map(|m| (u.id(), m)))
+            .collect();
+
+        PlayerView {
+            player_id,
+            players: engine.players().iter().map(PlayerInfo::player).collect(),
+            winner: engine.winner(),
+            current_turn: engine.current_turn(),
+            active_player_num: engine.active_player_num(),
+            alliances: engine.alliances().clone(),
+            truces: engine.truces().clone(),
+            truce_proposals: engine
+                .truce_proposals()
+                .iter()
+                .filter(|p| p.from == player_id || p.to == player_id)
+                .cloned()
+                .collect(),
+            location: Location::new_unvalidated(map, regions),
+            cargo,
+            region_money,
+            region_profit,
+            unit_moves,
+        }
+    }
+
+    /// Return id of the player who sees the game
+    pub fn player_id(&self) -> ID {
+        self.player_id
+    }
+
+    pub fn players(&self) -> &Vec<Player> {
+        &self.players
+    }
+
+    /// Return visible part of the location
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    pub fn is_visible(&self, coordinate: Coord) -> bool {
+        self.location.tile_at(coordinate).is_some()
+    }
+
+    /// Return a unit that is aboard a boat on provided coordinate, if the boat is visible
+    pub fn cargo_at(&self, coordinate: Coord) -> Option<&Unit> {
+        let boat = self.location.tile_at(coordinate)?.unit()?;
+        self.cargo.get(&boat.id())
+    }
+
+    pub fn current_turn(&self) -> u32 {
+        self.current_turn
+    }
+
+    pub fn winner(&self) -> Option<ID> {
+        self.winner
+    }
+
+    pub fn active_player_num(&self) -> usize {
+        self.active_player_num
+    }
+
+    pub fn active_player(&self) -> &Player {
+        &self.players[self.active_player_num]
+    }
+
+    /// Return true if players are the same player or belong to the same alliance
+    pub fn are_allies(&self, first: ID, second: ID) -> bool {
+        first == second
+            || self
+                .alliances
+                .iter()
+                .any(|a| a.contains(&first) && a.contains(&second))
+    }
+
+    pub fn truces(&self) -> &Vec<Truce> {
+        &self.truces
+    }
+
+    /// Return truces proposed by the player or to them that are not accepted yet
+    pub fn truce_proposals(&self) -> &Vec<TruceProposal> {
+        &self.truce_proposals
+    }
+
+    pub fn have_truce(&self, first: ID, second: ID) -> bool {
+        self.truces.iter().any(|t| t.binds(first, second))
+    }
+
+    /// Return money of a region. Money of regions of other players is hidden, so `None` is
+    /// returned for them.
+    pub fn region_money(&self, region_id: ID) -> Option<i32> {
+        self.region_money.get(&region_id).cloned()
+    }
+
+    /// Return profit of a region, see `GameEngine::region_profit`. Profit of regions of other
+    /// players is hidden, so `None` is returned for them.
+    pub fn region_profit(&self, region_id: ID) -> Option<i32> {
+        self.region_profit.get(&region_id).cloned()
+    }
+
+    /// Return number of moves left for a unit. Moves of units of other players are hidden, so
+    /// `None` is returned for them.
+    pub fn unit_moves_left(&self, unit_id: ID) -> Option<u32> {
+        self.unit_moves.get(&unit_id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::consts::*;
+    use crate::game::location::{Coord, UnitType};
+    use crate::game::rules::RulesVariant;
+    use crate::game::test_util::create_valid_engine;
+    use crate::game::PlayerAction;
+    use crate::init::ascii::parse;
+
+    #[test]
+    fn check_view_contains_only_tiles_in_sight() {
+        let (pl, ri, mut engine) = create_valid_engine();
+        engine.set_sight_radius(1);
+
+        let view = engine.view_for(pl[2].id());
+
+        let mut visible: Vec<Coord> = view.location().map().keys().cloned().collect();
+        visible.sort();
+        let mut expected = vec![
+            Coord::new(-2, 1),
+            Coord::new(-1, 0),
+            Coord::new(-1, 1),
+            Coord::new(0, -1),
+            Coord::new(0, 0),
+        ];
+        expected.sort();
+        assert_eq!(visible, expected);
+        assert!(!view.is_visible(Coord::new(1, 0)));
+
+        // Regions of other players are cut by the edge of the view
+        let region_one = &view.location().regions()[&ri[0]];
+        assert_eq!(region_one.coordinates().len(), 1);
+        assert!(view.location().regions().get(&ri[1]).is_none());
+        assert_eq!(
+            view.location().region_at(Coord::new(-1, 1)).unwrap().id(),
+            ri[2]
+        );
+    }
+
+    #[test]
+    fn check_view_hides_balances_of_other_players() {
+        let (pl, ri, engine) = create_valid_engine();
+
+        let view = engine.view_for(pl[0].id());
+
+        assert_eq!(view.player_id(), pl[0].id());
+        assert_eq!(view.region_money(ri[0]), engine.region_money(ri[0]));
+        assert_eq!(view.region_profit(ri[0]), engine.region_profit(ri[0]));
+        assert_eq!(view.region_money(ri[1]), None);
+        assert_eq!(view.region_profit(ri[3]), None);
+
+        let soldier = view.location().tile_at(Coord::new(1, 0)).unwrap().unit().unwrap();
+        let militia = view.location().tile_at(Coord::new(0, 1)).unwrap().unit().unwrap();
+        assert_eq!(view.unit_moves_left(soldier.id()), Some(STANDARD_MOVES_NUM));
+        assert_eq!(view.unit_moves_left(militia.id()), None);
+    }
+
+    #[test]
+    fn check_view_follows_the_game() {
+        let (pl, _, mut engine) = create_valid_engine();
+        engine.set_sight_radius(0);
+
+        let view = engine.view_for(pl[1].id());
+
+        assert_eq!(view.location().map().len(), 3);
+        assert_eq!(view.current_turn(), engine.current_turn());
+        assert_eq!(view.active_player(), engine.active_player());
+        assert_eq!(view.winner(), None);
+        assert!(!view.are_allies(pl[0].id(), pl[1].id()));
+        assert_eq!(engine.view_for(pl[1].id()), view);
+    }
+
+    #[test]
+    fn check_view_contains_units_aboard_boats() {
+        let text = "players 2\norigin 0 0\n1V  1S  1O  ~~  2.  2V\n  1.  1.  ~~  ~~  2.  2.\n";
+        let mut builder = parse(text).unwrap();
+        builder.set_rules_variant(RulesVariant::Archipelago);
+        let mut engine = builder.build().unwrap();
+        let pl: Vec<_> = engine.players().iter().map(|p| p.id()).collect();
+        let (shore, sea) = (Coord::new(1, 0), Coord::new(2, 0));
+        engine
+            .act(pl[0], PlayerAction::MoveUnit { src: shore, dst: sea })
+            .unwrap();
+        let soldier = *engine.cargo_at(sea).unwrap();
+        assert_eq!(soldier.unit_type(), UnitType::Soldier);
+
+        let own_view = engine.view_for(pl[0]);
+        assert_eq!(own_view.cargo_at(sea), Some(&soldier));
+        assert_eq!(
+            own_view.unit_moves_left(soldier.id()),
+            engine.unit_moves_left(soldier.id())
+        );
+
+        let other_view = engine.view_for(pl[1]);
+        assert_eq!(other_view.cargo_at(sea), Some(&soldier));
+        assert_eq!(other_view.unit_moves_left(soldier.id()), None);
+    }
+}
@@ -0,0 +1,471 @@
+//! Hints for new players: explanations of why an action was refused and suggestions of what to
+//! do next, in words.
+use crate::bots::GreedyBot;
+use crate::game::{
+    description, is_naval, Coord, GameEngine, Player, PlayerAction, PlayerActionError,
+    TileSurface, UnitType, ID,
+};
+
+/// Action that is worth making next, with a reason for it
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Suggestion {
+    pub action: PlayerAction,
+    pub reason: String,
+}
+
+fn at(coordinate: Coord) -> String {
+    format!("({}, {})", coordinate.x, coordinate.y)
+}
+
+/// Return a name of the player with their number in the order of turns
+fn player_name(engine: &GameEngine, player_id: ID) -> String {
+    if Player::new(player_id).is_neutral() {
+        return String::from("nobody");
+    }
+    match engine.players().iter().position(|p| p.id() == player_id) {
+        Some(i) => format!("player {}", i + 1),
+        None => format!("player #{}", player_id),
+    }
+}
+
+fn unit_type_at(engine: &GameEngine, coordinate: Coord) -> Option<UnitType> {
+    let tile = engine.location().tile_at(coordinate)?;
+    let unit = tile.unit()?;
+    Some(engine.cargo_at(coordinate).unwrap_or(unit).unit_type())
+}
+
+/// Return the type of the unit that makes the action
+fn actor(engine: &GameEngine, action: &PlayerAction) -> Option<UnitType> {
+    match *action {
+        PlayerAction::PlaceNewUnit(_, unit_type, _) => Some(unit_type),
+        PlayerAction::MoveUnit { src, .. } | PlayerAction::Shoot { src, .. } => {
+            unit_type_at(engine, src)
+        }
+        PlayerAction::UpgradeUnit(c) => unit_type_at(engine, c),
+        _ => None,
+    }
+}
+
+/// Explain in words why the action of the player was refused by `GameEngine::act` with provided
+/// error
+pub fn explain(
+    engine: &GameEngine,
+    player_id: ID,
+    action: &PlayerAction,
+    error: &PlayerActionError,
+) -> String {
+    let unit_type = actor(engine, action);
+    match *error {
+        PlayerActionError::OtherPlayersTurn(_) => format!(
+            "It is the turn of {} now, wait for your turn",
+            player_name(engine, engine.active_player().id())
+        ),
+        PlayerActionError::InaccessibleLocation(dst) => match (*action, unit_type) {
+            (PlayerAction::Shoot { src, .. }, Some(unit_type)) => format!(
+                "{} is {} tiles away, but {:?} shoots only {} tiles far",
+                at(dst),
+                src.distance(dst),
+                unit_type,
+                description(unit_type).attack_range
+            ),
+            (_, Some(unit_type)) => explain_inaccessible(engine, player_id, action, unit_type, dst),
+            _ => format!("There is no tile at {}", at(dst)),
+        },
+        PlayerActionError::AlreadyOccupied(dst) => {
+            match (unit_type_at(engine, dst), unit_type) {
+                (Some(occupant), Some(unit_type)) if is_naval(occupant) => format!(
+                    "{:?} at {} is already taken, new {:?} cannot be bought aboard it",
+                    occupant,
+                    at(dst),
+                    unit_type
+                ),
+                (Some(occupant), Some(unit_type)) => format!(
+                    "{:?} at {} cannot be joined with {:?}, only units of the same kind merge \
+                     into stronger ones",
+                    occupant,
+                    at(dst),
+                    unit_type
+                ),
+                _ => format!("Tile {} is already occupied", at(dst)),
+            }
+        }
+        PlayerActionError::CannotAttack(dst) => explain_defence(engine, player_id, action, dst),
+        PlayerActionError::NotEnoughMoney(region_id) => {
+            let money = engine.region_money(region_id).unwrap_or(0);
+            let profit = engine.region_profit(region_id).unwrap_or(0);
+            let cost = match *action {
+                PlayerAction::PlaceNewUnit(_, unit_type, _) => {
+                    format!("{:?} costs {}", unit_type, description(unit_type).purchase_cost)
+                }
+                PlayerAction::UpgradeUnit(_) => {
+                    let old = unit_type.map(description);
+                    match old.and_then(|d| d.upgrades_to) {
+                        Some(new) => format!(
+                            "upgrade to {:?} costs {}",
+                            new.name,
+                            new.purchase_cost - old.unwrap().purchase_cost
+                        ),
+                        None => String::from("it costs more"),
+                    }
+                }
+                _ => String::from("it costs more"),
+            };
+            format!(
+                "Region #{} has {} money, but {}. The region earns {} per turn",
+                region_id, money, cost, profit
+            )
+        }
+        PlayerActionError::NotEnoughMoves(left, needed) => match (*action, unit_type) {
+            (PlayerAction::Shoot { .. }, Some(unit_type)) => format!(
+                "{:?} has already used its moves this turn, it can shoot again next turn",
+                unit_type
+            ),
+            (PlayerAction::MoveUnit { dst, .. }, Some(unit_type)) => format!(
+                "{:?} has {} moves left, but the way to {} takes {}. Moves are refilled at \
+                 the beginning of your next turn",
+                unit_type,
+                left,
+                at(dst),
+                needed
+            ),
+            _ => format!("Unit has {} moves left, but needs {}", left, needed),
+        },
+        PlayerActionError::NotOwned(c) => match engine.location().region_at(c) {
+            Some(region) => format!(
+                "Tile {} belongs to {}, you can only use units and money of your own regions",
+                at(c),
+                player_name(engine, region.owner().id())
+            ),
+            None => format!("Tile {} does not belong to anyone", at(c)),
+        },
+        PlayerActionError::CannotBePlacedByPlayer(unit_type) => {
+            if is_naval(unit_type) && description(unit_type).is_purchasable {
+                format!(
+                    "{:?} can only be built when the game is played on an archipelago",
+                    unit_type
+                )
+            } else {
+                format!("{:?} cannot be bought, it appears on the map by itself", unit_type)
+            }
+        }
+        PlayerActionError::NoUnit(c) => match (*action, unit_type_at(engine, c)) {
+            (PlayerAction::Shoot { .. }, Some(target)) => format!(
+                "{:?} at {} cannot be shot, only units in the field on land can",
+                target,
+                at(c)
+            ),
+            (PlayerAction::Shoot { .. }, None) => format!("There is nobody to shoot at {}", at(c)),
+            _ => format!("There is no unit at {}, select a tile with a unit", at(c)),
+        },
+        PlayerActionError::NoUpgrade(unit_type) => format!(
+            "{:?} cannot be upgraded, merge units to make stronger ones instead",
+            unit_type
+        ),
+        PlayerActionError::CannotShoot(unit_type) => format!(
+            "{:?} cannot shoot, only units with an attack range like archers can",
+            unit_type
+        ),
+        PlayerActionError::GameAlreadyFinished => String::from("The game is over"),
+        PlayerActionError::CannotNegotiateWith(id) => format!(
+            "You cannot negotiate with {}: it is yourself, your ally or a defeated player",
+            player_name(engine, id)
+        ),
+        PlayerActionError::AlreadyInTruce(id) => {
+            format!("You already have a truce with {}", player_name(engine, id))
+        }
+        PlayerActionError::InvalidTruceDuration(turns) => {
+            format!("Truce cannot last {} turns, it should last at least one", turns)
+        }
+        PlayerActionError::NoTruceProposal(id) => {
+            format!("{} has not proposed you a truce", player_name(engine, id))
+        }
+        PlayerActionError::NoTruce(id) => {
+            format!("You have no truce with {}", player_name(engine, id))
+        }
+        PlayerActionError::LocationError(ref e) => format!("The map cannot be changed so: {}", e),
+    }
+}
+
+/// Explain why a unit cannot be moved or placed on the tile
+fn explain_inaccessible(
+    engine: &GameEngine,
+    player_id: ID,
+    action: &PlayerAction,
+    unit_type: UnitType,
+    dst: Coord,
+) -> String {
+    let location = engine.location();
+    let tile = match location.tile_at(dst) {
+        Some(tile) => tile,
+        None => return format!("There is no tile at {}", at(dst)),
+    };
+    let region = match location.region_at(dst) {
+        Some(region) => region,
+        None if !is_naval(unit_type) => {
+            return format!(
+                "{:?} cannot walk on water at {}, it needs a bridge or a boat",
+                unit_type,
+                at(dst)
+            );
+        }
+        None => return format!("{:?} cannot sail that far to {}", unit_type, at(dst)),
+    };
+    let owner_id = region.owner().id();
+    if owner_id == player_id {
+        return match (*action, tile.unit()) {
+            (PlayerAction::PlaceNewUnit(..), Some(unit)) => format!(
+                "{:?} at {} cannot be joined with a new unit, buy it on an empty tile",
+                unit.unit_type(),
+                at(dst)
+            ),
+            (_, Some(unit)) => format!(
+                "{:?} at {} cannot be joined with {:?}",
+                unit.unit_type(),
+                at(dst),
+                unit_type
+            ),
+            _ => format!("There is no way for {:?} to {}", unit_type, at(dst)),
+        };
+    }
+    if tile.surface().is_water() {
+        return format!("Bridges and boats of other players at {} cannot be captured", at(dst));
+    }
+    if engine.are_allies(player_id, owner_id) {
+        return format!(
+            "Tile {} belongs to {}, who is your ally",
+            at(dst),
+            player_name(engine, owner_id)
+        );
+    }
+    if engine.have_truce(player_id, owner_id) {
+        return format!(
+            "You have a truce with {}, break it before attacking {}",
+            player_name(engine, owner_id),
+            at(dst)
+        );
+    }
+    let src_region = match *action {
+        PlayerAction::MoveUnit { src, .. } => location.region_at(src),
+        PlayerAction::PlaceNewUnit(region_id, _, _) => location.regions().get(&region_id),
+        _ => None,
+    };
+    let is_on_border = src_region
+        .map(|r| dst.neighbors().iter().any(|c| r.coordinates().contains(c)))
+        == Some(true);
+    if !is_on_border {
+        return format!(
+            "Tile {} is not next to your region, units capture only tiles on its border",
+            at(dst)
+        );
+    }
+    let (defence, _) = engine.tile_defence(dst).unwrap();
+    if defence >= description(unit_type).attack {
+        return explain_defence(engine, player_id, action, dst);
+    }
+    format!("There is no way for {:?} to {}", unit_type, at(dst))
+}
+
+/// Explain what defends the tile from the attack of the unit
+fn explain_defence(
+    engine: &GameEngine,
+    player_id: ID,
+    action: &PlayerAction,
+    dst: Coord,
+) -> String {
+    let location = engine.location();
+    let attacker = actor(engine, action);
+    let attack = attacker.map_or(0, |t| description(t).attack);
+    if let Some(owner_id) = location.region_at(dst).map(|r| r.owner().id()) {
+        if engine.are_allies(player_id, owner_id) || engine.have_truce(player_id, owner_id) {
+            return format!(
+                "Tile {} belongs to {}, who you are at peace with",
+                at(dst),
+                player_name(engine, owner_id)
+            );
+        }
+    }
+    let defence = match *action {
+        // Arrows hit only the unit on the tile, neighbours do not protect it
+        PlayerAction::Shoot { .. } => engine.shot_defence(dst).map(|d| (d, Some(dst))),
+        _ => engine.tile_defence(dst),
+    };
+    let (defence, defender) = match defence {
+        Some(defence) => defence,
+        None => return format!("Tile {} cannot be attacked", at(dst)),
+    };
+    let attacker = match attacker {
+        Some(unit_type) => format!("{:?} attacks with {}", unit_type, attack),
+        None => String::from("attack"),
+    };
+
+    let defender = defender.and_then(|c| location.tile_at(c).unwrap().unit().map(|u| (c, u)));
+    let mut reason = match defender {
+        Some((c, unit)) => {
+            let veteran = if engine.unit_is_veteran(unit.id()) == Some(true) {
+                "veteran "
+            } else {
+                ""
+            };
+            format!(
+                "Tile {} is defended with {} by {}{:?} at {}",
+                at(dst),
+                defence,
+                veteran,
+                unit.unit_type(),
+                at(c)
+            )
+        }
+        None => format!("Tile {} has defence {}", at(dst), defence),
+    };
+    if let Some(TileSurface::Mountain) = location.tile_at(dst).map(|t| *t.surface()) {
+        reason.push_str(", the mountain adds to it");
+    }
+    format!(
+        "{}, but {}. Attack has to be higher than defence",
+        reason, attacker
+    )
+}
+
+/// Suggest an action that is worth making for the player now. Returns `None` when it is not their
+/// turn or the game is over.
+pub fn suggest(engine: &GameEngine, player_id: ID) -> Option<Suggestion> {
+    if engine.winner().is_some() || engine.active_player().id() != player_id {
+        return None;
+    }
+    let view = engine.view_for(player_id);
+    let action = GreedyBot::candidates(&view)
+        .into_iter()
+        .find(|a| engine.check_action(player_id, a).is_ok());
+    let action = match action {
+        Some(action) => action,
+        None => {
+            return Some(Suggestion {
+                action: PlayerAction::EndTurn,
+                reason: String::from("There is nothing useful left to do, end the turn"),
+            });
+        }
+    };
+
+    let location = engine.location();
+    let reason = match action {
+        PlayerAction::MoveUnit { src, dst } => {
+            let unit_type = unit_type_at(engine, src).unwrap();
+            let is_own = location.region_at(dst).map(|r| r.owner().id()) == Some(player_id);
+            match unit_type_at(engine, dst) {
+                Some(target) if is_own => format!(
+                    "Clear {:?} at {} with {:?} from {}, it takes income of the tile",
+                    target,
+                    at(dst),
+                    unit_type,
+                    at(src)
+                ),
+                Some(UnitType::Village) => format!(
+                    "Capture the capital at {} with {:?} from {}, it takes money of the region",
+                    at(dst),
+                    unit_type,
+                    at(src)
+                ),
+                Some(target) => format!(
+                    "Attack {:?} at {} with {:?} from {}",
+                    target,
+                    at(dst),
+                    unit_type,
+                    at(src)
+                ),
+                None => format!(
+                    "Capture {} with {:?} from {}, more land brings more income",
+                    at(dst),
+                    unit_type,
+                    at(src)
+                ),
+            }
+        }
+        PlayerAction::PlaceNewUnit(region_id, unit_type, dst) => format!(
+            "Buy {:?} with money of region #{} and capture {}",
+            unit_type,
+            region_id,
+            at(dst)
+        ),
+        _ => String::from("Try this action"),
+    };
+    Some(Suggestion { action, reason })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{explain, suggest};
+    use crate::game::{Coord, PlayerAction};
+    use crate::init::ascii::parse;
+
+    #[test]
+    fn explanation_names_the_defender() {
+        let text = "players 2\norigin 0 0\n1V  1S  2.  2K  2V\n";
+        let mut engine = parse(text).unwrap().build().unwrap();
+        let player_id = engine.players()[0].id();
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(2, 0),
+        };
+        let error = engine.act(player_id, action).unwrap_err();
+
+        assert_eq!(
+            explain(&engine, player_id, &action, &error),
+            "Tile (2, 0) is defended with 3 by Knight at (3, 0), but Soldier attacks with 2. \
+             Attack has to be higher than defence"
+        );
+    }
+
+    #[test]
+    fn explanation_tells_how_far_unit_can_go() {
+        let text = "players 2\norigin 0 0\n1V  1S  1.  1.  1.  1.  2.  2V\n";
+        let mut engine = parse(text).unwrap().build().unwrap();
+        let player_id = engine.players()[0].id();
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(6, 0),
+        };
+        let error = engine.act(player_id, action).unwrap_err();
+
+        assert_eq!(
+            explain(&engine, player_id, &action, &error),
+            "Soldier has 4 moves left, but the way to (6, 0) takes 5. Moves are refilled at the \
+             beginning of your next turn"
+        );
+    }
+
+    #[test]
+    fn explanation_tells_what_is_not_next_to_region() {
+        let text = "players 2\norigin 0 0\n1V  1S  2.  2.  2V\n";
+        let mut engine = parse(text).unwrap().build().unwrap();
+        let player_id = engine.players()[0].id();
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(3, 0),
+        };
+        let error = engine.act(player_id, action).unwrap_err();
+
+        assert_eq!(
+            explain(&engine, player_id, &action, &error),
+            "Tile (3, 0) is not next to your region, units capture only tiles on its border"
+        );
+    }
+
+    #[test]
+    fn suggestion_is_a_valid_action() {
+        let text = "players 2\norigin 0 0\n1V  1S  2.  2.  2V\n";
+        let engine = parse(text).unwrap().build().unwrap();
+        let player_id = engine.players()[0].id();
+        let other_id = engine.players()[1].id();
+
+        let suggestion = suggest(&engine, player_id).unwrap();
+        assert_eq!(
+            suggestion.action,
+            PlayerAction::MoveUnit {
+                src: Coord::new(1, 0),
+                dst: Coord::new(2, 0),
+            }
+        );
+        assert!(engine.clone().act(player_id, suggestion.action).is_ok());
+        assert_eq!(suggest(&engine, other_id), None);
+    }
+}
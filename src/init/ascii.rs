@@ -0,0 +1,584 @@
+//! Plain-text format of maps, that is handy for writing test maps by hand and for looking at maps
+//! in logs.
+//!
+//! A map starts with two header lines: `players N` with a number of players and `origin X Y` with
+//! a coordinate of the top left corner of the map. Every next line is a row of hexes with the same
+//! `y`, starting from `Y`. Each hex is drawn with two characters and every next row is shifted by
+//! a half of hex to the right:
+//!
+//! ```text
+//! players 2
+//! origin 0 0
+//! ~~  1V  1.
+//!   1S  .P  2.
+//!     ~~  2V  2.
+//! ```
+//!
+//! The first character of a hex is `~` for water, `.` for land without owner, `0` for neutral land
+//! that is left to nobody (see `Player::neutral`) or a digit from `1` to `9` with a number of the
+//! player that owns the land. The second character is `~` for water,
+//! `.` for a hex without unit or a letter of a unit (see `unit_char`). Land hex can be followed
+//! by a third character in the gap after it: `^` for a mountain or `,` for a marsh (see
+//! `surface_char`):
+//!
+//! ```text
+//! players 2
+//! origin 0 0
+//! 1V  1.^ 2.
+//!   1S  2., 2V
+//! ```
+//!
+//! Bridges and boats stand on water, so a hex with an owner and a letter of bridge or boat is
+//! water, like `1B` in the middle of this map:
+//!
+//! ```text
+//! players 2
+//! origin 0 0
+//! 1V  1S  1B  2.  2V
+//! ```
+//!
+//! Units aboard boats are not drawn.
+//!
+//! Only surfaces, owners and units are stored, so ids of tiles, units, regions and players are
+//! not kept between parsing and rendering. Regions are formed from tiles of the same owner that
+//! touch each other.
+use std::collections::HashMap;
+
+use crate::game::{
+    is_naval, Coord, Location, LocationValidationError, Player, TileSurface, UnitType, ID,
+};
+use crate::init::builder::{
+    GameEngineBuilder, GameEngineBuilderInitiationError, GameEngineBuilderModificationError,
+};
+
+/// Width of one hex in characters, without a gap after it
+const CELL_WIDTH: usize = 2;
+
+const WATER: char = '~';
+const NOTHING: char = '.';
+const MOUNTAIN: char = '^';
+const MARSH: char = ',';
+/// Owners are drawn with digits from `1` to `9`
+const MAX_OWNERS: usize = 9;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AsciiMapError {
+    /// Header line with provided number is missing or malformed
+    BadHeader(usize),
+    /// Hex starts at a position where no hex can be, line and character numbers are provided
+    MisalignedHex(usize, usize),
+    /// Character is not allowed at this place, line and character numbers are provided
+    UnknownSymbol(usize, usize, char),
+    /// Owner digit is bigger than the number of players, line and character numbers are provided
+    UnknownOwner(usize, usize),
+    /// Location has more owners than there are digits for them, the number of owners is provided
+    TooManyOwners(usize),
+    InitiationError(GameEngineBuilderInitiationError),
+    ModificationError(GameEngineBuilderModificationError),
+    LocationError(LocationValidationError),
+}
+
+impl From<GameEngineBuilderInitiationError> for AsciiMapError {
+    fn from(e: GameEngineBuilderInitiationError) -> Self {
+        AsciiMapError::InitiationError(e)
+    }
+}
+
+impl From<GameEngineBuilderModificationError> for AsciiMapError {
+    fn from(e: GameEngineBuilderModificationError) -> Self {
+        AsciiMapError::ModificationError(e)
+    }
+}
+
+impl From<LocationValidationError> for AsciiMapError {
+    fn from(e: LocationValidationError) -> Self {
+        AsciiMapError::LocationError(e)
+    }
+}
+
+/// Return a letter that represents a unit of provided type
+pub fn unit_char(unit_type: UnitType) -> char {
+    match unit_type {
+        UnitType::Grave => 'X',
+        UnitType::PineTree => 'P',
+        UnitType::PalmTree => 'L',
+        UnitType::Village => 'V',
+        UnitType::Tower => 'T',
+        UnitType::GreatKnight => 'G',
+        UnitType::Knight => 'K',
+        UnitType::Soldier => 'S',
+        UnitType::Militia => 'M',
+        UnitType::Bridge => 'B',
+        UnitType::Boat => 'O',
+        UnitType::Castle => 'C',
+        UnitType::Archer => 'A',
+        UnitType::Engineer => 'E',
+    }
+}
+
+/// Return a character that marks land with provided surface, plain land and water are drawn
+/// without a mark
+pub fn surface_char(surface: TileSurface) -> Option<char> {
+    match surface {
+        TileSurface::Mountain => Some(MOUNTAIN),
+        TileSurface::Marsh => Some(MARSH),
+        TileSurface::Water | TileSurface::Land => None,
+    }
+}
+
+/// Return a type of unit represented by provided letter
+pub fn unit_from_char(symbol: char) -> Option<UnitType> {
+    match symbol {
+        'X' => Some(UnitType::Grave),
+        'P' => Some(UnitType::PineTree),
+        'L' => Some(UnitType::PalmTree),
+        'V' => Some(UnitType::Village),
+        'T' => Some(UnitType::Tower),
+        'G' => Some(UnitType::GreatKnight),
+        'K' => Some(UnitType::Knight),
+        'S' => Some(UnitType::Soldier),
+        'M' => Some(UnitType::Militia),
+        'B' => Some(UnitType::Bridge),
+        'O' => Some(UnitType::Boat),
+        'C' => Some(UnitType::Castle),
+        'A' => Some(UnitType::Archer),
+        'E' => Some(UnitType::Engineer),
+        _ => None,
+    }
+}
+
+/// One hex read from the text
+struct ParsedHex {
+    coordinate: Coord,
+    surface: TileSurface,
+    /// Number of the owner, `0` is the neutral owner
+    owner: Option<usize>,
+    unit_type: Option<UnitType>,
+}
+
+fn parse_header(
+    line: Option<&str>,
+    line_num: usize,
+    key: &str,
+    values_num: usize,
+) -> Result<Vec<i32>, AsciiMapError> {
+    let mut words = line
+        .ok_or(AsciiMapError::BadHeader(line_num))?
+        .split_whitespace();
+    if words.next() != Some(key) {
+        return Err(AsciiMapError::BadHeader(line_num));
+    }
+    let values: Vec<i32> = words
+        .map(|w| w.parse().map_err(|_| AsciiMapError::BadHeader(line_num)))
+        .collect::<Result<_, _>>()?;
+    if values.len() != values_num {
+        return Err(AsciiMapError::BadHeader(line_num));
+    }
+    Ok(values)
+}
+
+fn parse_hex(
+    first: char,
+    second: char,
+    line_num: usize,
+    char_num: usize,
+) -> Result<(TileSurface, Option<usize>, Option<UnitType>), AsciiMapError> {
+    if first == WATER || second == WATER {
+        if first != second {
+            let (symbol, num) = if first == WATER {
+                (second, char_num + 1)
+            } else {
+                (first, char_num)
+            };
+            return Err(AsciiMapError::UnknownSymbol(line_num, num, symbol));
+        }
+        return Ok((TileSurface::Water, None, None));
+    }
+
+    let owner = match first {
+        NOTHING => None,
+        '0'..='9' => Some(first.to_digit(10).unwrap() as usize),
+        _ => return Err(AsciiMapError::UnknownSymbol(line_num, char_num, first)),
+    };
+    let unit_type = match second {
+        NOTHING => None,
+        _ => Some(
+            unit_from_char(second)
+                .ok_or(AsciiMapError::UnknownSymbol(line_num, char_num + 1, second))?,
+        ),
+    };
+    // Bridges and boats can stand only on water
+    let surface = if unit_type.map(is_naval) == Some(true) {
+        TileSurface::Water
+    } else {
+        TileSurface::Land
+    };
+    Ok((surface, owner, unit_type))
+}
+
+/// Parse a map and return a builder with the map and players from the header. Player with
+/// number `N` on the map is `builder.players()[N - 1]`, and land with number `0` is neutral.
+///
+/// Regions that are big enough to need a capital will get one during build if there is no
+/// village in them.
+///
+/// # Examples
+///
+/// ```rust
+/// use yasc::init::ascii::parse;
+///
+/// let builder = parse("players 2\norigin 0 0\n1V  1.  ~~\n  2.  2V").unwrap();
+/// assert_eq!(builder.players().len(), 2);
+/// assert_eq!(builder.map().len(), 5);
+/// assert!(builder.build().is_ok());
+/// ```
+pub fn parse(text: &str) -> Result<GameEngineBuilder, AsciiMapError> {
+    let mut lines = text.lines();
+    let players_num = parse_header(lines.next(), 1, "players", 1)?[0];
+    if !(0..=9).contains(&players_num) {
+        return Err(AsciiMapError::BadHeader(1));
+    }
+    let origin = parse_header(lines.next(), 2, "origin", 2)?;
+    let origin = Coord::new(origin[0], origin[1]);
+
+    let mut hexes = Vec::new();
+    for (row, line) in lines.enumerate() {
+        let line_num = row + 3;
+        let chars: Vec<char> = line.chars().collect();
+        let mut position = 0;
+        while position < chars.len() {
+            if chars[position] == ' ' {
+                position += 1;
+                continue;
+            }
+            // Hexes of one row are two half-hexes apart, and every next row is shifted by one
+            // half-hex, so a hex can only start at even half-hex of even rows and vice versa
+            let half_hexes = position / CELL_WIDTH;
+            if position % CELL_WIDTH != 0 || (half_hexes + row) % 2 != 0 {
+                return Err(AsciiMapError::MisalignedHex(line_num, position + 1));
+            }
+            let second = chars.get(position + 1).cloned().unwrap_or(' ');
+            let (surface, owner, unit_type) =
+                parse_hex(chars[position], second, line_num, position + 1)?;
+            let mark = chars.get(position + CELL_WIDTH).cloned();
+            let surface = match mark {
+                Some(MOUNTAIN) | Some(MARSH) if surface.is_water() => {
+                    let num = position + CELL_WIDTH + 1;
+                    return Err(AsciiMapError::UnknownSymbol(line_num, num, mark.unwrap()));
+                }
+                Some(MOUNTAIN) => TileSurface::Mountain,
+                Some(MARSH) => TileSurface::Marsh,
+                _ => surface,
+            };
+            if owner.map(|o| o > players_num as usize) == Some(true) {
+                return Err(AsciiMapError::UnknownOwner(line_num, position + 1));
+            }
+
+            let y = origin.y + row as i32;
+            let x = origin.x + ((half_hexes + row) / 2) as i32 - row as i32;
+            hexes.push(ParsedHex {
+                coordinate: Coord::new(x, y),
+                surface,
+                owner,
+                unit_type,
+            });
+            position += CELL_WIDTH;
+            if surface_char(surface).is_some() {
+                position += 1;
+            }
+        }
+    }
+
+    let mut builder =
+        GameEngineBuilder::with_coordinates(hexes.iter().map(|h| h.coordinate), players_num as u8)?;
+    let player_ids: Vec<ID> = builder.players().iter().map(|p| p.id()).collect();
+    for hex in hexes {
+        builder.set_surface(hex.coordinate, hex.surface)?;
+        // Unit goes first, as water can be owned only under a bridge or a boat
+        if let Some(unit_type) = hex.unit_type {
+            builder.place_unit(hex.coordinate, unit_type)?;
+        }
+        match hex.owner {
+            Some(0) => builder.set_owner(hex.coordinate, Player::neutral().id())?,
+            Some(owner) => builder.set_owner(hex.coordinate, player_ids[owner - 1])?,
+            None => (),
+        }
+    }
+    Ok(builder)
+}
+
+/// Parse a map into a location as it is drawn, without adding capitals and checking game rules
+pub fn parse_location(text: &str) -> Result<Location, AsciiMapError> {
+    Ok(parse(text)?.build_location()?)
+}
+
+/// Render a location. Owners are numbered in the order of their ids, and a number of players in
+/// the header is a number of owners, but not less than two. Neutral land is drawn with `0`, and
+/// only nine other owners can be rendered.
+pub fn render(location: &Location) -> Result<String, AsciiMapError> {
+    let mut owners: Vec<ID> = location
+        .regions()
+        .values()
+        .map(|r| r.owner().id())
+        .filter(|&id| !Player::new(id).is_neutral())
+        .collect();
+    owners.sort();
+    owners.dedup();
+    if owners.len() > MAX_OWNERS {
+        return Err(AsciiMapError::TooManyOwners(owners.len()));
+    }
+    let owner_nums: HashMap<ID, usize> = owners.iter().enumerate().map(|(i, &o)| (o, i)).collect();
+
+    let map = location.map();
+    let min_row = map.keys().map(|c| c.y).min().unwrap_or(0);
+    let max_row = map.keys().map(|c| c.y).max().unwrap_or(-1);
+    let min_column = map.keys().map(|c| 2 * c.x + c.y).min().unwrap_or(0);
+    // Origin is a real hex position, so it can be half a hex to the left of the leftmost hex
+    let origin_x = (min_column - min_row).div_euclid(2);
+    let origin_column = 2 * origin_x + min_row;
+
+    let mut out = format!(
+        "players {}\norigin {} {}\n",
+        owners.len().max(2),
+        origin_x,
+        min_row
+    );
+    for y in min_row..=max_row {
+        let mut row: Vec<Coord> = map.keys().filter(|c| c.y == y).cloned().collect();
+        row.sort_by_key(|c| c.x);
+
+        let mut line = String::new();
+        for c in row {
+            let position = (2 * c.x + c.y - origin_column) as usize * CELL_WIDTH;
+            while line.len() < position {
+                line.push(' ');
+            }
+            let tile = &map[&c];
+            if tile.surface().is_water() && tile.unit().is_none() {
+                line.push(WATER);
+                line.push(WATER);
+                continue;
+            }
+            line.push(location.region_at(c).map_or(NOTHING, |r| {
+                let num = owner_nums.get(&r.owner().id()).map_or(0, |n| n + 1);
+                std::char::from_digit(num as u32, 10).unwrap()
+            }));
+            line.push(tile.unit().map_or(NOTHING, |u| unit_char(u.unit_type())));
+            if let Some(mark) = surface_char(*tile.surface()) {
+                line.push(mark);
+            }
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{
+        parse, parse_location, render, surface_char, unit_char, unit_from_char, AsciiMapError,
+    };
+    use crate::game::test_util::create_valid_engine;
+    use crate::game::{Coord, Location, Player, Region, Tile, TileSurface, UnitType};
+
+    const MAP: &str = "players 3
+origin -1 -1
+1.  1V  1.  ~~
+  1.  ~~  1S  2V
+    3V  3.  2M  2P
+";
+
+    #[test]
+    fn check_all_units_have_letters() {
+        let units = [
+            UnitType::Grave,
+            UnitType::PineTree,
+            UnitType::PalmTree,
+            UnitType::Village,
+            UnitType::Tower,
+            UnitType::GreatKnight,
+            UnitType::Knight,
+            UnitType::Soldier,
+            UnitType::Militia,
+            UnitType::Bridge,
+            UnitType::Boat,
+            UnitType::Castle,
+            UnitType::Archer,
+            UnitType::Engineer,
+        ];
+        for &u in units.iter() {
+            assert_eq!(unit_from_char(unit_char(u)), Some(u));
+        }
+    }
+
+    #[test]
+    fn check_map_is_parsed() {
+        let builder = parse(MAP).unwrap();
+        let players: Vec<_> = builder.players().iter().map(|p| p.id()).collect();
+
+        assert_eq!(players.len(), 3);
+        assert_eq!(builder.map().len(), 12);
+        assert_eq!(builder.map()[&Coord::new(0, 0)].surface(), &TileSurface::Water);
+        assert_eq!(builder.owners()[&Coord::new(-1, 0)], players[0]);
+        assert_eq!(builder.owners()[&Coord::new(2, 0)], players[1]);
+        assert_eq!(builder.owners()[&Coord::new(-1, 1)], players[2]);
+        assert_eq!(builder.owners()[&Coord::new(2, 1)], players[1]);
+        assert_eq!(
+            builder.map()[&Coord::new(1, 0)].unit().map(|u| u.unit_type()),
+            Some(UnitType::Soldier)
+        );
+        assert_eq!(
+            builder.map()[&Coord::new(2, 1)].unit().map(|u| u.unit_type()),
+            Some(UnitType::PineTree)
+        );
+    }
+
+    #[test]
+    fn check_parsed_map_is_rendered_back() {
+        let location = parse_location(MAP).unwrap();
+        assert_eq!(render(&location).unwrap(), MAP);
+
+        // Land without owner is kept as well
+        let text = "players 2\norigin 2 -3\n1V  1.  .P\n  ~~  2.  2V\n";
+        let location = parse_location(text).unwrap();
+        assert_eq!(location.region_at(Coord::new(4, -3)), None);
+        assert_eq!(render(&location).unwrap(), text);
+    }
+
+    #[test]
+    fn check_engine_location_round_trip() {
+        let (_, _, engine) = create_valid_engine();
+        let text = render(engine.location()).unwrap();
+
+        let location = parse_location(&text).unwrap();
+        assert_eq!(render(&location).unwrap(), text);
+        assert_eq!(location.map().len(), engine.location().map().len());
+        assert_eq!(location.regions().len(), engine.location().regions().len());
+        for (c, tile) in engine.location().map() {
+            let parsed = location.tile_at(*c).unwrap();
+            assert_eq!(parsed.surface(), tile.surface());
+            assert_eq!(
+                parsed.unit().map(|u| u.unit_type()),
+                tile.unit().map(|u| u.unit_type())
+            );
+            assert_eq!(
+                location.region_at(*c).map(|r| r.coordinates().len()),
+                engine.location().region_at(*c).map(|r| r.coordinates().len())
+            );
+        }
+    }
+
+    #[test]
+    fn check_too_many_owners_are_not_rendered() {
+        let mut map = HashMap::default();
+        let mut regions = Vec::new();
+        for i in 0..10 {
+            let coordinate = Coord::new(i, 0);
+            let id = i as u32;
+            map.insert(coordinate, Tile::new(id, TileSurface::Land));
+            let coordinates = [coordinate].iter().cloned().collect();
+            regions.push(Region::new(100 + id, Player::new(200 + id), coordinates));
+        }
+        let location = Location::new(map, regions).unwrap();
+
+        assert_eq!(render(&location), Err(AsciiMapError::TooManyOwners(10)));
+    }
+
+    #[test]
+    fn check_neutral_land_is_parsed_and_rendered_back() {
+        let text = "players 2\norigin 0 0\n1V  1.  0.  0S  2.  2V\n";
+        let builder = parse(text).unwrap();
+        assert_eq!(builder.owners()[&Coord::new(2, 0)], Player::neutral().id());
+
+        let engine = builder.build().unwrap();
+        let region = engine.location().region_at(Coord::new(3, 0)).unwrap();
+        assert!(region.owner().is_neutral());
+        assert_eq!(region.coordinates().len(), 2);
+        assert_eq!(render(engine.location()).unwrap(), text);
+    }
+
+    #[test]
+    fn check_terrain_is_parsed_and_rendered_back() {
+        let text = "players 2\norigin 0 0\n1V  1.^ 2.\n  1S  2., 2V\n";
+        let location = parse_location(text).unwrap();
+
+        let surface = |x, y| *location.tile_at(Coord::new(x, y)).unwrap().surface();
+        assert_eq!(surface(0, 0), TileSurface::Land);
+        assert_eq!(surface(1, 0), TileSurface::Mountain);
+        assert_eq!(surface(1, 1), TileSurface::Marsh);
+        assert_eq!(surface_char(TileSurface::Land), None);
+        assert_eq!(render(&location).unwrap(), text);
+    }
+
+    #[test]
+    fn check_bridges_and_boats_are_parsed_on_water() {
+        let text = "players 2\norigin 0 0\n1V  1S  1B  ~~  2V\n  1.  ~~  2O  2.\n";
+        let location = parse_location(text).unwrap();
+
+        let tile = location.tile_at(Coord::new(2, 0)).unwrap();
+        assert_eq!(*tile.surface(), TileSurface::Water);
+        assert_eq!(tile.unit().map(|u| u.unit_type()), Some(UnitType::Bridge));
+        assert!(location.region_at(Coord::new(2, 0)).is_some());
+        let tile = location.tile_at(Coord::new(2, 1)).unwrap();
+        assert_eq!(*tile.surface(), TileSurface::Water);
+        assert_eq!(tile.unit().map(|u| u.unit_type()), Some(UnitType::Boat));
+        assert_eq!(render(&location).unwrap(), text);
+        assert_eq!(
+            parse("players 2\norigin 0 0\n1O^ 1V\n"),
+            Err(AsciiMapError::UnknownSymbol(3, 3, '^'))
+        );
+    }
+
+    #[test]
+    fn check_parsed_map_can_be_built() {
+        let engine = parse(MAP).unwrap().build().unwrap();
+
+        assert_eq!(engine.players().len(), 3);
+        assert_eq!(render(engine.location()).unwrap(), MAP);
+    }
+
+    #[test]
+    fn check_bad_header_error() {
+        assert_eq!(parse(""), Err(AsciiMapError::BadHeader(1)));
+        assert_eq!(parse("players 2\n1V"), Err(AsciiMapError::BadHeader(2)));
+        assert_eq!(
+            parse("players 2\norigin 0\n1V"),
+            Err(AsciiMapError::BadHeader(2))
+        );
+    }
+
+    #[test]
+    fn check_misaligned_hex_error() {
+        assert_eq!(
+            parse("players 2\norigin 0 0\n1V 1."),
+            Err(AsciiMapError::MisalignedHex(3, 4))
+        );
+        assert_eq!(
+            parse("players 2\norigin 0 0\n1V\n1."),
+            Err(AsciiMapError::MisalignedHex(4, 1))
+        );
+    }
+
+    #[test]
+    fn check_unknown_symbols_errors() {
+        assert_eq!(
+            parse("players 2\norigin 0 0\n1V  1Q"),
+            Err(AsciiMapError::UnknownSymbol(3, 6, 'Q'))
+        );
+        assert_eq!(
+            parse("players 2\norigin 0 0\n~V"),
+            Err(AsciiMapError::UnknownSymbol(3, 2, 'V'))
+        );
+        assert_eq!(
+            parse("players 2\norigin 0 0\n~~^ 1V"),
+            Err(AsciiMapError::UnknownSymbol(3, 3, '^'))
+        );
+        assert_eq!(
+            parse("players 2\norigin 0 0\n1V  3."),
+            Err(AsciiMapError::UnknownOwner(3, 5))
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::game::{
-    Coord, EngineValidationError, GameEngine, IdProducer, Location, Player, Region, Tile,
-    TileSurface, Unit, UnitType, ID,
+    is_naval, needs_capital, Coord, EngineValidationError, GameEngine, IdProducer, Location,
+    LocationValidationError, Player, PlayerProfile, Region, RulesVariant, Tile, TileSurface, Unit,
+    UnitType, ID,
 };
 use hex2d::Direction;
 
@@ -16,7 +17,10 @@ pub enum GameEngineBuilderInitiationError {
 pub enum GameEngineBuilderModificationError {
     CoordinateOutOfBounds(Coord),
     CoordinateCannotBeOwned(Coord),
+    CoordinateCannotHoldUnit(Coord),
     NoSuchPlayer(ID),
+    AlreadyAllied(ID),
+    InvalidColor(ID),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -26,6 +30,9 @@ pub struct GameEngineBuilder {
     id_producer: IdProducer,
     players: Vec<Player>,
     player_ids: HashSet<ID>,
+    alliances: Vec<Vec<ID>>,
+    profiles: HashMap<ID, PlayerProfile>,
+    rules_variant: RulesVariant,
 }
 
 impl GameEngineBuilder {
@@ -47,6 +54,9 @@ impl GameEngineBuilder {
             player_ids,
             id_producer,
             coodinate_to_owner: HashMap::new(),
+            alliances: Vec::new(),
+            profiles: HashMap::new(),
+            rules_variant: RulesVariant::default(),
         })
     }
 
@@ -88,6 +98,24 @@ impl GameEngineBuilder {
         Self::new(map, players_num, id_producer)
     }
 
+    /// Create a map of arbitrary shape that consists of provided coordinates
+    pub fn with_coordinates<I: IntoIterator<Item = Coord>>(
+        coordinates: I,
+        players_num: u8,
+    ) -> Result<Self, GameEngineBuilderInitiationError> {
+        let mut coordinates: Vec<Coord> = coordinates.into_iter().collect();
+        if coordinates.is_empty() {
+            return Err(GameEngineBuilderInitiationError::TooSmallMap);
+        }
+        coordinates.sort();
+        let mut id_producer = IdProducer::default();
+        let map: HashMap<Coord, Tile> = coordinates
+            .into_iter()
+            .map(|c| (c, Tile::new(id_producer.next_id(), TileSurface::Water)))
+            .collect();
+        Self::new(map, players_num, id_producer)
+    }
+
     pub fn map(&self) -> &HashMap<Coord, Tile> {
         &self.map
     }
@@ -100,6 +128,59 @@ impl GameEngineBuilder {
         &self.coodinate_to_owner
     }
 
+    pub fn alliances(&self) -> &Vec<Vec<ID>> {
+        &self.alliances
+    }
+
+    pub fn rules_variant(&self) -> RulesVariant {
+        self.rules_variant
+    }
+
+    /// Set rules that the built engine will play by
+    pub fn set_rules_variant(&mut self, rules_variant: RulesVariant) {
+        self.rules_variant = rules_variant;
+    }
+
+    /// Return the profile set for the player, players without one get `PlayerProfile::numbered`
+    pub fn profile(&self, player_id: ID) -> Option<&PlayerProfile> {
+        self.profiles.get(&player_id)
+    }
+
+    /// Set a name, a color and a controller of the player. The color must be in the `#rrggbb`
+    /// form.
+    pub fn set_profile(
+        &mut self,
+        player_id: ID,
+        profile: PlayerProfile,
+    ) -> Result<(), GameEngineBuilderModificationError> {
+        if !self.player_ids.contains(&player_id) {
+            return Err(GameEngineBuilderModificationError::NoSuchPlayer(player_id));
+        }
+        if !profile.has_valid_color() {
+            return Err(GameEngineBuilderModificationError::InvalidColor(player_id));
+        }
+        self.profiles.insert(player_id, profile);
+
+        Ok(())
+    }
+
+    /// Make provided players allies. Each player can be a member of only one alliance.
+    pub fn add_alliance(
+        &mut self,
+        players: &[ID],
+    ) -> Result<(), GameEngineBuilderModificationError> {
+        for (i, &id) in players.iter().enumerate() {
+            if !self.player_ids.contains(&id) {
+                return Err(GameEngineBuilderModificationError::NoSuchPlayer(id));
+            } else if players[..i].contains(&id) || self.alliances.iter().any(|a| a.contains(&id)) {
+                return Err(GameEngineBuilderModificationError::AlreadyAllied(id));
+            }
+        }
+        self.alliances.push(players.to_vec());
+
+        Ok(())
+    }
+
     pub fn set_surface(
         &mut self,
         coordinate: Coord,
@@ -109,7 +190,8 @@ impl GameEngineBuilder {
             .map
             .get_mut(&coordinate)
             .ok_or_else(|| GameEngineBuilderModificationError::CoordinateOutOfBounds(coordinate))?;
-        if surface == TileSurface::Water && tile.unit().is_some() {
+        // Bridges and boats stay only on water, and other units only on land
+        if tile.unit().map(|u| is_naval(u.unit_type()) != surface.is_water()) == Some(true) {
             tile.take_unit();
         }
         self.coodinate_to_owner.remove(&coordinate);
@@ -118,6 +200,8 @@ impl GameEngineBuilder {
         Ok(())
     }
 
+    /// Give a tile to a player, or to nobody with the id of `Player::neutral`. Neutral land has no
+    /// capitals and can be captured by any player.
     pub fn set_owner(
         &mut self,
         coordinate: Coord,
@@ -127,9 +211,13 @@ impl GameEngineBuilder {
             return Err(GameEngineBuilderModificationError::CoordinateOutOfBounds(
                 coordinate,
             ));
-        } else if !self.player_ids.contains(&owner_id) {
+        } else if !self.player_ids.contains(&owner_id) && !Player::new(owner_id).is_neutral() {
             return Err(GameEngineBuilderModificationError::NoSuchPlayer(owner_id));
-        } else if !self.map[&coordinate].surface().is_land() {
+        }
+        // Water can be owned only under a bridge or a boat
+        let tile = &self.map[&coordinate];
+        let holds_water = tile.unit().map(|u| is_naval(u.unit_type())) == Some(true);
+        if !tile.surface().is_land() && !holds_water {
             return Err(GameEngineBuilderModificationError::CoordinateCannotBeOwned(
                 coordinate,
             ));
@@ -139,13 +227,40 @@ impl GameEngineBuilder {
         Ok(())
     }
 
+    /// Place a unit of specified type on a tile, replacing the unit that was there before.
+    /// Bridges and boats are placed on water, other units are placed on land.
+    ///
+    /// Capitals of regions are placed automatically during build, but villages placed with this
+    /// method are kept as capitals.
+    pub fn place_unit(
+        &mut self,
+        coordinate: Coord,
+        unit_type: UnitType,
+    ) -> Result<(), GameEngineBuilderModificationError> {
+        let tile = self
+            .map
+            .get_mut(&coordinate)
+            .ok_or(GameEngineBuilderModificationError::CoordinateOutOfBounds(coordinate))?;
+        if is_naval(unit_type) != tile.surface().is_water() {
+            return Err(GameEngineBuilderModificationError::CoordinateCannotHoldUnit(
+                coordinate,
+            ));
+        }
+        tile.place_unit(Unit::new(self.id_producer.next_id(), unit_type));
+
+        Ok(())
+    }
+
     fn build_regions(
         coordinate_to_owner: &HashMap<Coord, ID>,
         id_producer: &mut IdProducer,
     ) -> Vec<Region> {
         let mut coordinate_to_region: HashMap<Coord, ID> = HashMap::new();
         let mut regions: HashMap<ID, Region> = HashMap::new();
-        for (&c, &owner_id) in coordinate_to_owner.iter() {
+        // Coordinates are processed in a stable order, so the same map always gets the same ids
+        let mut owned: Vec<(&Coord, &ID)> = coordinate_to_owner.iter().collect();
+        owned.sort();
+        for (&c, &owner_id) in owned.into_iter() {
             let neighbours = c.neighbors();
             let same_owners: Vec<Coord> = neighbours
                 .iter()
@@ -184,7 +299,7 @@ impl GameEngineBuilder {
                     region.add(c);
                     coordinate_to_region.insert(c, region_id);
                 } else {
-                    let region_id = *region_ids.iter().next().unwrap();
+                    let region_id = *region_ids.iter().min().unwrap();
                     let mut region = regions.remove(&region_id).unwrap();
                     region.add(c);
                     coordinate_to_region.insert(c, region_id);
@@ -204,15 +319,38 @@ impl GameEngineBuilder {
             }
         }
 
-        regions.values().cloned().collect()
+        let mut regions: Vec<Region> = regions.values().cloned().collect();
+        regions.sort_by_key(Region::id);
+        regions
     }
 
     fn set_capitals(location: &mut Location, id_producer: &mut IdProducer) {
-        let capitals_coordinates: Vec<Coord> = location
-            .regions()
-            .values()
-            .map(|r| *r.coordinates().iter().next().unwrap())
-            .collect();
+        // Only regions with enough land to be controlled need a capital. We prefer free land tiles
+        // and choose the smallest coordinate among them, so the result does not depend on the
+        // order of iteration over regions.
+        let mut regions: Vec<&Region> = location.regions().values().collect();
+        regions.sort_by_key(|r| r.id());
+        let capitals_coordinates: Vec<Coord> = regions
+            .into_iter()
+            .filter(|r| needs_capital(location, r))
+            .filter(|r| {
+                !r.coordinates().iter().any(|&c| {
+                    location.tile_at(c).unwrap().unit().map(|u| u.unit_type())
+                        == Some(UnitType::Village)
+                })
+            }).map(|r| {
+                let land: Vec<Coord> = r
+                    .coordinates()
+                    .iter()
+                    .filter(|&&c| location.tile_at(c).unwrap().surface().is_land())
+                    .cloned()
+                    .collect();
+                let free = land
+                    .iter()
+                    .filter(|&&c| location.tile_at(c).unwrap().unit().is_none())
+                    .min();
+                *free.unwrap_or_else(|| land.iter().min().unwrap())
+            }).collect();
 
         for coordinate in capitals_coordinates {
             location
@@ -224,13 +362,25 @@ impl GameEngineBuilder {
         }
     }
 
+    /// Build only a location with regions formed by owners of tiles. Unlike `build`, this method
+    /// does not place capitals and does not check game rules.
+    pub fn build_location(mut self) -> Result<Location, LocationValidationError> {
+        let regions: Vec<Region> =
+            Self::build_regions(&self.coodinate_to_owner, &mut self.id_producer);
+        Location::new(self.map, regions)
+    }
+
     pub fn build(mut self) -> Result<GameEngine, EngineValidationError> {
         let regions: Vec<Region> =
             Self::build_regions(&self.coodinate_to_owner, &mut self.id_producer);
         let mut location = Location::new(self.map, regions)?;
         Self::set_capitals(&mut location, &mut self.id_producer);
 
-        GameEngine::new(location, self.players, self.id_producer)
+        let mut engine =
+            GameEngine::with_rules(location, self.players, self.id_producer, self.rules_variant)?;
+        engine.set_alliances(self.alliances);
+        engine.set_profiles(self.profiles);
+        Ok(engine)
     }
 }
 
@@ -239,7 +389,7 @@ mod test {
     use super::{
         GameEngineBuilder, GameEngineBuilderInitiationError, GameEngineBuilderModificationError,
     };
-    use crate::game::{Coord, TileSurface};
+    use crate::game::{Controller, Coord, PlayerProfile, TileSurface, UnitType, ID};
 
     #[test]
     fn check_circle_creation_size_error() {
@@ -254,6 +404,17 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn check_custom_map_creation() {
+        let coordinates = vec![Coord::new(0, 0), Coord::new(1, 0), Coord::new(5, -2)];
+        let builder = GameEngineBuilder::with_coordinates(coordinates, 2).unwrap();
+        assert_eq!(builder.map().len(), 3);
+        assert!(builder.map().contains_key(&Coord::new(5, -2)));
+
+        let result = GameEngineBuilder::with_coordinates(vec![], 2);
+        assert_eq!(result, Err(GameEngineBuilderInitiationError::TooSmallMap));
+    }
+
     #[test]
     fn check_rectangle_creation_size_error() {
         let result = GameEngineBuilder::rectangle(1, 10, 2);
@@ -290,6 +451,87 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn check_alliances_are_validated() {
+        let mut builder = GameEngineBuilder::circle(3, 3).unwrap();
+        let ids: Vec<ID> = builder.players().iter().map(|p| p.id()).collect();
+
+        assert_eq!(
+            builder.add_alliance(&[ids[0], 100]),
+            Err(GameEngineBuilderModificationError::NoSuchPlayer(100))
+        );
+        assert_eq!(
+            builder.add_alliance(&[ids[0], ids[0]]),
+            Err(GameEngineBuilderModificationError::AlreadyAllied(ids[0]))
+        );
+        assert_eq!(builder.add_alliance(&[ids[2], ids[0]]), Ok(()));
+        assert_eq!(
+            builder.add_alliance(&[ids[1], ids[2]]),
+            Err(GameEngineBuilderModificationError::AlreadyAllied(ids[2]))
+        );
+        assert_eq!(builder.alliances(), &vec![vec![ids[2], ids[0]]]);
+    }
+
+    /// Return a builder with land split between the first two players, and their ids
+    fn create_split_builder() -> (GameEngineBuilder, ID, ID) {
+        let mut builder = GameEngineBuilder::circle(3, 2).unwrap();
+        let one_id = builder.players()[0].id();
+        let two_id = builder.players()[1].id();
+        Coord::new(0, 0).for_each_in_range(2, |c| {
+            builder.set_surface(c, TileSurface::Land).unwrap();
+            let owner = if c.y > 0 || (c.y == 0 && c.x > 0) {
+                one_id
+            } else {
+                two_id
+            };
+            builder.set_owner(c, owner).unwrap();
+        });
+        (builder, one_id, two_id)
+    }
+
+    #[test]
+    fn check_alliances_are_passed_to_engine() {
+        let (mut builder, one_id, two_id) = create_split_builder();
+        builder.add_alliance(&[two_id, one_id]).unwrap();
+
+        let engine = builder.build().unwrap();
+
+        assert_eq!(engine.alliances(), &vec![vec![one_id, two_id]]);
+        assert!(engine.are_allies(two_id, one_id));
+    }
+
+    #[test]
+    fn check_profiles_are_passed_to_engine() {
+        let (mut builder, one_id, two_id) = create_split_builder();
+        let profile = PlayerProfile {
+            name: "Remote".to_owned(),
+            color: "#123456".to_owned(),
+            controller: Controller::Remote,
+        };
+
+        assert_eq!(
+            builder.set_profile(100, profile.clone()),
+            Err(GameEngineBuilderModificationError::NoSuchPlayer(100))
+        );
+        let broken = PlayerProfile {
+            color: "red\"/><script/>".to_owned(),
+            ..profile.clone()
+        };
+        assert_eq!(
+            builder.set_profile(two_id, broken),
+            Err(GameEngineBuilderModificationError::InvalidColor(two_id))
+        );
+        assert_eq!(builder.set_profile(two_id, profile.clone()), Ok(()));
+        assert_eq!(builder.profile(two_id), Some(&profile));
+        assert_eq!(builder.profile(one_id), None);
+
+        let engine = builder.build().unwrap();
+
+        assert_eq!(engine.players()[0].profile(), &PlayerProfile::numbered(0));
+        assert_eq!(engine.players()[1].profile(), &profile);
+        assert_eq!(engine.profile(two_id), Some(&profile));
+    }
+
     #[test]
     fn check_cannot_set_owner_for_water_tile() {
         let mut builder = GameEngineBuilder::circle(4, 2).unwrap();
@@ -331,4 +573,89 @@ mod test {
         assert_eq!(builder.set_surface(coord, TileSurface::Water), Ok(()));
         assert_eq!(builder.owners().get(&coord), None);
     }
+
+    #[test]
+    fn check_cannot_place_unit_on_water_tile() {
+        let mut builder = GameEngineBuilder::circle(4, 2).unwrap();
+        let coord = Coord::new(0, 0);
+
+        assert_eq!(
+            builder.place_unit(coord, UnitType::PineTree),
+            Err(GameEngineBuilderModificationError::CoordinateCannotHoldUnit(
+                coord
+            ))
+        );
+        assert_eq!(builder.map()[&coord].unit(), None);
+    }
+
+    #[test]
+    fn check_naval_units_are_placed_only_on_water() {
+        let mut builder = GameEngineBuilder::circle(4, 2).unwrap();
+        let (water, land) = (Coord::new(0, 0), Coord::new(1, 0));
+        builder.set_surface(land, TileSurface::Land).unwrap();
+        let owner = builder.players()[0].id();
+
+        assert_eq!(
+            builder.place_unit(land, UnitType::Boat),
+            Err(GameEngineBuilderModificationError::CoordinateCannotHoldUnit(
+                land
+            ))
+        );
+        builder.place_unit(water, UnitType::Boat).unwrap();
+        builder.set_owner(water, owner).unwrap();
+        assert_eq!(builder.owners().get(&water), Some(&owner));
+
+        builder.set_surface(water, TileSurface::Land).unwrap();
+        assert_eq!(builder.map()[&water].unit(), None);
+    }
+
+    #[test]
+    fn check_placed_village_is_kept_as_capital() {
+        let mut builder = GameEngineBuilder::circle(3, 2).unwrap();
+        let one_id = builder.players()[0].id();
+        let two_id = builder.players()[1].id();
+        let capital = Coord::new(1, 1);
+
+        let start_coord = Coord::new(0, 0);
+        start_coord.for_each_in_range(3, |c| {
+            builder.set_surface(c, TileSurface::Land).unwrap();
+            let owner = if c.y > 0 { one_id } else { two_id };
+            builder.set_owner(c, owner).unwrap();
+        });
+        builder.place_unit(capital, UnitType::Village).unwrap();
+
+        let engine = builder.build().unwrap();
+        let capitals: Vec<Coord> = engine
+            .location()
+            .region_at(capital)
+            .unwrap()
+            .coordinates()
+            .iter()
+            .filter(|&&c| {
+                engine.location().tile_at(c).unwrap().unit().map(|u| u.unit_type())
+                    == Some(UnitType::Village)
+            }).cloned()
+            .collect();
+        assert_eq!(capitals, vec![capital]);
+    }
+
+    #[test]
+    fn check_small_regions_have_no_capitals() {
+        let mut builder = GameEngineBuilder::circle(3, 2).unwrap();
+        let one_id = builder.players()[0].id();
+        let two_id = builder.players()[1].id();
+        let lonely = Coord::new(0, 0);
+
+        let start_coord = Coord::new(0, 0);
+        start_coord.for_each_in_range(3, |c| {
+            builder.set_surface(c, TileSurface::Land).unwrap();
+            builder.set_owner(c, one_id).unwrap();
+        });
+        builder.set_owner(lonely, two_id).unwrap();
+        builder.set_owner(Coord::new(3, 0), two_id).unwrap();
+        builder.set_owner(Coord::new(3, -1), two_id).unwrap();
+
+        let engine = builder.build().unwrap();
+        assert_eq!(engine.location().tile_at(lonely).unwrap().unit(), None);
+    }
 }
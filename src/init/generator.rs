@@ -0,0 +1,180 @@
+//! Generation of random maps in the spirit of original game: a round island with land randomly
+//! shared between players and some trees growing here and there.
+use std::collections::HashSet;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+use crate::game::{Coord, EngineValidationError, GameEngine, TileSurface, UnitType};
+use crate::init::builder::{GameEngineBuilder, GameEngineBuilderInitiationError};
+
+/// Share of the map that is covered with land, in percents
+const LAND_COVERAGE_PCT: usize = 70;
+
+/// Share of land that is covered with trees in the beginning of the game, in percents
+const TREES_PCT: u32 = 8;
+
+/// How many times we try to generate a map before giving up
+const MAX_ATTEMPTS: u32 = 20;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MapGenerationError {
+    InitiationError(GameEngineBuilderInitiationError),
+    NoValidMap(EngineValidationError),
+}
+
+impl From<GameEngineBuilderInitiationError> for MapGenerationError {
+    fn from(e: GameEngineBuilderInitiationError) -> Self {
+        MapGenerationError::InitiationError(e)
+    }
+}
+
+/// Generate a new game on a round map of provided radius. The same seed always produces the same
+/// map.
+///
+/// Land is grown from the center of the map, so it is always connected, and then each tile is
+/// given to a random player so all players own roughly the same amount of land.
+///
+/// # Examples
+///
+/// ```rust
+/// use yasc::init::generator::generate;
+///
+/// let engine = generate(42, 6, 3).unwrap();
+/// assert_eq!(engine.players().len(), 3);
+/// assert_eq!(engine.validate(), Ok(()));
+/// ```
+pub fn generate(seed: u64, radius: u32, players_num: u8) -> Result<GameEngine, MapGenerationError> {
+    let mut rng = XorShiftRng::seed_from_u64(seed);
+    let mut last_error = None;
+    for _ in 0..MAX_ATTEMPTS {
+        let builder = generate_builder(&mut rng, radius, players_num)?;
+        match builder.build() {
+            Ok(engine) => return Ok(engine),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(MapGenerationError::NoValidMap(last_error.unwrap()))
+}
+
+fn generate_builder<R: Rng>(
+    rng: &mut R,
+    radius: u32,
+    players_num: u8,
+) -> Result<GameEngineBuilder, GameEngineBuilderInitiationError> {
+    let mut builder = GameEngineBuilder::circle(radius, players_num)?;
+
+    let mut land = grow_land(rng, &builder);
+    for &c in land.iter() {
+        builder.set_surface(c, TileSurface::Land).unwrap();
+    }
+
+    let player_ids: Vec<_> = builder.players().iter().map(|p| p.id()).collect();
+    land.shuffle(rng);
+    for (i, &c) in land.iter().enumerate() {
+        builder.set_owner(c, player_ids[i % player_ids.len()]).unwrap();
+    }
+
+    land.sort();
+    for &c in land.iter() {
+        if !rng.gen_ratio(TREES_PCT, 100) {
+            continue;
+        }
+        let is_coast = c
+            .neighbors()
+            .iter()
+            .any(|n| builder.map().get(n).map(|t| t.surface().is_land()) != Some(true));
+        let tree = if is_coast {
+            UnitType::PalmTree
+        } else {
+            UnitType::PineTree
+        };
+        builder.place_unit(c, tree).unwrap();
+    }
+
+    Ok(builder)
+}
+
+/// Return coordinates of land, that grows randomly tile by tile starting from the center of map
+fn grow_land<R: Rng>(rng: &mut R, builder: &GameEngineBuilder) -> Vec<Coord> {
+    let map = builder.map();
+    let land_size = map.len() * LAND_COVERAGE_PCT / 100;
+
+    let mut land = Vec::new();
+    let mut seen: HashSet<Coord> = HashSet::new();
+    let mut frontier = vec![Coord::new(0, 0)];
+    seen.insert(Coord::new(0, 0));
+
+    while land.len() < land_size && !frontier.is_empty() {
+        let i = rng.gen_range(0, frontier.len());
+        let c = frontier.swap_remove(i);
+        land.push(c);
+        for n in c.neighbors().iter() {
+            if map.contains_key(n) && seen.insert(*n) {
+                frontier.push(*n);
+            }
+        }
+    }
+    land
+}
+
+#[cfg(test)]
+mod test {
+    use super::{generate, MapGenerationError};
+    use crate::game::GameEngine;
+    use crate::init::builder::GameEngineBuilderInitiationError;
+
+    fn describe(engine: &GameEngine) -> Vec<String> {
+        let mut tiles: Vec<String> = engine
+            .location()
+            .map()
+            .iter()
+            .map(|(c, t)| {
+                format!(
+                    "{:?} {:?} {:?} {:?}",
+                    c,
+                    t.surface(),
+                    t.unit().map(|u| u.unit_type()),
+                    engine.location().region_at(*c).map(|r| r.owner().id())
+                )
+            }).collect();
+        tiles.sort();
+        tiles
+    }
+
+    #[test]
+    fn check_same_seed_gives_same_map() {
+        let one = generate(7, 6, 3).unwrap();
+        let two = generate(7, 6, 3).unwrap();
+
+        assert_eq!(describe(&one), describe(&two));
+    }
+
+    #[test]
+    fn check_different_seeds_give_different_maps() {
+        let one = generate(1, 6, 3).unwrap();
+        let two = generate(2, 6, 3).unwrap();
+
+        assert_ne!(describe(&one), describe(&two));
+    }
+
+    #[test]
+    fn check_generated_maps_are_valid() {
+        for seed in 0..20 {
+            let engine = generate(seed, 5, 4).unwrap();
+            assert_eq!(engine.validate(), Ok(()));
+            assert_eq!(engine.players().len(), 4);
+        }
+    }
+
+    #[test]
+    fn check_too_small_map_error() {
+        assert_eq!(
+            generate(1, 1, 2),
+            Err(MapGenerationError::InitiationError(
+                GameEngineBuilderInitiationError::TooSmallMap
+            ))
+        );
+    }
+}
@@ -1 +1,3 @@
+pub mod ascii;
 pub mod builder;
+pub mod generator;
@@ -4,9 +4,19 @@ extern crate serde_derive;
 extern crate chrono;
 #[macro_use]
 extern crate log;
+extern crate rand;
+extern crate rand_xorshift;
 extern crate serde;
+extern crate serde_json;
 extern crate serde_yaml;
 
+pub mod bots;
+pub mod campaign;
 pub mod game;
+pub mod hints;
 pub mod init;
+pub mod net;
+pub mod ratings;
 pub mod saves;
+pub mod scenario;
+pub mod svg;
@@ -0,0 +1,110 @@
+use std::io::{self, BufReader};
+use std::net::{TcpStream, ToSocketAddrs};
+
+use super::{read_message, write_message, ClientMessage, GameState, ServerMessage};
+use crate::game::{PlayerAction, PlayerActionError, ID};
+
+/// Connection to a game hosted by `Server`. Client keeps the state of the game up to date with
+/// all updates it receives.
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    player_id: ID,
+    players: Vec<ID>,
+    state: GameState,
+    /// Number of the next request to the server
+    next_request: u64,
+}
+
+impl Client {
+    /// Connect to a server and wait until the game starts
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Self::from_stream(TcpStream::connect(addr)?)
+    }
+
+    /// Wait until the game starts on an already opened connection. Players are given to clients
+    /// in the order they connect, so opening connections one by one before waiting for the game
+    /// decides which client gets which player.
+    pub fn from_stream(writer: TcpStream) -> io::Result<Self> {
+        let mut reader = BufReader::new(writer.try_clone()?);
+        match read_message(&mut reader)? {
+            Some(ServerMessage::Welcome {
+                player_id,
+                players,
+                state,
+            }) => Ok(Client {
+                reader,
+                writer,
+                player_id,
+                players,
+                state,
+                next_request: 0,
+            }),
+            Some(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Server should greet a client first",
+            )),
+            None => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+        }
+    }
+
+    pub fn player_id(&self) -> ID {
+        self.player_id
+    }
+
+    /// Return ids of all players in the order they make their turns
+    pub fn players(&self) -> &[ID] {
+        &self.players
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn is_my_turn(&self) -> bool {
+        self.state.winner.is_none() && self.state.active_player == self.player_id
+    }
+
+    /// Wait for the next message from the server and update the state of the game with it
+    pub fn receive(&mut self) -> io::Result<ServerMessage> {
+        let message = read_message(&mut self.reader)?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        if let ServerMessage::Update { ref diff, .. } = message {
+            self.state.apply(diff);
+        }
+        Ok(message)
+    }
+
+    /// Send an action to the server and wait until it is accepted or rejected. Updates made by
+    /// other players in the meantime are applied to the state.
+    pub fn act(&mut self, action: PlayerAction) -> io::Result<Result<(), PlayerActionError>> {
+        let request = self.next_request;
+        self.next_request += 1;
+        write_message(&mut self.writer, &ClientMessage::Act { request, action })?;
+        loop {
+            match self.receive()? {
+                ServerMessage::Update {
+                    player_id,
+                    request: Some(done),
+                    ..
+                } if player_id == self.player_id && done == request => return Ok(Ok(())),
+                ServerMessage::Rejected {
+                    request: rejected,
+                    error,
+                } if rejected == request => return Ok(Err(error)),
+                ServerMessage::Error(e) => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Receive updates until it is the turn of this client's player or the game is finished
+    pub fn wait_for_turn(&mut self) -> io::Result<()> {
+        while !self.is_my_turn() && self.state.winner.is_none() {
+            self.receive()?;
+        }
+        Ok(())
+    }
+}
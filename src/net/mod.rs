@@ -0,0 +1,321 @@
+//! Playing over network: a server that hosts a game and a client library to connect to it.
+//!
+//! Server and clients talk with JSON messages, one message per line. Each message is an object
+//! with `type` field, and `data` field with its contents if there are any.
+//!
+//! Client sends only one kind of messages:
+//!
+//! - `Act` with a `PlayerAction` as data: an action client wants to do for its player, and a
+//!   `request` number chosen by the client that the server sends back with the answer
+//!
+//! Server sends:
+//!
+//! - `Welcome` when all players have connected: id of the client's player, ids of all players in
+//!   the order they make turns and the state of the game as the client's player sees it
+//! - `Update` to all clients when an action was accepted: who did it, what was done, the number
+//!   of the request that asked for it and how the state of the game has changed for the client's
+//!   player. Actions that the
+//!   server makes itself, like ending turns of players who have run out of time, have no request.
+//! - `Rejected` to the client whose action was not accepted, with the number of the request and
+//!   a `PlayerActionError`
+//! - `Error` to the client whose message could not be understood
+//!
+//! Maps use coordinates as keys, and coordinates cannot be keys of JSON objects, so the state of
+//! the game is sent as a list of tiles instead of a `GameEngine`.
+use std::io::{self, BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::game::{
+    Coord, GameEvent, PlayerAction, PlayerActionError, PlayerView, TileSurface, UnitType, ID,
+};
+
+mod client;
+mod server;
+
+pub use self::client::Client;
+pub use self::server::Server;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum ClientMessage {
+    Act { request: u64, action: PlayerAction },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum ServerMessage {
+    Welcome {
+        player_id: ID,
+        players: Vec<ID>,
+        state: GameState,
+    },
+    Update {
+        player_id: ID,
+        action: PlayerAction,
+        /// Request of the acting player's client, `None` if the server has made the action
+        request: Option<u64>,
+        diff: StateDiff,
+    },
+    Rejected {
+        request: u64,
+        error: PlayerActionError,
+    },
+    Error(String),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct TileState {
+    pub coordinate: Coord,
+    pub surface: TileSurface,
+    pub unit: Option<UnitType>,
+    pub region: Option<ID>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct RegionState {
+    pub id: ID,
+    pub owner: ID,
+    /// Money of the region, known only for regions of the client's player
+    pub money: Option<i32>,
+}
+
+/// State of the game as the player of a client sees it, see `PlayerView`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameState {
+    pub turn: u32,
+    pub active_player: ID,
+    pub winner: Option<ID>,
+    /// Tiles sorted by coordinate
+    pub tiles: Vec<TileState>,
+    /// Regions sorted by id
+    pub regions: Vec<RegionState>,
+}
+
+/// Changes of the game state made by one action
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateDiff {
+    pub turn: u32,
+    pub active_player: ID,
+    pub winner: Option<ID>,
+    /// Tiles that have changed or become visible, sorted by coordinate
+    pub tiles: Vec<TileState>,
+    /// Coordinates of tiles that are not visible anymore, sorted
+    pub hidden_tiles: Vec<Coord>,
+    /// Regions that have appeared or changed, sorted by id
+    pub regions: Vec<RegionState>,
+    /// Ids of regions that do not exist anymore
+    pub removed_regions: Vec<ID>,
+    pub events: Vec<GameEvent>,
+}
+
+impl GameState {
+    pub fn new(view: &PlayerView) -> Self {
+        let location = view.location();
+        let mut tiles: Vec<TileState> = location
+            .map()
+            .iter()
+            .map(|(&coordinate, tile)| TileState {
+                coordinate,
+                surface: *tile.surface(),
+                unit: tile.unit().map(|u| u.unit_type()),
+                region: location.region_at(coordinate).map(|r| r.id()),
+            }).collect();
+        tiles.sort();
+        let mut regions: Vec<RegionState> = location
+            .regions()
+            .values()
+            .map(|r| RegionState {
+                id: r.id(),
+                owner: r.owner().id(),
+                money: view.region_money(r.id()),
+            }).collect();
+        regions.sort();
+
+        GameState {
+            turn: view.current_turn(),
+            active_player: view.active_player().id(),
+            winner: view.winner(),
+            tiles,
+            regions,
+        }
+    }
+
+    /// Return changes that turn this state into the new one. Events are not known to states, so
+    /// they are left empty.
+    pub fn diff(&self, new: &GameState) -> StateDiff {
+        let tiles = new
+            .tiles
+            .iter()
+            .filter(|t| self.tile_at(t.coordinate) != Some(*t))
+            .cloned()
+            .collect();
+        let hidden_tiles = self
+            .tiles
+            .iter()
+            .filter(|t| new.tile_at(t.coordinate).is_none())
+            .map(|t| t.coordinate)
+            .collect();
+        let regions = new
+            .regions
+            .iter()
+            .filter(|r| self.region(r.id) != Some(*r))
+            .cloned()
+            .collect();
+        let removed_regions = self
+            .regions
+            .iter()
+            .filter(|r| new.region(r.id).is_none())
+            .map(|r| r.id)
+            .collect();
+
+        StateDiff {
+            turn: new.turn,
+            active_player: new.active_player,
+            winner: new.winner,
+            tiles,
+            hidden_tiles,
+            regions,
+            removed_regions,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn apply(&mut self, diff: &StateDiff) {
+        self.turn = diff.turn;
+        self.active_player = diff.active_player;
+        self.winner = diff.winner;
+        self.tiles
+            .retain(|t| !diff.hidden_tiles.contains(&t.coordinate));
+        for &tile in diff.tiles.iter() {
+            match self
+                .tiles
+                .binary_search_by_key(&tile.coordinate, |t| t.coordinate)
+            {
+                Ok(i) => self.tiles[i] = tile,
+                Err(i) => self.tiles.insert(i, tile),
+            }
+        }
+        self.regions
+            .retain(|r| !diff.removed_regions.contains(&r.id));
+        for &region in diff.regions.iter() {
+            match self.regions.binary_search_by_key(&region.id, |r| r.id) {
+                Ok(i) => self.regions[i] = region,
+                Err(i) => self.regions.insert(i, region),
+            }
+        }
+    }
+
+    pub fn tile_at(&self, coordinate: Coord) -> Option<&TileState> {
+        self.tiles
+            .binary_search_by_key(&coordinate, |t| t.coordinate)
+            .ok()
+            .map(|i| &self.tiles[i])
+    }
+
+    pub fn region(&self, id: ID) -> Option<&RegionState> {
+        self.regions
+            .binary_search_by_key(&id, |r| r.id)
+            .ok()
+            .map(|i| &self.regions[i])
+    }
+}
+
+/// Write a message as one line of JSON
+fn write_message<W: Write, T: Serialize>(writer: &mut W, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+/// Read a message from one line of JSON. Return `None` if the connection was closed, and an
+/// error of `InvalidData` kind if the line is not a valid message.
+fn read_message<R: BufRead, T: DeserializeOwned>(reader: &mut R) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    // Conversion of serde errors keeps the kind of truncated lines as `UnexpectedEof`, but the
+    // line is complete here, so it is just not a valid message
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_message, write_message, ClientMessage, GameState};
+    use crate::game::test_util::create_valid_engine;
+    use crate::game::{Coord, PlayerAction};
+    use std::io::{Cursor, ErrorKind};
+
+    #[test]
+    fn check_diff_turns_old_state_into_new_one() {
+        let (pl, _, mut engine) = create_valid_engine();
+        let mut state = GameState::new(&engine.view_for(pl[0].id()));
+
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(1, 1),
+        };
+        engine.act(pl[0].id(), action).unwrap();
+        let new_state = GameState::new(&engine.view_for(pl[0].id()));
+        let diff = state.diff(&new_state);
+        state.apply(&diff);
+
+        assert!(diff.tiles.len() < new_state.tiles.len());
+        assert!(!diff.regions.is_empty());
+        assert_eq!(state, new_state);
+    }
+
+    #[test]
+    fn check_diff_hides_tiles_that_are_not_visible() {
+        let (pl, _, mut engine) = create_valid_engine();
+        engine.set_sight_radius(0);
+        let mut state = GameState::new(&engine.view_for(pl[0].id()));
+        let other_state = GameState::new(&engine.view_for(pl[1].id()));
+
+        let diff = state.diff(&other_state);
+        state.apply(&diff);
+
+        assert!(!diff.hidden_tiles.is_empty());
+        assert_eq!(state, other_state);
+    }
+
+    #[test]
+    fn check_messages_are_read_line_by_line() {
+        let first_act = ClientMessage::Act {
+            request: 1,
+            action: PlayerAction::UpgradeUnit(Coord::new(1, -2)),
+        };
+        let second_act = ClientMessage::Act {
+            request: 2,
+            action: PlayerAction::EndTurn,
+        };
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &first_act).unwrap();
+        write_message(&mut buffer, &second_act).unwrap();
+        buffer.extend_from_slice(b"not a message\n");
+
+        let mut reader = Cursor::new(buffer);
+        let first: Option<ClientMessage> = read_message(&mut reader).unwrap();
+        let second: Option<ClientMessage> = read_message(&mut reader).unwrap();
+        let third: Result<Option<ClientMessage>, _> = read_message(&mut reader);
+        let fourth: Option<ClientMessage> = read_message(&mut reader).unwrap();
+
+        assert_eq!(first, Some(first_act));
+        assert_eq!(second, Some(second_act));
+        assert_eq!(third.unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(fourth, None);
+    }
+
+    #[test]
+    fn check_truncated_messages_are_invalid() {
+        let mut reader = Cursor::new(b"{\"type\":\"Act\",\"data\":{\"request\":1\n".to_vec());
+        let message: Result<Option<ClientMessage>, _> = read_message(&mut reader);
+
+        assert_eq!(message.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,229 @@
+use std::io::{self, BufReader};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use super::{read_message, write_message, ClientMessage, GameState, ServerMessage};
+use crate::game::{GameEngine, PlayerAction, SystemTimeSource, TimeSource, ID};
+
+/// Something that has happened with one of the clients, clients are numbered in the order of
+/// their players
+enum Incoming {
+    Message(usize, ClientMessage),
+    Malformed(usize, String),
+    Disconnected(usize),
+}
+
+/// Server that hosts one game. Each client that connects gets the next player of the game, and
+/// the game starts when all players have their clients.
+///
+/// Time controls of the game are checked against the time source of the server.
+pub struct Server<T: TimeSource = SystemTimeSource> {
+    listener: TcpListener,
+    engine: GameEngine,
+    time: T,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A, engine: GameEngine) -> io::Result<Self> {
+        Server::bind_with_time(addr, engine, SystemTimeSource)
+    }
+}
+
+impl<T: TimeSource> Server<T> {
+    pub fn bind_with_time<A: ToSocketAddrs>(
+        addr: A,
+        engine: GameEngine,
+        time: T,
+    ) -> io::Result<Self> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            engine,
+            time,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Wait for clients of all players and host the game until somebody wins or all clients
+    /// disconnect. Turns of players whose clients have disconnected or who have run out of time
+    /// are ended automatically.
+    ///
+    /// Return the engine with the final state of the game.
+    pub fn run(self) -> io::Result<GameEngine> {
+        let players: Vec<ID> = self.engine.players().iter().map(|p| p.id()).collect();
+        let (sender, receiver) = mpsc::channel();
+        let mut clients = Vec::new();
+        for num in 0..players.len() {
+            let (stream, _) = self.listener.accept()?;
+            let reader = stream.try_clone()?;
+            let sender = sender.clone();
+            thread::spawn(move || read_client(num, reader, &sender));
+            clients.push(Some(stream));
+        }
+        drop(sender);
+
+        let states = players
+            .iter()
+            .map(|&id| GameState::new(&self.engine.view_for(id)))
+            .collect();
+        let mut game = HostedGame {
+            engine: self.engine,
+            time: self.time,
+            states,
+            players,
+            clients,
+        };
+        game.play(&receiver);
+        Ok(game.close())
+    }
+}
+
+/// Read messages of one client until it disconnects
+fn read_client(num: usize, stream: TcpStream, sender: &Sender<Incoming>) {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let incoming = match read_message(&mut reader) {
+            Ok(Some(message)) => Incoming::Message(num, message),
+            Err(ref e) if e.kind() == io::ErrorKind::InvalidData => {
+                Incoming::Malformed(num, e.to_string())
+            }
+            Ok(None) | Err(_) => {
+                let _ = sender.send(Incoming::Disconnected(num));
+                return;
+            }
+        };
+        if sender.send(incoming).is_err() {
+            return;
+        }
+    }
+}
+
+struct HostedGame<T: TimeSource> {
+    engine: GameEngine,
+    time: T,
+    /// States that clients have seen last, each client sees only what its player can see
+    states: Vec<GameState>,
+    players: Vec<ID>,
+    /// Streams to clients, `None` for clients that have disconnected
+    clients: Vec<Option<TcpStream>>,
+}
+
+impl<T: TimeSource> HostedGame<T> {
+    fn play(&mut self, receiver: &Receiver<Incoming>) {
+        for num in 0..self.clients.len() {
+            let welcome = ServerMessage::Welcome {
+                player_id: self.players[num],
+                players: self.players.clone(),
+                state: self.states[num].clone(),
+            };
+            self.send(num, &welcome);
+        }
+
+        loop {
+            self.skip_disconnected();
+            if self.engine.winner().is_some() || self.clients.iter().all(Option::is_none) {
+                return;
+            }
+            let incoming = match self.receive(receiver) {
+                Ok(incoming) => incoming,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+            // The active player may have run out of time while the message was on its way
+            self.check_time();
+            match incoming {
+                Incoming::Message(num, ClientMessage::Act { request, action }) => {
+                    let player_id = self.players[num];
+                    match self.engine.act_with_time(player_id, action, &self.time) {
+                        Ok(()) => self.broadcast_update(player_id, action, Some(request)),
+                        Err(error) => self.send(num, &ServerMessage::Rejected { request, error }),
+                    }
+                }
+                Incoming::Malformed(num, error) => {
+                    self.send(num, &ServerMessage::Error(error));
+                }
+                Incoming::Disconnected(num) => self.disconnect(num),
+            }
+        }
+    }
+
+    /// Wait for something to happen with clients, but not longer than the active player has
+    /// time for their turn
+    fn receive(&mut self, receiver: &Receiver<Incoming>) -> Result<Incoming, RecvTimeoutError> {
+        self.check_time();
+        let player_id = self.engine.active_player().id();
+        match self.engine.time_left(player_id) {
+            Some(left) => receiver.recv_timeout(Duration::from_millis(left)),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        }
+    }
+
+    /// End the turn of the active player if they have run out of time
+    fn check_time(&mut self) {
+        if let Some(player_id) = self.engine.check_time(&self.time) {
+            self.broadcast_update(player_id, PlayerAction::EndTurn, None);
+        }
+    }
+
+    /// End turns of active players while their clients are disconnected
+    fn skip_disconnected(&mut self) {
+        while self.engine.winner().is_none() && self.clients.iter().any(Option::is_some) {
+            let num = self.engine.active_player_num();
+            if self.clients[num].is_some() {
+                return;
+            }
+            let player_id = self.players[num];
+            self.engine
+                .act_with_time(player_id, PlayerAction::EndTurn, &self.time)
+                .expect("Active player should be always able to end turn");
+            self.broadcast_update(player_id, PlayerAction::EndTurn, None);
+        }
+    }
+
+    fn broadcast_update(&mut self, player_id: ID, action: PlayerAction, request: Option<u64>) {
+        let events = self.engine.take_events();
+        for num in 0..self.clients.len() {
+            let state = GameState::new(&self.engine.view_for(self.players[num]));
+            let mut diff = self.states[num].diff(&state);
+            diff.events = events.clone();
+            self.states[num] = state;
+
+            let update = ServerMessage::Update {
+                player_id,
+                action,
+                request,
+                diff,
+            };
+            self.send(num, &update);
+        }
+    }
+
+    /// Send a message to a client, client is disconnected if it cannot receive it
+    fn send(&mut self, num: usize, message: &ServerMessage) {
+        let failed = match self.clients[num] {
+            Some(ref mut stream) => write_message(stream, message).is_err(),
+            None => false,
+        };
+        if failed {
+            self.disconnect(num);
+        }
+    }
+
+    fn disconnect(&mut self, num: usize) {
+        if let Some(stream) = self.clients[num].take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// Disconnect all clients and return the engine
+    fn close(mut self) -> GameEngine {
+        for num in 0..self.clients.len() {
+            self.disconnect(num);
+        }
+        self.engine
+    }
+}
@@ -0,0 +1,286 @@
+//! Ratings of named players and the history of their matches.
+//!
+//! Ratings follow the Elo system extended to several players: a match of N players is counted as
+//! matches between every pair of them, where the player who placed higher wins, and changes of
+//! rating are scaled down by the number of opponents.
+//!
+//! Ratings are kept next to saved games, see `SavedGamesCatalog::load_ratings`.
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use chrono::prelude::*;
+use serde_yaml;
+
+use crate::game::{GameEngine, ID};
+
+/// Rating of a new player
+pub const DEFAULT_RATING: f64 = 1500.0;
+
+/// Maximal change of rating in a match against one opponent
+pub const K_FACTOR: f64 = 32.0;
+
+/// Final places of named players in a finished game, the first place is 1. Players that share
+/// a place, like members of the winning alliance, are tied.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct MatchResult {
+    pub places: Vec<(String, u32)>,
+}
+
+impl MatchResult {
+    /// Build a result of a finished game: winners share the first place and other players are
+    /// placed in the reverse order of their defeat. Players are named after their profiles.
+    /// Returns `None` if the game is not finished.
+    pub fn from_engine(engine: &GameEngine) -> Option<Self> {
+        let winners = engine.winners();
+        if winners.is_empty() {
+            return None;
+        }
+        let mut groups: Vec<Vec<ID>> = vec![winners.clone()];
+        for group in engine.defeated_players().iter().rev() {
+            let group: Vec<ID> = group
+                .iter()
+                .filter(|id| !winners.contains(id))
+                .cloned()
+                .collect();
+            if !group.is_empty() {
+                groups.push(group);
+            }
+        }
+
+        let mut places = Vec::new();
+        for group in groups {
+            let place = places.len() as u32 + 1;
+            for id in group {
+                places.push((engine.profile(id)?.name.clone(), place));
+            }
+        }
+        Some(MatchResult { places })
+    }
+}
+
+/// Rating of a player with the number of games they have played
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Profile {
+    pub rating: f64,
+    pub games: u32,
+    pub wins: u32,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile {
+            rating: DEFAULT_RATING,
+            games: 0,
+            wins: 0,
+        }
+    }
+}
+
+/// Place and ratings of a player in a recorded match
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MatchPlayer {
+    pub name: String,
+    pub place: u32,
+    pub rating_before: f64,
+    pub rating_after: f64,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MatchRecord {
+    pub timestamp: DateTime<Utc>,
+    pub players: Vec<MatchPlayer>,
+}
+
+/// Profiles of all players of a league with the history of their matches
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Ratings {
+    profiles: BTreeMap<String, Profile>,
+    history: Vec<MatchRecord>,
+}
+
+/// Return the probability of winning for a player with the first rating against the second one
+fn expected_score(rating: f64, other_rating: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((other_rating - rating) / 400.0))
+}
+
+impl Ratings {
+    /// Read ratings from a YAML file, a missing file gives empty ratings
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let buffer = match File::open(path) {
+            Ok(buffer) => buffer,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Ratings::default()),
+            Err(e) => return Err(e),
+        };
+        serde_yaml::from_reader(buffer)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write ratings to a YAML file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let buffer = File::create(path)?;
+        serde_yaml::to_writer(buffer, self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+
+    /// Return names and profiles of players from the highest rating to the lowest one
+    pub fn leaderboard(&self) -> Vec<(&str, &Profile)> {
+        let mut leaderboard: Vec<(&str, &Profile)> =
+            self.profiles.iter().map(|(n, p)| (n.as_str(), p)).collect();
+        leaderboard.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating).then(a.0.cmp(b.0)));
+        leaderboard
+    }
+
+    /// Return recorded matches from the oldest to the newest one
+    pub fn history(&self) -> &Vec<MatchRecord> {
+        &self.history
+    }
+
+    /// Update ratings of players of the match and add it to the history. Players without
+    /// profiles get new ones.
+    pub fn record(&mut self, result: &MatchResult) -> &MatchRecord {
+        let before: Vec<f64> = result
+            .places
+            .iter()
+            .map(|(name, _)| self.profiles.get(name).map_or(DEFAULT_RATING, |p| p.rating))
+            .collect();
+        let opponents = result.places.len().saturating_sub(1).max(1) as f64;
+
+        let mut players = Vec::new();
+        for (i, (name, place)) in result.places.iter().enumerate() {
+            let mut change = 0.0;
+            for (j, (_, other_place)) in result.places.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                let score = if place < other_place {
+                    1.0
+                } else if place == other_place {
+                    0.5
+                } else {
+                    0.0
+                };
+                change += score - expected_score(before[i], before[j]);
+            }
+            let rating_after = before[i] + K_FACTOR * change / opponents;
+
+            let profile = self.profiles.entry(name.clone()).or_default();
+            profile.rating = rating_after;
+            profile.games += 1;
+            if *place == 1 {
+                profile.wins += 1;
+            }
+            players.push(MatchPlayer {
+                name: name.clone(),
+                place: *place,
+                rating_before: before[i],
+                rating_after,
+            });
+        }
+
+        self.history.push(MatchRecord {
+            timestamp: Utc::now().with_nanosecond(0).unwrap(),
+            players,
+        });
+        self.history.last().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MatchResult, Ratings, DEFAULT_RATING};
+    use crate::game::{Controller, Coord, PlayerAction, PlayerProfile, ID};
+    use crate::init::ascii::parse;
+
+    fn result(places: &[(&str, u32)]) -> MatchResult {
+        MatchResult {
+            places: places.iter().map(|&(n, p)| (n.to_owned(), p)).collect(),
+        }
+    }
+
+    #[test]
+    fn winner_takes_rating_of_losers() {
+        let mut ratings = Ratings::default();
+        ratings.record(&result(&[("alice", 1), ("bob", 2), ("carol", 3)]));
+
+        let alice = ratings.profile("alice").unwrap();
+        let bob = ratings.profile("bob").unwrap();
+        let carol = ratings.profile("carol").unwrap();
+        assert!(alice.rating > DEFAULT_RATING);
+        assert!((bob.rating - DEFAULT_RATING).abs() < 1e-9);
+        assert!(carol.rating < DEFAULT_RATING);
+        assert!((alice.rating + bob.rating + carol.rating - 3.0 * DEFAULT_RATING).abs() < 1e-9);
+        assert_eq!((alice.games, alice.wins), (1, 1));
+        assert_eq!((carol.games, carol.wins), (1, 0));
+
+        let names: Vec<&str> = ratings.leaderboard().iter().map(|(n, _)| *n).collect();
+        assert_eq!(names, vec!["alice", "bob", "carol"]);
+        assert_eq!(ratings.history().len(), 1);
+    }
+
+    #[test]
+    fn leaderboard_accepts_any_ratings() {
+        let mut ratings = Ratings::default();
+        ratings.record(&result(&[("alice", 1), ("bob", 2), ("carol", 3)]));
+        ratings.profiles.get_mut("bob").unwrap().rating = f64::NAN;
+
+        let names: Vec<&str> = ratings.leaderboard().iter().map(|(n, _)| *n).collect();
+        assert_eq!(names.len(), 3);
+        assert!(names.contains(&"bob"));
+    }
+
+    #[test]
+    fn upset_changes_ratings_more() {
+        let mut ratings = Ratings::default();
+        for _ in 0..3 {
+            ratings.record(&result(&[("alice", 1), ("bob", 2)]));
+        }
+        let alice_before = ratings.profile("alice").unwrap().rating;
+        let record = ratings.record(&result(&[("bob", 1), ("alice", 2)])).clone();
+
+        let alice_after = ratings.profile("alice").unwrap().rating;
+        assert!(alice_before - alice_after > 16.0);
+        assert_eq!(record.players[1].rating_before, alice_before);
+        assert_eq!(record.players[1].rating_after, alice_after);
+    }
+
+    #[test]
+    fn result_places_players_in_reverse_order_of_defeat() {
+        let text = "players 3\norigin 0 0\n1V  1S  1.  2.  2V  3.  3V\n";
+        let mut builder = parse(text).unwrap();
+        let pl: Vec<ID> = builder.players().iter().map(|p| p.id()).collect();
+        for (&id, name) in pl.iter().zip(["alice", "bob", "carol"].iter()) {
+            let profile = PlayerProfile {
+                name: name.to_string(),
+                color: "#000000".to_owned(),
+                controller: Controller::Human,
+            };
+            builder.set_profile(id, profile).unwrap();
+        }
+        let mut engine = builder.build().unwrap();
+
+        for &(src, dst) in [(1, 3), (3, 4), (4, 5)].iter() {
+            assert_eq!(MatchResult::from_engine(&engine), None);
+            let action = PlayerAction::MoveUnit {
+                src: Coord::new(src, 0),
+                dst: Coord::new(dst, 0),
+            };
+            engine.act(pl[0], action).unwrap();
+            engine.act(pl[0], PlayerAction::EndTurn).unwrap();
+            if engine.winner().is_none() {
+                engine.act(pl[2], PlayerAction::EndTurn).unwrap();
+            }
+        }
+
+        assert_eq!(engine.defeated_players(), &vec![vec![pl[1]], vec![pl[2]]]);
+        assert_eq!(
+            MatchResult::from_engine(&engine),
+            Some(result(&[("alice", 1), ("carol", 2), ("bob", 3)]))
+        );
+    }
+}
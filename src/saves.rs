@@ -6,7 +6,9 @@ use std::path::{Path, PathBuf};
 use chrono::prelude::*;
 use serde_yaml;
 
+use crate::campaign::CampaignProgress;
 use crate::game::GameEngine;
+use crate::ratings::Ratings;
 
 const VERSION: u8 = 1;
 
@@ -23,12 +25,19 @@ struct SavedGame {
     engine: GameEngine,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SavedCampaign {
+    info: SavedGameInfo,
+    progress: CampaignProgress,
+}
+
 #[derive(Clone, Debug)]
 pub struct SavedGamesCatalog {
     version: u8,
     root: PathBuf,
     prefix: String,
     saved_games: Vec<SavedGameInfo>,
+    saved_campaigns: Vec<SavedGameInfo>,
 }
 
 impl SavedGamesCatalog {
@@ -40,47 +49,13 @@ impl SavedGamesCatalog {
         if !root.is_dir() {
             return Err(io::Error::from(io::ErrorKind::InvalidInput));
         }
-        let mut saved_games = Vec::new();
-        for entry in fs::read_dir(root)? {
-            let path = entry?.path();
-            if path.is_file() {
-                let file_name = path.file_name().unwrap().to_str();
-                if file_name.is_none() {
-                    continue;
-                }
-                let file_name = file_name.unwrap();
-                if !file_name.starts_with(prefix) {
-                    continue;
-                }
-                let parts: Vec<_> = file_name.split('_').collect();
-                if parts.len() != 4 || parts[0] != prefix {
-                    continue;
-                }
-                let info_version: Result<u8, _> = parts[1].parse();
-                if info_version.is_err() {
-                    continue;
-                }
-                let info_version = info_version.unwrap();
-                if info_version != VERSION {
-                    continue;
-                }
-                let name = parts[2].to_string();
-                let timestamp: Result<DateTime<Utc>, _> =
-                    Utc.datetime_from_str(&parts[3], "%Y%m%d%H%M%S.yaml");
-                if timestamp.is_err() {
-                    continue;
-                }
-                saved_games.push(SavedGameInfo {
-                    name,
-                    timestamp: timestamp.unwrap(),
-                    version: info_version,
-                });
-            }
-        }
+        let saved_games = read_saved_games(root, prefix)?;
+        let saved_campaigns = read_saved_games(root, &campaign_prefix(prefix))?;
         info!("Successfully initiated saved games catalog with path {:?}, prefix {:?} and existing games {:?}",
             root, prefix, saved_games);
         Ok(SavedGamesCatalog {
             saved_games,
+            saved_campaigns,
             version: VERSION,
             prefix: prefix.to_owned(),
             root: root.to_owned(),
@@ -91,10 +66,14 @@ impl SavedGamesCatalog {
         &self.saved_games
     }
 
+    pub fn list_saved_campaigns(&self) -> &Vec<SavedGameInfo> {
+        &self.saved_campaigns
+    }
+
     pub fn save(&mut self, name: &str, engine: &GameEngine) -> io::Result<SavedGameInfo> {
         info!("Trying to save game as '{}'", name);
         let state = self.create_game_state(name, engine.clone());
-        let path = self.save_file_path(&state.info);
+        let path = self.save_file_path(&self.prefix, &state.info);
 
         let buffer = File::create(path.as_path()).unwrap();
         serde_yaml::to_writer(buffer, &state).unwrap();
@@ -109,10 +88,10 @@ impl SavedGamesCatalog {
         Ok(state.info)
     }
 
-    fn save_file_path(&self, saved_game: &SavedGameInfo) -> PathBuf {
+    fn save_file_path(&self, prefix: &str, saved_game: &SavedGameInfo) -> PathBuf {
         let file_name = format!(
             "{}_{}_{}_{}.yaml",
-            self.prefix,
+            prefix,
             saved_game.version,
             saved_game.name,
             saved_game.timestamp.format("%Y%m%d%H%M%S")
@@ -121,14 +100,17 @@ impl SavedGamesCatalog {
     }
 
     fn create_game_state(&self, name: &str, engine: GameEngine) -> SavedGame {
+        let info = self.create_info(name);
+        SavedGame { info, engine }
+    }
+
+    fn create_info(&self, name: &str) -> SavedGameInfo {
         let timestamp = Utc::now().with_nanosecond(0).unwrap();
-        let info = SavedGameInfo {
+        SavedGameInfo {
             timestamp,
             name: String::from(name),
             version: self.version,
-        };
-
-        SavedGame { info, engine }
+        }
     }
 
     pub fn load(&self, game: &SavedGameInfo) -> io::Result<GameEngine> {
@@ -137,11 +119,115 @@ impl SavedGamesCatalog {
             return Err(io::Error::from(io::ErrorKind::InvalidInput));
         }
 
-        let path = self.save_file_path(game);
+        let path = self.save_file_path(&self.prefix, game);
         let buffer = File::open(path)?;
         let mut state: SavedGame = serde_yaml::from_reader(buffer).unwrap();
 
         state.engine.repair();
         Ok(state.engine)
     }
+
+    /// Save progress of a campaign. Saved campaigns are listed apart from saved games, see
+    /// `list_saved_campaigns`.
+    pub fn save_campaign(
+        &mut self,
+        name: &str,
+        progress: &CampaignProgress,
+    ) -> io::Result<SavedGameInfo> {
+        info!("Trying to save campaign progress as '{}'", name);
+        let state = SavedCampaign {
+            info: self.create_info(name),
+            progress: progress.clone(),
+        };
+        let path = self.save_file_path(&campaign_prefix(&self.prefix), &state.info);
+
+        let buffer = File::create(path.as_path())?;
+        serde_yaml::to_writer(buffer, &state)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        info!(
+            "Successfully saved campaign progress '{:?}' to {:?}",
+            state.info,
+            path.as_path()
+        );
+        self.saved_campaigns.push(state.info.clone());
+
+        Ok(state.info)
+    }
+
+    pub fn load_campaign(&self, game: &SavedGameInfo) -> io::Result<CampaignProgress> {
+        if !self.saved_campaigns.contains(game) {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+
+        let path = self.save_file_path(&campaign_prefix(&self.prefix), game);
+        let buffer = File::open(path)?;
+        let state: SavedCampaign = serde_yaml::from_reader(buffer)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(state.progress)
+    }
+
+    fn ratings_file_path(&self) -> PathBuf {
+        self.root.join(format!("{}_ratings.yaml", self.prefix))
+    }
+
+    /// Load ratings of players and their match history that are kept with saved games. Ratings
+    /// are empty if none were saved yet.
+    pub fn load_ratings(&self) -> io::Result<Ratings> {
+        Ratings::load(self.ratings_file_path())
+    }
+
+    pub fn save_ratings(&self, ratings: &Ratings) -> io::Result<()> {
+        let path = self.ratings_file_path();
+        ratings.save(path.as_path())?;
+        info!("Successfully saved ratings to {:?}", path.as_path());
+        Ok(())
+    }
+}
+
+/// Saved campaigns are kept in files with their own prefix, so they are not listed as games
+fn campaign_prefix(prefix: &str) -> String {
+    format!("{}-campaign", prefix)
+}
+
+/// Return saved games or campaigns of the current version with provided prefix in a directory
+fn read_saved_games(root: &Path, prefix: &str) -> io::Result<Vec<SavedGameInfo>> {
+    let mut saved_games = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        if path.is_file() {
+            let file_name = path.file_name().unwrap().to_str();
+            if file_name.is_none() {
+                continue;
+            }
+            let file_name = file_name.unwrap();
+            if !file_name.starts_with(prefix) {
+                continue;
+            }
+            let parts: Vec<_> = file_name.split('_').collect();
+            if parts.len() != 4 || parts[0] != prefix {
+                continue;
+            }
+            let info_version: Result<u8, _> = parts[1].parse();
+            if info_version.is_err() {
+                continue;
+            }
+            let info_version = info_version.unwrap();
+            if info_version != VERSION {
+                continue;
+            }
+            let name = parts[2].to_string();
+            let timestamp: Result<DateTime<Utc>, _> =
+                Utc.datetime_from_str(&parts[3], "%Y%m%d%H%M%S.yaml");
+            if timestamp.is_err() {
+                continue;
+            }
+            saved_games.push(SavedGameInfo {
+                name,
+                timestamp: timestamp.unwrap(),
+                version: info_version,
+            });
+        }
+    }
+    Ok(saved_games)
 }
@@ -0,0 +1,401 @@
+//! Scenarios are puzzles played on a prepared game: one player has to reach objectives, like
+//! "capture the enemy capital in 2 turns" or "hold your capital for 5 turns", while all other
+//! players follow a script.
+//!
+//! Scenarios are stored in YAML files, see `Scenario::load` and `Scenario::save`.
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use serde_yaml;
+
+use crate::game::{Coord, GameEngine, PlayerAction, PlayerActionError, ID};
+
+/// Goal that the player of a scenario has to reach
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum Objective {
+    /// Own the tile with provided coordinate
+    CaptureTile(Coord),
+    /// Own the tile with provided coordinate during the whole scenario, losing it fails the
+    /// scenario
+    HoldTile(Coord),
+    /// Own at least provided number of tiles
+    ReachTiles(usize),
+    /// Stay in the game for provided number of turns
+    SurviveTurns(u32),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum ScenarioStatus {
+    #[default]
+    InProgress,
+    Succeeded,
+    Failed,
+}
+
+/// Action that a player who is not controlled by a human makes in their turn
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct ScriptedAction {
+    pub player_id: ID,
+    pub action: PlayerAction,
+}
+
+/// Game with objectives for one player and a script for all others
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Scenario {
+    engine: GameEngine,
+    player_id: ID,
+    objectives: Vec<Objective>,
+    /// Number of turns the player has to reach objectives in
+    #[serde(default)]
+    turn_limit: Option<u32>,
+    /// Actions of other players that are not made yet
+    #[serde(default)]
+    script: VecDeque<ScriptedAction>,
+    starting_turn: u32,
+    #[serde(default)]
+    status: ScenarioStatus,
+}
+
+impl Scenario {
+    /// Create a scenario where provided player has to reach all objectives. Other players only
+    /// end their turns until a script is set.
+    pub fn new(engine: GameEngine, player_id: ID, objectives: Vec<Objective>) -> Self {
+        let starting_turn = engine.current_turn();
+        Scenario {
+            engine,
+            player_id,
+            objectives,
+            turn_limit: None,
+            script: VecDeque::new(),
+            starting_turn,
+            status: ScenarioStatus::InProgress,
+        }
+    }
+
+    /// Read a scenario from a YAML file
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let buffer = File::open(path)?;
+        let mut scenario: Scenario = serde_yaml::from_reader(buffer)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        scenario.repair();
+        info!("Successfully loaded scenario with objectives {:?}", scenario.objectives);
+        Ok(scenario)
+    }
+
+    /// Fix all countable fields of the game, see `GameEngine::repair`
+    pub(crate) fn repair(&mut self) {
+        self.engine.repair();
+    }
+
+    /// Write the scenario to a YAML file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let buffer = File::create(path)?;
+        serde_yaml::to_writer(buffer, self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn engine(&self) -> &GameEngine {
+        &self.engine
+    }
+
+    pub fn player_id(&self) -> ID {
+        self.player_id
+    }
+
+    pub fn objectives(&self) -> &Vec<Objective> {
+        &self.objectives
+    }
+
+    pub fn turn_limit(&self) -> Option<u32> {
+        self.turn_limit
+    }
+
+    /// Limit the number of turns, including the current one, in which objectives can be reached
+    pub fn set_turn_limit(&mut self, turns: u32) {
+        self.turn_limit = Some(turns);
+    }
+
+    /// Set actions that other players make in their turns. Actions are made in the order of the
+    /// script: a turn of a player lasts while the next action is theirs, and their `EndTurn` or an
+    /// action of another player ends it. Actions of players who have lost are skipped.
+    pub fn set_script(&mut self, script: Vec<ScriptedAction>) {
+        self.script = script.into_iter().collect();
+    }
+
+    /// Give money to the player of the scenario, see `GameEngine::grant_money`
+    pub fn grant_money(&mut self, amount: i32) -> Option<ID> {
+        self.engine.grant_money(self.player_id, amount)
+    }
+
+    pub fn status(&self) -> ScenarioStatus {
+        self.status
+    }
+
+    /// Perform an action for the player of the scenario and let other players make their turns.
+    /// Returns the status of the scenario after all of that.
+    pub fn act(&mut self, action: PlayerAction) -> Result<ScenarioStatus, PlayerActionError> {
+        if self.status != ScenarioStatus::InProgress {
+            return Err(PlayerActionError::GameAlreadyFinished);
+        }
+        self.play_script();
+        if self.status != ScenarioStatus::InProgress {
+            return Err(PlayerActionError::GameAlreadyFinished);
+        }
+        self.engine.act(self.player_id, action)?;
+        self.status = self.evaluate();
+        self.play_script();
+        Ok(self.status)
+    }
+
+    /// Make turns of other players until it is the turn of the scenario player again
+    fn play_script(&mut self) {
+        while self.status == ScenarioStatus::InProgress
+            && self.engine.winner().is_none()
+            && self.engine.active_player().id() != self.player_id
+        {
+            while let Some(scripted) = self.script.front() {
+                if self.engine.is_player_active(scripted.player_id) {
+                    break;
+                }
+                self.script.pop_front();
+            }
+            let active_id = self.engine.active_player().id();
+            let action = match self.script.front() {
+                Some(scripted) if scripted.player_id == active_id => {
+                    self.script.pop_front().unwrap().action
+                }
+                _ => PlayerAction::EndTurn,
+            };
+            if let Err(e) = self.engine.act(active_id, action) {
+                info!("Scripted action {:?} was skipped: {}", action, e);
+            }
+            self.status = self.evaluate();
+        }
+    }
+
+    /// Check objectives of the scenario in the current state of the game
+    pub fn evaluate(&self) -> ScenarioStatus {
+        let engine = &self.engine;
+        if !engine.is_player_active(self.player_id) {
+            return ScenarioStatus::Failed;
+        }
+        if engine.winner().is_some() {
+            return if engine.winners().contains(&self.player_id) {
+                ScenarioStatus::Succeeded
+            } else {
+                ScenarioStatus::Failed
+            };
+        }
+
+        let turns_passed = engine.current_turn() - self.starting_turn;
+        let mut reached = true;
+        for objective in self.objectives.iter() {
+            match *objective {
+                Objective::CaptureTile(coordinate) => reached &= self.owns(coordinate),
+                Objective::HoldTile(coordinate) => {
+                    if !self.owns(coordinate) {
+                        return ScenarioStatus::Failed;
+                    }
+                }
+                Objective::ReachTiles(tiles) => reached &= self.tiles_num() >= tiles,
+                Objective::SurviveTurns(turns) => reached &= turns_passed >= turns,
+            }
+        }
+
+        if reached {
+            ScenarioStatus::Succeeded
+        } else if self.turn_limit.map(|t| turns_passed >= t) == Some(true) {
+            ScenarioStatus::Failed
+        } else {
+            ScenarioStatus::InProgress
+        }
+    }
+
+    fn owns(&self, coordinate: Coord) -> bool {
+        self.engine
+            .location()
+            .region_at(coordinate)
+            .map(|r| r.owner().id() == self.player_id)
+            == Some(true)
+    }
+
+    fn tiles_num(&self) -> usize {
+        self.engine
+            .location()
+            .regions()
+            .values()
+            .filter(|r| r.owner().id() == self.player_id)
+            .map(|r| r.coordinates().len())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Objective, Scenario, ScenarioStatus, ScriptedAction};
+    use crate::game::{Coord, PlayerAction, PlayerActionError, UnitType, ID};
+    use crate::init::ascii::parse;
+
+    fn create_scenario(text: &str, objectives: Vec<Objective>) -> Scenario {
+        let engine = parse(text).unwrap().build().unwrap();
+        let player_id = engine.players()[0].id();
+        Scenario::new(engine, player_id, objectives)
+    }
+
+    #[test]
+    fn capturing_tile_solves_scenario() {
+        let text = "players 2\norigin 0 0\n1V  1S  1.  2.  2V  2.\n";
+        let mut scenario = create_scenario(text, vec![Objective::CaptureTile(Coord::new(3, 0))]);
+        scenario.set_turn_limit(1);
+
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(3, 0),
+        };
+        assert_eq!(scenario.act(action), Ok(ScenarioStatus::Succeeded));
+        assert_eq!(
+            scenario.act(PlayerAction::EndTurn),
+            Err(PlayerActionError::GameAlreadyFinished)
+        );
+    }
+
+    #[test]
+    fn scenario_fails_when_turns_are_over() {
+        let text = "players 2\norigin 0 0\n1V  1S  1.  2.  2V  2.\n";
+        let mut scenario = create_scenario(text, vec![Objective::CaptureTile(Coord::new(3, 0))]);
+        scenario.set_turn_limit(2);
+
+        assert_eq!(
+            scenario.act(PlayerAction::EndTurn),
+            Ok(ScenarioStatus::InProgress)
+        );
+        assert_eq!(scenario.engine().current_turn(), 2);
+        assert_eq!(
+            scenario.act(PlayerAction::EndTurn),
+            Ok(ScenarioStatus::Failed)
+        );
+    }
+
+    #[test]
+    fn scripted_opponent_takes_held_tile() {
+        let text = "players 2\norigin 0 0\n1V  1.  1.  2S  2V  2.\n";
+        let mut scenario = create_scenario(
+            text,
+            vec![
+                Objective::HoldTile(Coord::new(2, 0)),
+                Objective::SurviveTurns(2),
+            ],
+        );
+        let opponent_id = scenario.engine().players()[1].id();
+        scenario.set_script(vec![ScriptedAction {
+            player_id: opponent_id,
+            action: PlayerAction::MoveUnit {
+                src: Coord::new(3, 0),
+                dst: Coord::new(2, 0),
+            },
+        }]);
+
+        assert_eq!(
+            scenario.act(PlayerAction::EndTurn),
+            Ok(ScenarioStatus::Failed)
+        );
+        assert_eq!(
+            scenario
+                .engine()
+                .location()
+                .region_at(Coord::new(2, 0))
+                .unwrap()
+                .owner()
+                .id(),
+            opponent_id
+        );
+    }
+
+    #[test]
+    fn scenario_finished_by_script_before_player_turn_rejects_action() {
+        let text = "players 2\norigin 0 0\n1V  1S  1.  2.  2V  2.\n";
+        let engine = parse(text).unwrap().build().unwrap();
+        let (opponent_id, player_id) = (engine.players()[0].id(), engine.players()[1].id());
+        let objectives = vec![Objective::HoldTile(Coord::new(3, 0))];
+        let mut scenario = Scenario::new(engine, player_id, objectives);
+        scenario.set_script(vec![ScriptedAction {
+            player_id: opponent_id,
+            action: PlayerAction::MoveUnit {
+                src: Coord::new(1, 0),
+                dst: Coord::new(3, 0),
+            },
+        }]);
+
+        assert_eq!(
+            scenario.act(PlayerAction::EndTurn),
+            Err(PlayerActionError::GameAlreadyFinished)
+        );
+        assert_eq!(scenario.status(), ScenarioStatus::Failed);
+    }
+
+    #[test]
+    fn script_skips_actions_of_defeated_players() {
+        let text = "players 3\norigin 0 0\n1V  1S  2.  2V  3.  3S  3V\n";
+        let mut scenario = create_scenario(text, vec![Objective::SurviveTurns(5)]);
+        let players: Vec<ID> = scenario.engine().players().iter().map(|p| p.id()).collect();
+        scenario.set_script(vec![
+            ScriptedAction {
+                player_id: players[1],
+                action: PlayerAction::EndTurn,
+            },
+            ScriptedAction {
+                player_id: players[2],
+                action: PlayerAction::MoveUnit {
+                    src: Coord::new(5, 0),
+                    dst: Coord::new(4, 0),
+                },
+            },
+        ]);
+
+        let action = PlayerAction::MoveUnit {
+            src: Coord::new(1, 0),
+            dst: Coord::new(2, 0),
+        };
+        assert_eq!(scenario.act(action), Ok(ScenarioStatus::InProgress));
+        assert!(!scenario.engine().is_player_active(players[1]));
+        assert_eq!(
+            scenario.act(PlayerAction::EndTurn),
+            Ok(ScenarioStatus::InProgress)
+        );
+        assert_eq!(
+            scenario
+                .engine()
+                .location()
+                .tile_at(Coord::new(4, 0))
+                .unwrap()
+                .unit()
+                .map(|u| u.unit_type()),
+            Some(UnitType::Soldier)
+        );
+    }
+
+    #[test]
+    fn holding_tile_for_several_turns_solves_scenario() {
+        let text = "players 2\norigin 0 0\n1V  1.  1.  2.  2V  2.\n";
+        let mut scenario = create_scenario(
+            text,
+            vec![
+                Objective::HoldTile(Coord::new(0, 0)),
+                Objective::SurviveTurns(2),
+                Objective::ReachTiles(3),
+            ],
+        );
+
+        assert_eq!(
+            scenario.act(PlayerAction::EndTurn),
+            Ok(ScenarioStatus::InProgress)
+        );
+        assert_eq!(
+            scenario.act(PlayerAction::EndTurn),
+            Ok(ScenarioStatus::Succeeded)
+        );
+    }
+}
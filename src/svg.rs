@@ -0,0 +1,336 @@
+//! Rendering of game positions into SVG pictures for bug reports and docs.
+//!
+//! Hexes have pointy tops and rows of the same `y` go from left to right, just like in the
+//! terminal client. The output only depends on the position, so it can be used in snapshot tests.
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::game::{Coord, GameEngine, Location, UnitType, DEFAULT_PLAYER_COLORS, ID};
+use crate::init::ascii::{surface_char, unit_char};
+
+const PLAYER_COLORS: [&str; 6] = DEFAULT_PLAYER_COLORS;
+const WATER_COLOR: &str = "#3b6ea5";
+const NEUTRAL_COLOR: &str = "#9e9e9e";
+const BORDER_COLOR: &str = "#202020";
+const CAPITAL_COLOR: &str = "#ffd700";
+
+/// Offsets of neighbours in the same order as edges of a hex, starting from the right one and
+/// going clockwise
+const NEIGHBOUR_OFFSETS: [(i32, i32); 6] = [(1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SvgOptions {
+    /// Distance from the center of a hex to its corners, in pixels
+    pub hex_size: f64,
+    /// Draw axial coordinates on each hex
+    pub coordinate_labels: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            hex_size: 20.0,
+            coordinate_labels: false,
+        }
+    }
+}
+
+/// Round a value to the precision of the output, so there are no negative zeros in it
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0 + 0.0
+}
+
+/// Return a center of a hex with provided coordinate
+fn center(coordinate: Coord, size: f64) -> (f64, f64) {
+    let x = size * 3f64.sqrt() * (f64::from(coordinate.x) + f64::from(coordinate.y) / 2.0);
+    let y = size * 1.5 * f64::from(coordinate.y);
+    (round(x), round(y))
+}
+
+/// Return a corner of a hex. Corners are numbered clockwise starting from the upper right one,
+/// so edge `i` lies between corners `i` and `i + 1`.
+fn corner(center: (f64, f64), size: f64, i: usize) -> (f64, f64) {
+    let angle = (60.0 * i as f64 - 30.0).to_radians();
+    (
+        round(center.0 + size * angle.cos()),
+        round(center.1 + size * angle.sin()),
+    )
+}
+
+/// Render a position of the game. Players are colored with colors of their profiles, which are
+/// always in the `#rrggbb` form, see `GameEngine::set_profiles`.
+pub fn render_engine(engine: &GameEngine, options: &SvgOptions) -> String {
+    let colors: HashMap<ID, &str> = engine
+        .players()
+        .iter()
+        .map(|p| (p.id(), p.profile().color.as_str()))
+        .collect();
+    render(engine.location(), &colors, options)
+}
+
+/// Render a location. Owners are colored in the order of their ids, land of the neutral owner is
+/// gray.
+pub fn render_location(location: &Location, options: &SvgOptions) -> String {
+    let mut owners: Vec<ID> = location
+        .regions()
+        .values()
+        .filter(|r| !r.owner().is_neutral())
+        .map(|r| r.owner().id())
+        .collect();
+    owners.sort();
+    owners.dedup();
+    let colors: HashMap<ID, &str> = owners
+        .iter()
+        .enumerate()
+        .map(|(i, &id)| (id, PLAYER_COLORS[i % PLAYER_COLORS.len()]))
+        .collect();
+    render(location, &colors, options)
+}
+
+fn render(location: &Location, colors: &HashMap<ID, &str>, options: &SvgOptions) -> String {
+    let size = options.hex_size;
+
+    // Hexes are drawn row by row so the output does not depend on the order of the map
+    let mut coordinates: Vec<Coord> = location.map().keys().cloned().collect();
+    coordinates.sort_by_key(|c| (c.y, c.x));
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (0f64, 0f64, 0f64, 0f64);
+    if let Some(&first) = coordinates.first() {
+        let (x, y) = center(first, size);
+        min_x = x;
+        max_x = x;
+        min_y = y;
+        max_y = y;
+    }
+    for &c in coordinates.iter() {
+        let (x, y) = center(c, size);
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+    let margin = size * 1.5;
+    let (left, top) = (min_x - margin, min_y - margin);
+    let (width, height) = (max_x - min_x + 2.0 * margin, max_y - min_y + 2.0 * margin);
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" \
+         viewBox=\"{:.2} {:.2} {:.2} {:.2}\">",
+        width.ceil(),
+        height.ceil(),
+        left,
+        top,
+        width,
+        height
+    ).unwrap();
+
+    out.push_str("<g class=\"tiles\" stroke=\"#606060\" stroke-width=\"1\">\n");
+    for &c in coordinates.iter() {
+        let tile = location.tile_at(c).unwrap();
+        let fill = if tile.surface().is_water() {
+            WATER_COLOR
+        } else {
+            location
+                .region_at(c)
+                .and_then(|r| colors.get(&r.owner().id()))
+                .cloned()
+                .unwrap_or(NEUTRAL_COLOR)
+        };
+        let points: Vec<String> = (0..6)
+            .map(|i| corner(center(c, size), size, i))
+            .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+            .collect();
+        writeln!(
+            out,
+            "<polygon points=\"{}\" fill=\"{}\"/>",
+            points.join(" "),
+            fill
+        ).unwrap();
+        // Mountains and marshes are marked in the upper part of a hex to leave room for units
+        if let Some(mark) = surface_char(*tile.surface()) {
+            let (x, y) = center(c, size);
+            writeln!(
+                out,
+                "<text class=\"terrain\" x=\"{:.2}\" y=\"{:.2}\" font-family=\"monospace\" \
+                 font-size=\"{:.2}\" text-anchor=\"middle\">{}</text>",
+                x,
+                y - size * 0.45,
+                size / 2.0,
+                mark
+            ).unwrap();
+        }
+    }
+    out.push_str("</g>\n");
+
+    out.push_str(&format!(
+        "<g class=\"borders\" stroke=\"{}\" stroke-width=\"{:.2}\" stroke-linecap=\"round\">\n",
+        BORDER_COLOR,
+        size / 8.0
+    ));
+    for &c in coordinates.iter() {
+        let region = match location.region_at(c) {
+            Some(region) => region,
+            None => continue,
+        };
+        for (i, &(dx, dy)) in NEIGHBOUR_OFFSETS.iter().enumerate() {
+            let neighbour = Coord::new(c.x + dx, c.y + dy);
+            if region.coordinates().contains(&neighbour) {
+                continue;
+            }
+            let (x1, y1) = corner(center(c, size), size, i);
+            let (x2, y2) = corner(center(c, size), size, (i + 1) % 6);
+            writeln!(
+                out,
+                "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>",
+                x1, y1, x2, y2
+            ).unwrap();
+        }
+    }
+    out.push_str("</g>\n");
+
+    out.push_str(&format!(
+        "<g class=\"units\" font-family=\"monospace\" font-size=\"{:.2}\" \
+         font-weight=\"bold\" text-anchor=\"middle\" dominant-baseline=\"central\">\n",
+        size
+    ));
+    for &c in coordinates.iter() {
+        let unit = match location.tile_at(c).unwrap().unit() {
+            Some(unit) => unit,
+            None => continue,
+        };
+        let (x, y) = center(c, size);
+        if unit.unit_type() == UnitType::Village {
+            writeln!(
+                out,
+                "<circle class=\"capital\" cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" fill=\"{}\"/>",
+                x,
+                y,
+                size * 0.6,
+                CAPITAL_COLOR
+            ).unwrap();
+        }
+        writeln!(
+            out,
+            "<text x=\"{:.2}\" y=\"{:.2}\">{}</text>",
+            x,
+            y,
+            unit_char(unit.unit_type())
+        ).unwrap();
+    }
+    out.push_str("</g>\n");
+
+    if options.coordinate_labels {
+        out.push_str(&format!(
+            "<g class=\"labels\" font-family=\"sans-serif\" font-size=\"{:.2}\" \
+             text-anchor=\"middle\">\n",
+            size / 3.0
+        ));
+        for &c in coordinates.iter() {
+            let (x, y) = center(c, size);
+            writeln!(
+                out,
+                "<text x=\"{:.2}\" y=\"{:.2}\">{},{}</text>",
+                x,
+                y + size * 0.75,
+                c.x,
+                c.y
+            ).unwrap();
+        }
+        out.push_str("</g>\n");
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::{render_engine, render_location, SvgOptions};
+    use crate::game::test_util::create_valid_engine;
+    use crate::game::UnitType;
+    use crate::init::ascii::parse_location;
+
+    #[test]
+    fn check_output_is_deterministic() {
+        let (_, _, engine) = create_valid_engine();
+        let copy = engine.clone();
+
+        let one = render_engine(&engine, &SvgOptions::default());
+        let two = render_engine(&copy, &SvgOptions::default());
+
+        assert_eq!(one, two);
+        assert!(one.starts_with("<svg "));
+        assert!(one.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn check_all_tiles_and_units_are_drawn() {
+        let (_, _, engine) = create_valid_engine();
+        let svg = render_engine(&engine, &SvgOptions::default());
+
+        let units: Vec<UnitType> = engine
+            .location()
+            .map()
+            .values()
+            .filter_map(|t| t.unit().map(|u| u.unit_type()))
+            .collect();
+        let capitals = units.iter().filter(|&&u| u == UnitType::Village).count();
+
+        assert_eq!(svg.matches("<polygon ").count(), engine.location().map().len());
+        assert_eq!(svg.matches("<text ").count(), units.len());
+        assert_eq!(svg.matches("class=\"capital\"").count(), capitals);
+    }
+
+    #[test]
+    fn check_players_have_different_colors() {
+        let (pl, _, engine) = create_valid_engine();
+        let svg = render_engine(&engine, &SvgOptions::default());
+
+        assert_eq!(pl.len(), 3);
+        for color in super::PLAYER_COLORS.iter().take(3) {
+            assert!(svg.contains(color));
+        }
+        assert!(!svg.contains(super::PLAYER_COLORS[3]));
+    }
+
+    #[test]
+    fn check_coordinate_labels_are_optional() {
+        let (_, _, engine) = create_valid_engine();
+        let options = SvgOptions {
+            coordinate_labels: true,
+            ..SvgOptions::default()
+        };
+
+        let without_labels = render_location(engine.location(), &SvgOptions::default());
+        let with_labels = render_location(engine.location(), &options);
+
+        assert!(!without_labels.contains("class=\"labels\""));
+        assert!(with_labels.contains("class=\"labels\""));
+        assert!(with_labels.contains(">-2,1</text>"));
+    }
+
+    #[test]
+    fn check_terrain_is_marked() {
+        let text = "players 2\norigin 0 0\n1V  1.^ 2.\n  1S  2., 2V\n";
+        let location = parse_location(text).unwrap();
+        let svg = render_location(&location, &SvgOptions::default());
+
+        assert_eq!(svg.matches("class=\"terrain\"").count(), 2);
+        assert!(svg.contains(">^</text>"));
+        assert!(svg.contains(">,</text>"));
+    }
+
+    #[test]
+    fn check_neutral_land_has_no_player_color() {
+        let text = "players 2\norigin 0 0\n1V  1.  0.  0S  2.  2V\n";
+        let location = parse_location(text).unwrap();
+        let svg = render_location(&location, &SvgOptions::default());
+
+        assert!(svg.contains(super::NEUTRAL_COLOR));
+        assert!(svg.contains(super::PLAYER_COLORS[0]));
+        assert!(svg.contains(super::PLAYER_COLORS[1]));
+        assert!(!svg.contains(super::PLAYER_COLORS[2]));
+    }
+}
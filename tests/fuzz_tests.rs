@@ -0,0 +1,252 @@
+//! Property tests that play random actions on random maps and check that the engine never panics
+//! and always stays valid. Failing cases are shrunk to the smallest map and the shortest list of
+//! steps, and if the engine becomes invalid, the actions made by the steps are printed.
+//!
+//! Run more cases with `PROPTEST_CASES=10000 cargo test --test fuzz_tests`.
+extern crate proptest;
+extern crate yasc;
+
+use proptest::prelude::*;
+
+use yasc::bots::{Bot, GreedyBot};
+use yasc::game::{Coord, GameEngine, Player, PlayerAction, RulesVariant, TileSurface, UnitType, ID};
+use yasc::init::builder::GameEngineBuilder;
+
+/// Radius of generated maps, the map of this radius has 37 tiles
+const MAP_RADIUS: u32 = 3;
+const MAP_SIZE: usize = 37;
+/// The longest distance between source and destination of moves and shots
+const ACTION_RANGE: i32 = 2;
+
+const UNIT_TYPES: [UnitType; 14] = [
+    UnitType::Grave,
+    UnitType::PineTree,
+    UnitType::PalmTree,
+    UnitType::Village,
+    UnitType::Tower,
+    UnitType::GreatKnight,
+    UnitType::Knight,
+    UnitType::Soldier,
+    UnitType::Militia,
+    UnitType::Bridge,
+    UnitType::Boat,
+    UnitType::Castle,
+    UnitType::Archer,
+    UnitType::Engineer,
+];
+
+#[derive(Clone, Debug)]
+struct TileSpec {
+    surface: TileSurface,
+    /// Index of the owner: `0` is nobody, `1` is the neutral owner and others are players
+    owner: usize,
+    unit: Option<UnitType>,
+}
+
+#[derive(Clone, Debug)]
+struct MapSpec {
+    players_num: u8,
+    rules_variant: RulesVariant,
+    tiles: Vec<TileSpec>,
+}
+
+/// One step of a game. Indices are taken modulo the number of things they choose from, so any
+/// step can be applied to any state of any game.
+#[derive(Clone, Debug)]
+enum Step {
+    /// Move a unit to a tile in `ACTION_RANGE`
+    Move { src: usize, dst: usize },
+    /// Shoot at a tile in `ACTION_RANGE`
+    Shoot { src: usize, dst: usize },
+    /// Buy a unit with money of a region of the player and place it in or next to the region
+    Buy { region: usize, unit: usize, dst: usize },
+    Upgrade { src: usize },
+    ProposeTruce { player: usize, turns: u32 },
+    AcceptTruce { player: usize },
+    BreakTruce { player: usize },
+    EndTurn,
+    Resign,
+    /// Make the action chosen by the greedy bot, so games go further than random moves take them
+    Greedy,
+    /// End a turn on behalf of a player who may be not active
+    OutOfTurn { player: usize },
+}
+
+fn tile_strategy() -> impl Strategy<Value = TileSpec> {
+    let surface = prop_oneof![
+        6 => Just(TileSurface::Land),
+        1 => Just(TileSurface::Mountain),
+        1 => Just(TileSurface::Marsh),
+        1 => Just(TileSurface::Water),
+    ];
+    let owner = prop_oneof![1 => 0..2usize, 8 => 2..6usize];
+    let unit = prop_oneof![
+        6 => Just(None),
+        1 => (0..UNIT_TYPES.len()).prop_map(|i| Some(UNIT_TYPES[i])),
+    ];
+    (surface, owner, unit).prop_map(|(surface, owner, unit)| TileSpec {
+        surface,
+        owner,
+        unit,
+    })
+}
+
+fn map_strategy() -> impl Strategy<Value = MapSpec> {
+    let rules_variant = prop_oneof![
+        Just(RulesVariant::Continent),
+        Just(RulesVariant::Archipelago)
+    ];
+    let tiles = prop::collection::vec(tile_strategy(), MAP_SIZE);
+    (2..5u8, rules_variant, tiles).prop_map(|(players_num, rules_variant, tiles)| MapSpec {
+        players_num,
+        rules_variant,
+        tiles,
+    })
+}
+
+fn step_strategy() -> impl Strategy<Value = Step> {
+    let index = || 0..64usize;
+    prop_oneof![
+        3 => (index(), index()).prop_map(|(src, dst)| Step::Move { src, dst }),
+        1 => (index(), index()).prop_map(|(src, dst)| Step::Shoot { src, dst }),
+        2 => (index(), index(), index()).prop_map(|(region, unit, dst)| Step::Buy {
+            region,
+            unit,
+            dst
+        }),
+        1 => index().prop_map(|src| Step::Upgrade { src }),
+        1 => (index(), 0..4u32).prop_map(|(player, turns)| Step::ProposeTruce { player, turns }),
+        1 => index().prop_map(|player| Step::AcceptTruce { player }),
+        1 => index().prop_map(|player| Step::BreakTruce { player }),
+        2 => Just(Step::EndTurn),
+        1 => Just(Step::Resign),
+        6 => Just(Step::Greedy),
+        1 => index().prop_map(|player| Step::OutOfTurn { player }),
+    ]
+}
+
+/// Build an engine from the spec, or return `None` if the map cannot be played
+fn build_engine(spec: &MapSpec) -> Option<GameEngine> {
+    let mut builder = GameEngineBuilder::circle(MAP_RADIUS, spec.players_num).ok()?;
+    builder.set_rules_variant(spec.rules_variant);
+    let player_ids: Vec<ID> = builder.players().iter().map(|p| p.id()).collect();
+    let mut coordinates: Vec<Coord> = builder.map().keys().cloned().collect();
+    coordinates.sort();
+
+    for (&c, tile) in coordinates.iter().zip(spec.tiles.iter()) {
+        builder.set_surface(c, tile.surface).ok()?;
+        // Units that do not fit the surface are just skipped, as well as owners of water
+        if let Some(unit_type) = tile.unit {
+            let _ = builder.place_unit(c, unit_type);
+        }
+        let owner = match tile.owner {
+            0 => continue,
+            1 => Player::neutral().id(),
+            n => player_ids[(n - 2) % player_ids.len()],
+        };
+        let _ = builder.set_owner(c, owner);
+    }
+    builder.build().ok()
+}
+
+fn sorted_coordinates(engine: &GameEngine) -> Vec<Coord> {
+    let mut coordinates: Vec<Coord> = engine.location().map().keys().cloned().collect();
+    coordinates.sort();
+    coordinates
+}
+
+/// Return a tile of the map and a tile in `ACTION_RANGE` from it
+fn pick_pair(engine: &GameEngine, src: usize, dst: usize) -> (Coord, Coord) {
+    let coordinates = sorted_coordinates(engine);
+    let src = coordinates[src % coordinates.len()];
+    let near: Vec<Coord> = coordinates
+        .into_iter()
+        .filter(|&c| c.distance(src) <= ACTION_RANGE)
+        .collect();
+    (src, near[dst % near.len()])
+}
+
+/// Turn a step into an action of some player for the current state of the game
+fn to_action(engine: &GameEngine, step: &Step) -> (ID, PlayerAction) {
+    let active_id = engine.active_player().id();
+    let player_ids: Vec<ID> = engine.players().iter().map(|p| p.id()).collect();
+    let other = |i: usize| player_ids[i % player_ids.len()];
+
+    let action = match *step {
+        Step::Move { src, dst } => {
+            let (src, dst) = pick_pair(engine, src, dst);
+            PlayerAction::MoveUnit { src, dst }
+        }
+        Step::Shoot { src, dst } => {
+            let (src, dst) = pick_pair(engine, src, dst);
+            PlayerAction::Shoot { src, dst }
+        }
+        Step::Buy { region, unit, dst } => {
+            let mut regions: Vec<_> = engine
+                .location()
+                .regions()
+                .values()
+                .filter(|r| r.owner().id() == active_id)
+                .collect();
+            if regions.is_empty() {
+                return (active_id, PlayerAction::EndTurn);
+            }
+            regions.sort_by_key(|r| r.id());
+            let region = regions[region % regions.len()];
+            let mut places: Vec<Coord> = region
+                .coordinates()
+                .iter()
+                .flat_map(|c| {
+                    let mut places = c.neighbors().to_vec();
+                    places.push(*c);
+                    places
+                }).filter(|c| engine.location().tile_at(*c).is_some())
+                .collect();
+            places.sort();
+            places.dedup();
+            let unit_type = UNIT_TYPES[unit % UNIT_TYPES.len()];
+            PlayerAction::PlaceNewUnit(region.id(), unit_type, places[dst % places.len()])
+        }
+        Step::Upgrade { src } => {
+            let coordinates = sorted_coordinates(engine);
+            PlayerAction::UpgradeUnit(coordinates[src % coordinates.len()])
+        }
+        Step::ProposeTruce { player, turns } => PlayerAction::ProposeTruce {
+            player_id: other(player),
+            turns,
+        },
+        Step::AcceptTruce { player } => PlayerAction::AcceptTruce(other(player)),
+        Step::BreakTruce { player } => PlayerAction::BreakTruce(other(player)),
+        Step::EndTurn => PlayerAction::EndTurn,
+        Step::Resign => PlayerAction::Resign,
+        Step::Greedy => GreedyBot::new().next_action(&engine.view_for(active_id)),
+        Step::OutOfTurn { player } => return (other(player), PlayerAction::EndTurn),
+    };
+    (active_id, action)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        max_shrink_iters: 2048,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn check_engine_stays_valid_after_any_actions(
+        spec in map_strategy().prop_filter("map cannot be played", |s| build_engine(s).is_some()),
+        steps in prop::collection::vec(step_strategy(), 0..80),
+    ) {
+        let mut engine = build_engine(&spec).unwrap();
+        let mut made = Vec::new();
+        for step in steps.iter() {
+            if engine.winner().is_some() {
+                break;
+            }
+            let (player_id, action) = to_action(&engine, step);
+            made.push((player_id, action));
+            // Refused actions are fine, but the engine must not panic and must stay valid
+            let _ = engine.act(player_id, action);
+            prop_assert_eq!(engine.validate(), Ok(()), "after actions {:?}", made);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+extern crate serde_json;
+extern crate yasc;
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread::{self, JoinHandle};
+
+use yasc::game::{
+    Coord, GameEngine, GameEvent, ManualTimeSource, PlayerAction, PlayerActionError, TimeControl,
+    UnitType,
+};
+use yasc::init::ascii::parse;
+use yasc::net::{Client, ClientMessage, GameState, Server, ServerMessage};
+
+const MAP: &str = "players 2
+origin 0 0
+1V  1.  1S  2.
+  1.  2.  2V  2.
+";
+
+fn new_engine() -> GameEngine {
+    parse(MAP).unwrap().build().unwrap()
+}
+
+fn start_server() -> (SocketAddr, JoinHandle<GameEngine>) {
+    let server = Server::bind("127.0.0.1:0", new_engine()).unwrap();
+    let addr = server.local_addr().unwrap();
+    let handle = thread::spawn(move || server.run().unwrap());
+    (addr, handle)
+}
+
+fn start_server_with(
+    engine: GameEngine,
+    time: ManualTimeSource,
+) -> (SocketAddr, JoinHandle<GameEngine>) {
+    let server = Server::bind_with_time("127.0.0.1:0", engine, time).unwrap();
+    let addr = server.local_addr().unwrap();
+    let handle = thread::spawn(move || server.run().unwrap());
+    (addr, handle)
+}
+
+/// Open a connection that gets the first player, the game starts when the others connect
+fn connect_first(addr: SocketAddr) -> JoinHandle<Client> {
+    let stream = TcpStream::connect(addr).unwrap();
+    thread::spawn(move || Client::from_stream(stream).unwrap())
+}
+
+fn connect_all(addr: SocketAddr) -> (Client, Client) {
+    let first = connect_first(addr);
+    let second = Client::connect(addr).unwrap();
+    (first.join().unwrap(), second)
+}
+
+#[test]
+fn check_clients_get_different_players() {
+    let (addr, server) = start_server();
+    let (first, second) = connect_all(addr);
+    let engine = new_engine();
+
+    assert_eq!(first.player_id(), engine.players()[0].id());
+    assert_eq!(second.player_id(), engine.players()[1].id());
+    assert_eq!(first.players(), second.players());
+    assert_eq!(
+        first.state(),
+        &GameState::new(&engine.view_for(first.player_id()))
+    );
+    assert_eq!(
+        second.state(),
+        &GameState::new(&engine.view_for(second.player_id()))
+    );
+    assert!(first.is_my_turn());
+    assert!(!second.is_my_turn());
+
+    drop(first);
+    drop(second);
+    server.join().unwrap();
+}
+
+#[test]
+fn check_actions_are_validated_and_broadcasted() {
+    let (addr, server) = start_server();
+    let (mut first, mut second) = connect_all(addr);
+    let mut engine = new_engine();
+
+    assert_eq!(
+        second.act(PlayerAction::EndTurn).unwrap(),
+        Err(PlayerActionError::OtherPlayersTurn(first.player_id()))
+    );
+
+    let attack = PlayerAction::MoveUnit {
+        src: Coord::new(2, 0),
+        dst: Coord::new(3, 0),
+    };
+    assert_eq!(first.act(attack).unwrap(), Ok(()));
+    assert_eq!(first.act(PlayerAction::EndTurn).unwrap(), Ok(()));
+    second.wait_for_turn().unwrap();
+
+    engine.act(first.player_id(), attack).unwrap();
+    engine
+        .act(first.player_id(), PlayerAction::EndTurn)
+        .unwrap();
+    let expected = GameState::new(&engine.view_for(first.player_id()));
+    assert_eq!(first.state(), &expected);
+    assert_eq!(
+        second.state(),
+        &GameState::new(&engine.view_for(second.player_id()))
+    );
+    assert_eq!(
+        second.state().tile_at(Coord::new(3, 0)).unwrap().unit,
+        Some(UnitType::Soldier)
+    );
+
+    drop(first);
+    drop(second);
+    let final_engine = server.join().unwrap();
+    let final_view = final_engine.view_for(final_engine.players()[0].id());
+    assert_eq!(GameState::new(&final_view).tiles, expected.tiles);
+}
+
+#[test]
+fn check_money_of_enemy_regions_is_hidden() {
+    let (addr, server) = start_server();
+    let (first, second) = connect_all(addr);
+
+    let state = first.state();
+    let own_region = state.tile_at(Coord::new(0, 0)).unwrap().region.unwrap();
+    let enemy_region = state.tile_at(Coord::new(3, 0)).unwrap().region.unwrap();
+    assert!(state.region(own_region).unwrap().money.is_some());
+    assert_eq!(state.region(enemy_region).unwrap().money, None);
+    assert!(second.state().region(enemy_region).unwrap().money.is_some());
+
+    drop(first);
+    drop(second);
+    server.join().unwrap();
+}
+
+#[test]
+fn check_turns_of_disconnected_players_are_skipped() {
+    let (addr, server) = start_server();
+    let (first, mut second) = connect_all(addr);
+
+    drop(first);
+    second.wait_for_turn().unwrap();
+    assert!(second.is_my_turn());
+    assert_eq!(second.act(PlayerAction::EndTurn).unwrap(), Ok(()));
+    second.wait_for_turn().unwrap();
+    assert_eq!(second.state().turn, 2);
+
+    drop(second);
+    server.join().unwrap();
+}
+
+#[test]
+fn check_turns_of_players_out_of_time_are_ended() {
+    let mut engine = new_engine();
+    engine.set_time_control(Some(TimeControl::PerTurn { limit: 100 }));
+    let time = ManualTimeSource::new(0);
+    let (addr, server) = start_server_with(engine, time.clone());
+    let (mut first, mut second) = connect_all(addr);
+
+    // The turn is ended before the late action, so the action is rejected
+    time.advance(100);
+    assert_eq!(
+        first.act(PlayerAction::EndTurn).unwrap(),
+        Err(PlayerActionError::OtherPlayersTurn(second.player_id()))
+    );
+    match second.receive().unwrap() {
+        ServerMessage::Update {
+            player_id,
+            action,
+            request,
+            diff,
+        } => {
+            assert_eq!(player_id, first.player_id());
+            assert_eq!(action, PlayerAction::EndTurn);
+            assert_eq!(request, None);
+            assert!(diff.events.contains(&GameEvent::TurnTimedOut(player_id)));
+        }
+        message => panic!("Unexpected message {:?}", message),
+    }
+    assert!(!first.is_my_turn());
+    assert!(second.is_my_turn());
+
+    // The second player has the whole limit for their own turn
+    time.advance(99);
+    assert_eq!(second.act(PlayerAction::EndTurn).unwrap(), Ok(()));
+    first.wait_for_turn().unwrap();
+    assert_eq!(first.state().turn, 2);
+
+    drop(first);
+    drop(second);
+    let final_engine = server.join().unwrap();
+    assert_eq!(final_engine.current_turn(), 2);
+}
+
+#[test]
+fn check_malformed_messages_are_reported() {
+    let (addr, server) = start_server();
+    let first = connect_first(addr);
+    let mut raw = TcpStream::connect(addr).unwrap();
+    let mut reader = BufReader::new(raw.try_clone().unwrap());
+    let first = first.join().unwrap();
+
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert!(line.starts_with("{\"type\":\"Welcome\""));
+
+    for malformed in ["{\"type\":\"Dance\"}\n", "{\"type\":\"Act\",\"data\":{\n"].iter() {
+        raw.write_all(malformed.as_bytes()).unwrap();
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        match serde_json::from_str(&line).unwrap() {
+            ServerMessage::Error(_) => (),
+            message => panic!("Unexpected message {:?}", message),
+        }
+    }
+
+    // Answers carry the number of the request
+    let act = ClientMessage::Act {
+        request: 7,
+        action: PlayerAction::EndTurn,
+    };
+    raw.write_all(format!("{}\n", serde_json::to_string(&act).unwrap()).as_bytes())
+        .unwrap();
+    line.clear();
+    reader.read_line(&mut line).unwrap();
+    match serde_json::from_str(&line).unwrap() {
+        ServerMessage::Rejected { request, .. } => assert_eq!(request, 7),
+        message => panic!("Unexpected message {:?}", message),
+    }
+
+    drop(first);
+    drop(raw);
+    drop(reader);
+    server.join().unwrap();
+}
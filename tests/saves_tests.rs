@@ -8,6 +8,12 @@ use chrono::prelude::*;
 use chrono::Duration;
 
 use yasc::game::test_util::create_valid_engine;
+use yasc::game::{
+    Controller, Coord, GameEngine, ManualTimeSource, PlayerAction, PlayerProfile, RulesVariant,
+    TimeControl, UnitType,
+};
+use yasc::init::ascii::parse;
+use yasc::ratings::{MatchResult, Ratings};
 use yasc::saves::SavedGamesCatalog;
 
 #[test]
@@ -77,3 +83,148 @@ fn check_saved_engine_is_recoverable_through_new_catalog() {
     assert!(loaded_engine.is_ok());
     assert_eq!(loaded_engine.unwrap(), engine);
 }
+
+/// Save the engine in a catalog in a new temporary directory and load it back
+fn save_and_load(engine: &GameEngine) -> GameEngine {
+    let dir = tempfile::tempdir().unwrap();
+    let mut catalog = SavedGamesCatalog::new(dir.path().to_str().unwrap(), "test").unwrap();
+    let info = catalog.save("name", engine).unwrap();
+    catalog.load(&info).unwrap()
+}
+
+#[test]
+fn check_saved_engine_keeps_alliances() {
+    let (pl, _, mut engine) = create_valid_engine();
+    engine.set_alliances(vec![vec![pl[0].id(), pl[2].id()]]);
+
+    let loaded_engine = save_and_load(&engine);
+    assert_eq!(loaded_engine.alliances(), engine.alliances());
+    assert!(loaded_engine.are_allies(pl[2].id(), pl[0].id()));
+}
+
+#[test]
+fn check_units_aboard_boats_can_move_after_loading() {
+    let text = "players 2\norigin 0 0\n1V  1S  1O  ~~  2.  2V\n  1.  1.  ~~  ~~  2.  2.\n";
+    let mut builder = parse(text).unwrap();
+    builder.set_rules_variant(RulesVariant::Archipelago);
+    let mut engine = builder.build().unwrap();
+    let pl: Vec<_> = engine.players().iter().map(|p| p.id()).collect();
+    let (shore, sea) = (Coord::new(1, 0), Coord::new(2, 0));
+    engine
+        .act(pl[0], PlayerAction::MoveUnit { src: shore, dst: sea })
+        .unwrap();
+
+    let mut loaded_engine = save_and_load(&engine);
+    for &player_id in pl.iter() {
+        loaded_engine.act(player_id, PlayerAction::EndTurn).unwrap();
+    }
+    loaded_engine
+        .act(pl[0], PlayerAction::MoveUnit { src: sea, dst: shore })
+        .unwrap();
+    let unit = loaded_engine.location().tile_at(shore).unwrap().unit();
+    assert_eq!(unit.map(|u| u.unit_type()), Some(UnitType::Soldier));
+}
+
+#[test]
+fn check_saved_engine_keeps_experience_of_units() {
+    let text = "players 2\norigin 0 0\n1.  1V  1S  2M  2V  2.\n  1.  1.  1.  2.  2.  2.\n";
+    let mut engine = parse(text).unwrap().build().unwrap();
+    let pl: Vec<_> = engine.players().iter().map(|p| p.id()).collect();
+    let (src, dst) = (Coord::new(2, 0), Coord::new(3, 0));
+    engine
+        .act(pl[0], PlayerAction::MoveUnit { src, dst })
+        .unwrap();
+    let soldier_id = engine.location().tile_at(dst).unwrap().unit().unwrap().id();
+
+    // Soldier becomes a veteran after some turns in the field
+    while engine.unit_is_veteran(soldier_id) == Some(false) {
+        for &player_id in pl.iter() {
+            engine.act(player_id, PlayerAction::EndTurn).unwrap();
+        }
+    }
+
+    let loaded_engine = save_and_load(&engine);
+    assert_eq!(loaded_engine.unit_kills(soldier_id), Some(1));
+    assert_eq!(loaded_engine.unit_is_veteran(soldier_id), Some(true));
+}
+
+#[test]
+fn check_saved_engine_keeps_statistics() {
+    let (pl, _, mut engine) = create_valid_engine();
+    for _ in 0..2 {
+        for p in pl.iter() {
+            engine.act(p.id(), PlayerAction::EndTurn).unwrap();
+        }
+    }
+
+    let loaded_engine = save_and_load(&engine);
+    assert_eq!(loaded_engine.stats().turns().len(), 2);
+    assert_eq!(loaded_engine.stats(), engine.stats());
+}
+
+#[test]
+fn check_saved_engine_keeps_player_profiles() {
+    let (pl, _, mut engine) = create_valid_engine();
+    let bot = PlayerProfile {
+        name: "Random".to_owned(),
+        color: "#abcdef".to_owned(),
+        controller: Controller::Bot {
+            name: "random".to_owned(),
+            seed: 42,
+        },
+    };
+    engine.set_profiles(vec![(pl[1].id(), bot.clone())].into_iter().collect());
+
+    let loaded_engine = save_and_load(&engine);
+    assert_eq!(loaded_engine.profile(pl[1].id()), Some(&bot));
+    assert_eq!(
+        loaded_engine.profile(pl[0].id()).unwrap().controller,
+        Controller::Human
+    );
+}
+
+#[test]
+fn check_saved_engine_keeps_time_used_by_players() {
+    let (pl, _, mut engine) = create_valid_engine();
+    let control = TimeControl::ChessClock {
+        initial: 1000,
+        increment: 0,
+    };
+    engine.set_time_control(Some(control));
+    let time = ManualTimeSource::new(0);
+    engine.check_time(&time);
+    time.advance(400);
+    engine.check_time(&time);
+    engine.act(pl[0].id(), PlayerAction::EndTurn).unwrap();
+    time.advance(300);
+    engine.check_time(&time);
+
+    let mut loaded_engine = save_and_load(&engine);
+    assert_eq!(loaded_engine.clock().unwrap().control(), control);
+    assert_eq!(loaded_engine.time_left(pl[0].id()), Some(600));
+    assert_eq!(loaded_engine.time_left(pl[1].id()), Some(700));
+
+    // Time between saving and loading is not counted
+    time.advance(5000);
+    assert_eq!(loaded_engine.check_time(&time), None);
+    assert_eq!(loaded_engine.time_left(pl[1].id()), Some(700));
+}
+
+#[test]
+fn check_ratings_are_kept_with_saved_games() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut catalog = SavedGamesCatalog::new(dir.path().to_str().unwrap(), "test").unwrap();
+    assert_eq!(catalog.load_ratings().unwrap(), Ratings::default());
+
+    let mut ratings = Ratings::default();
+    ratings.record(&MatchResult {
+        places: vec![("alice".to_owned(), 1), ("bob".to_owned(), 2)],
+    });
+    catalog.save_ratings(&ratings).unwrap();
+    let (_, _, engine) = create_valid_engine();
+    catalog.save("name", &engine).unwrap();
+
+    let catalog = SavedGamesCatalog::new(dir.path().to_str().unwrap(), "test").unwrap();
+    assert_eq!(catalog.list_saved_games().len(), 1);
+    assert_eq!(catalog.load_ratings().unwrap(), ratings);
+}
@@ -0,0 +1,135 @@
+extern crate tempfile;
+extern crate yasc;
+
+use yasc::campaign::{Campaign, CampaignError, CampaignStage};
+use yasc::game::{Coord, PlayerAction};
+use yasc::init::ascii::parse;
+use yasc::saves::SavedGamesCatalog;
+use yasc::scenario::{Objective, Scenario, ScenarioStatus, ScriptedAction};
+
+fn create_scenario() -> Scenario {
+    let text = "players 2\norigin 0 0\n1V  1S  1.  2.  2S  2V  2.\n";
+    let engine = parse(text).unwrap().build().unwrap();
+    let player_id = engine.players()[0].id();
+    let opponent_id = engine.players()[1].id();
+    let mut scenario = Scenario::new(
+        engine,
+        player_id,
+        vec![Objective::CaptureTile(Coord::new(5, 0))],
+    );
+    scenario.set_turn_limit(2);
+    scenario.set_script(vec![
+        ScriptedAction {
+            player_id: opponent_id,
+            action: PlayerAction::MoveUnit {
+                src: Coord::new(4, 0),
+                dst: Coord::new(6, 0),
+            },
+        },
+        ScriptedAction {
+            player_id: opponent_id,
+            action: PlayerAction::EndTurn,
+        },
+    ]);
+    scenario
+}
+
+#[test]
+fn check_saved_scenario_is_recoverable() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("scenario.yaml");
+
+    let scenario = create_scenario();
+    scenario.save(&path).unwrap();
+
+    let loaded_scenario = Scenario::load(&path).unwrap();
+    assert_eq!(loaded_scenario, scenario);
+}
+
+#[test]
+fn check_loaded_scenario_follows_script() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("scenario.yaml");
+    create_scenario().save(&path).unwrap();
+
+    let mut scenario = Scenario::load(&path).unwrap();
+    assert_eq!(
+        scenario.act(PlayerAction::EndTurn),
+        Ok(ScenarioStatus::InProgress)
+    );
+    let location = scenario.engine().location();
+    assert!(location.tile_at(Coord::new(4, 0)).unwrap().unit().is_none());
+    assert!(location.tile_at(Coord::new(6, 0)).unwrap().unit().is_some());
+
+    assert_eq!(
+        scenario.act(PlayerAction::EndTurn),
+        Ok(ScenarioStatus::Failed)
+    );
+}
+
+#[test]
+fn check_loading_missing_scenario_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(Scenario::load(dir.path().join("missing.yaml")).is_err());
+}
+
+fn create_campaign() -> Campaign {
+    let stages = vec![
+        CampaignStage {
+            name: "first".to_owned(),
+            scenario: create_scenario(),
+            requires: Vec::new(),
+            bonus_money: 10,
+        },
+        CampaignStage {
+            name: "second".to_owned(),
+            scenario: create_scenario(),
+            requires: vec!["first".to_owned()],
+            bonus_money: 0,
+        },
+    ];
+    Campaign::new("campaign", stages).unwrap()
+}
+
+#[test]
+fn check_saved_campaign_is_recoverable() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("campaign.yaml");
+
+    let campaign = create_campaign();
+    campaign.save(&path).unwrap();
+
+    let loaded_campaign = Campaign::load(&path).unwrap();
+    assert_eq!(loaded_campaign, campaign);
+}
+
+#[test]
+fn check_campaign_progress_is_kept_in_saves_catalog() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut catalog = SavedGamesCatalog::new(dir.path().to_str().unwrap(), "campaign").unwrap();
+
+    let mut campaign = create_campaign();
+    let mut scenario = campaign.start("first").unwrap();
+    scenario.act(PlayerAction::EndTurn).unwrap();
+    scenario.act(PlayerAction::EndTurn).unwrap();
+    campaign.finish("first", &scenario).unwrap();
+    let info = catalog.save_campaign("player", campaign.progress()).unwrap();
+
+    let catalog = SavedGamesCatalog::new(dir.path().to_str().unwrap(), "campaign").unwrap();
+    assert!(catalog.list_saved_games().is_empty());
+    assert_eq!(catalog.list_saved_campaigns(), &vec![info.clone()]);
+    assert!(catalog.load(&info).is_err());
+    let progress = catalog.load_campaign(&info).unwrap();
+    assert_eq!(&progress, campaign.progress());
+    assert_eq!(progress.result("first"), Some(ScenarioStatus::Failed));
+
+    let mut loaded_campaign = create_campaign();
+    loaded_campaign.set_progress(progress.clone()).unwrap();
+    assert_eq!(loaded_campaign.available_stages(), vec!["first"]);
+
+    let mut other_campaign = Campaign::new("other", campaign.stages().clone()).unwrap();
+    assert_eq!(
+        other_campaign.set_progress(progress),
+        Err(CampaignError::OtherCampaign("campaign".to_owned()))
+    );
+}
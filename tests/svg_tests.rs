@@ -0,0 +1,23 @@
+extern crate yasc;
+
+use yasc::init::ascii::parse;
+use yasc::svg::{render_engine, SvgOptions};
+
+const MAP: &str = "players 2
+origin 0 0
+1V  1.  2K
+  1M  2.  2V
+";
+
+#[test]
+fn check_svg_matches_snapshot() {
+    let engine = parse(MAP).unwrap().build().unwrap();
+    let options = SvgOptions {
+        coordinate_labels: true,
+        ..SvgOptions::default()
+    };
+
+    let svg = render_engine(&engine, &options);
+
+    assert_eq!(svg, include_str!("snapshots/small_map.svg"));
+}